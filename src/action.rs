@@ -0,0 +1,283 @@
+use pdfstring::{ascii_string, text_string};
+use rect::Rect;
+use std::io;
+use PageInfo;
+
+/// How to show the page of a [Destination](enum.Destination.html), as
+/// described in section 12.3.2.2 of the PDF specification.
+///
+/// Coordinates are given in the coordinate system of the destination
+/// page, including its [Origin](enum.Origin.html).
+/// A `None` value keeps the current value of the viewer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zoom {
+    /// Fit the whole page in the window.
+    FitPage,
+    /// Fit the width of the page in the window, with the given y
+    /// coordinate at the top of the window.
+    FitWidth(Option<f32>),
+    /// Fit the height of the page in the window, with the given x
+    /// coordinate at the left edge of the window.
+    FitHeight(Option<f32>),
+    /// Show the point (x, y) at the top left corner of the window,
+    /// magnified by a zoom factor (where 1.0 is 100 %).
+    XYZ(Option<f32>, Option<f32>, Option<f32>),
+    /// Fit a rectangle of the page in the window.
+    FitRect(Rect),
+}
+
+/// A place in the document, to go to from a link.
+///
+/// Pages are given by their index in the document, starting at 0.
+/// The page doesn't have to exist yet when the destination is
+/// created, it is resolved when the document is finished.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Destination {
+    /// A page, shown as given by the zoom.
+    Page(usize, Zoom),
+    /// A named destination, as added by
+    /// [Pdf::add_named_destination](struct.Pdf.html#method.add_named_destination)
+    /// (or defined in another document).
+    Named(String),
+}
+
+impl Destination {
+    /// The top of a page, keeping the current zoom.
+    pub fn page(page: usize) -> Self {
+        Destination::Page(page, Zoom::XYZ(None, None, None))
+    }
+
+    /// The point (x, y) on a page, at the top left corner of the
+    /// window, keeping the current zoom.
+    pub fn at(page: usize, x: f32, y: f32) -> Self {
+        Destination::Page(page, Zoom::XYZ(Some(x), Some(y), None))
+    }
+
+    /// The destination as a pdf array (or name), with the page
+    /// resolved.
+    pub(crate) fn to_pdf(&self, pages: &[PageInfo]) -> io::Result<String> {
+        match *self {
+            Destination::Named(ref name) => Ok(ascii_string(name)),
+            Destination::Page(index, zoom) => {
+                let page = pages.get(index).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Destination on page {}, but the document has \
+                             {} pages",
+                            index,
+                            pages.len(),
+                        ),
+                    )
+                })?;
+                let view =
+                    zoom.to_pdf(|y| page.flip_height.map_or(y, |h| h - y));
+                Ok(format!("[{} 0 R {}]", page.object_id, view))
+            }
+        }
+    }
+
+    /// The destination as a pdf array (or name), in another document.
+    fn to_remote_pdf(&self) -> String {
+        match *self {
+            Destination::Named(ref name) => ascii_string(name),
+            Destination::Page(index, zoom) => {
+                format!("[{} {}]", index, zoom.to_pdf(|y| y))
+            }
+        }
+    }
+}
+
+impl Zoom {
+    /// The zoom as the view part of a destination array, with y
+    /// coordinates converted by `y`.
+    fn to_pdf<F>(self, y: F) -> String
+    where
+        F: Fn(f32) -> f32,
+    {
+        match self {
+            Zoom::FitPage => "/Fit".to_string(),
+            Zoom::FitWidth(top) => format!("/FitH {}", number(top.map(&y))),
+            Zoom::FitHeight(left) => format!("/FitV {}", number(left)),
+            Zoom::XYZ(left, top, zoom) => format!(
+                "/XYZ {} {} {}",
+                number(left),
+                number(top.map(&y)),
+                number(zoom),
+            ),
+            Zoom::FitRect(r) => {
+                let (y1, y2) = (y(r.y), y(r.top()));
+                format!(
+                    "/FitR {} {} {} {}",
+                    r.x,
+                    y1.min(y2),
+                    r.right(),
+                    y1.max(y2),
+                )
+            }
+        }
+    }
+}
+
+/// Something to do when a link is clicked, as described in section
+/// 12.6 of the PDF specification.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// Go to a destination in this document.
+    GoTo(Destination),
+    /// Open another pdf file, given by its path relative to this
+    /// document, at a destination in it.
+    ///
+    /// Pages of the other document are given by their index, starting
+    /// at 0, and coordinates in the default (bottom left) origin.
+    GoToRemote(String, Destination),
+    /// Open a file, or run an application, given by its path relative
+    /// to this document.
+    ///
+    /// Note that the effect depends on the viewer and the operating
+    /// system.  Most viewers ask the user for permission first, and
+    /// many refuse to launch applications at all, since it is a common
+    /// way to attack users with malicious documents.  Don't rely on it
+    /// in documents for a general audience.
+    Launch(String),
+    /// Open a URI, typically a web page.
+    Uri(String),
+    /// Run a JavaScript script, in viewers that support it.
+    JavaScript(String),
+    /// Send the values of the form fields of the document to a URL,
+    /// in the given format.
+    SubmitForm(String, SubmitFormat),
+    /// Reset the form fields with the given names to their default
+    /// values, or all fields of the document if no names are given.
+    ResetForm(Vec<String>),
+}
+
+/// The format to submit form data in, see
+/// [Action::SubmitForm](enum.Action.html#variant.SubmitForm).
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Action, FormField, Pdf, Rect, SubmitFormat};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// let submit = Action::SubmitForm(
+///     "https://example.com/order".to_string(),
+///     SubmitFormat::Html,
+/// );
+/// let rect = Rect::new(10.0, 10.0, 60.0, 20.0);
+/// canvas.add_field(FormField::push_button("submit", rect, "Send", submit))?;
+/// // A button to clear the form.
+/// let reset = Action::ResetForm(Vec::new());
+/// let rect = Rect::new(80.0, 10.0, 60.0, 20.0);
+/// canvas.add_field(FormField::push_button("clear", rect, "Clear", reset))
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmitFormat {
+    /// Forms Data Format, the native format of pdf forms.
+    Fdf,
+    /// XFDF, the XML version of FDF.
+    Xfdf,
+    /// HTML form submission, as `application/x-www-form-urlencoded`.
+    Html,
+    /// The whole pdf document, with the filled in fields.
+    Pdf,
+}
+
+impl Default for SubmitFormat {
+    fn default() -> Self {
+        SubmitFormat::Fdf
+    }
+}
+
+impl SubmitFormat {
+    /// The flags of the submit-form action for this format.
+    fn flags(self) -> u32 {
+        match self {
+            SubmitFormat::Fdf => 0,
+            // ExportFormat
+            SubmitFormat::Html => 1 << 2,
+            // XFDF
+            SubmitFormat::Xfdf => 1 << 5,
+            // SubmitPDF
+            SubmitFormat::Pdf => 1 << 8,
+        }
+    }
+}
+
+impl Action {
+    /// The action as a pdf dictionary, with any page resolved.
+    pub(crate) fn to_pdf(&self, pages: &[PageInfo]) -> io::Result<String> {
+        Ok(match *self {
+            Action::GoTo(ref destination) => {
+                format!("<< /S /GoTo /D {} >>", destination.to_pdf(pages)?)
+            }
+            Action::GoToRemote(ref file, ref destination) => format!(
+                "<< /S /GoToR /F {} /D {} >>",
+                ascii_string(file),
+                destination.to_remote_pdf(),
+            ),
+            Action::Launch(ref file) => {
+                format!("<< /S /Launch /F {} >>", ascii_string(file))
+            }
+            Action::Uri(ref uri) => {
+                format!("<< /S /URI /URI {} >>", ascii_string(uri))
+            }
+            Action::JavaScript(ref script) => {
+                format!("<< /S /JavaScript /JS {} >>", text_string(script))
+            }
+            Action::SubmitForm(ref url, format) => format!(
+                "<< /S /SubmitForm /F << /FS /URL /F {} >> /Flags {} >>",
+                ascii_string(url),
+                format.flags(),
+            ),
+            Action::ResetForm(ref fields) => {
+                if fields.is_empty() {
+                    "<< /S /ResetForm >>".to_string()
+                } else {
+                    let names = fields
+                        .iter()
+                        .map(|name| format!(" {}", text_string(name)))
+                        .collect::<String>();
+                    format!("<< /S /ResetForm /Fields [{} ] >>", names)
+                }
+            }
+        })
+    }
+}
+
+impl From<Destination> for Action {
+    fn from(destination: Destination) -> Self {
+        Action::GoTo(destination)
+    }
+}
+
+fn number(value: Option<f32>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
+
+#[test]
+fn test_remote_destination() {
+    let volume = Action::GoToRemote(
+        "volume2.pdf".to_string(),
+        Destination::Page(3, Zoom::FitWidth(Some(700.0))),
+    );
+    assert_eq!(
+        "<< /S /GoToR /F (volume2.pdf) /D [3 /FitH 700] >>",
+        volume.to_pdf(&[]).unwrap(),
+    );
+}
+
+#[test]
+fn test_reset_form() {
+    let all = Action::ResetForm(Vec::new());
+    assert_eq!("<< /S /ResetForm >>", all.to_pdf(&[]).unwrap());
+    let some =
+        Action::ResetForm(vec!["name".to_string(), "date".to_string()]);
+    assert_eq!(
+        "<< /S /ResetForm /Fields [ (name) (date) ] >>",
+        some.to_pdf(&[]).unwrap(),
+    );
+}
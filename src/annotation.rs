@@ -0,0 +1,1360 @@
+use action::Action;
+use canvas::{render_content, Canvas};
+use embeddedfile::EmbeddedFile;
+use fontsource::{BuiltinFont, FontSource};
+use formfield::FormField;
+use formxobject::FormXObject;
+use graphicsstate::Color;
+use measure::MeasureScale;
+use pdfstring::{name, text_string};
+use rect::Rect;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use PageInfo;
+
+/// An annotation, an interactive object placed on a page, as
+/// described in section 12.5 of the PDF specification.
+///
+/// Add an annotation to a page with
+/// [Canvas::add_annotation](struct.Canvas.html#method.add_annotation).
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Action, Annotation, Destination, Pdf, Rect};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.render_page(180.0, 240.0, |canvas| {
+///     // A link to the next page, which is not rendered yet.
+///     let next = Action::GoTo(Destination::page(1));
+///     canvas.add_annotation(Annotation::link(
+///         Rect::new(140.0, 10.0, 30.0, 20.0),
+///         next,
+///     ));
+///     Ok(())
+/// }).unwrap();
+/// document.render_page(180.0, 240.0, |_canvas| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Annotation {
+    rect: Rect,
+    kind: AnnotationKind,
+    contents: Option<String>,
+    author: Option<String>,
+    style: AnnotationStyle,
+    appearance: Option<FormXObject>,
+    popup: Option<(Rect, bool)>,
+}
+
+/// The border, color and flags of an annotation, see
+/// [Annotation::with_style](struct.Annotation.html#method.with_style).
+///
+/// The default style has no border or color set (used as given by the
+/// kind of annotation), and the [default flags](struct.AnnotationFlags.html).
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Annotation, AnnotationFlags, AnnotationStyle, Pdf, Rect};
+/// # use pdf_canvas::graphicsstate::Color;
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// let style = AnnotationStyle {
+///     border_width: Some(2.0),
+///     border_dash: vec![4.0, 2.0],
+///     color: Some(Color::rgb(0, 0, 255)),
+///     flags: AnnotationFlags {
+///         locked: true,
+///         ..AnnotationFlags::default()
+///     },
+/// };
+/// let area = Annotation::square(Rect::new(10.0, 10.0, 100.0, 60.0))
+///     .with_style(style);
+/// canvas.add_annotation(area);
+/// # Ok(())
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnnotationStyle {
+    /// The width of the border, 0 for no border.
+    pub border_width: Option<f32>,
+    /// The lengths of alternating dashes and gaps of the border, or
+    /// empty for a solid border.
+    pub border_dash: Vec<f32>,
+    /// The color of the annotation, used e.g. for the border, the icon
+    /// of a note and the marking of a text markup annotation.
+    pub color: Option<Color>,
+    /// The flags of the annotation.
+    pub flags: AnnotationFlags,
+}
+
+/// Flags of an annotation, as described in section 12.5.3 of the PDF
+/// specification.
+///
+/// By default, only `print` is set, so annotations are printed with
+/// the page they are on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnnotationFlags {
+    /// Don't show or print the annotation.
+    pub hidden: bool,
+    /// Print the annotation when the page is printed.
+    pub print: bool,
+    /// Keep the size of the annotation when the page is zoomed.
+    pub no_zoom: bool,
+    /// Keep the annotation upright when the page is rotated.
+    pub no_rotate: bool,
+    /// Don't show the annotation on screen (but still print it if
+    /// `print` is set).
+    pub no_view: bool,
+    /// Don't let the user interact with the annotation.
+    pub read_only: bool,
+    /// Don't let the user move, resize or delete the annotation.
+    pub locked: bool,
+    /// Don't let the user change the contents of the annotation.
+    pub locked_contents: bool,
+}
+
+impl Default for AnnotationFlags {
+    fn default() -> Self {
+        AnnotationFlags {
+            hidden: false,
+            print: true,
+            no_zoom: false,
+            no_rotate: false,
+            no_view: false,
+            read_only: false,
+            locked: false,
+            locked_contents: false,
+        }
+    }
+}
+
+impl AnnotationFlags {
+    /// The flags as the value of the /F entry of an annotation.
+    fn bits(self) -> u32 {
+        let flags = [
+            (self.hidden, 1 << 1),
+            (self.print, 1 << 2),
+            (self.no_zoom, 1 << 3),
+            (self.no_rotate, 1 << 4),
+            (self.no_view, 1 << 5),
+            (self.read_only, 1 << 6),
+            (self.locked, 1 << 7),
+            (self.locked_contents, 1 << 9),
+        ];
+        flags
+            .iter()
+            .filter(|&&(set, _)| set)
+            .map(|&(_, bit)| bit)
+            .sum()
+    }
+}
+
+#[derive(Clone)]
+enum AnnotationKind {
+    Link(Action),
+    Text {
+        icon: NoteIcon,
+        open: bool,
+    },
+    Markup(MarkupKind, Vec<Rect>),
+    Shape {
+        circle: bool,
+        interior: Option<Color>,
+    },
+    FreeText {
+        font: BuiltinFont,
+        size: f32,
+        text_color: Color,
+        callout: Vec<(f32, f32)>,
+    },
+    Stamp(Option<StampName>),
+    FileAttachment(EmbeddedFile, AttachmentIcon),
+    Ink(Vec<Vec<(f32, f32)>>),
+    Measure {
+        points: Vec<(f32, f32)>,
+        area: bool,
+        scale: MeasureScale,
+    },
+    Redact {
+        areas: Vec<Rect>,
+        interior: Option<Color>,
+        overlay: Option<String>,
+    },
+    Widget {
+        field: Arc<FormField>,
+        state: Option<String>,
+        appearances: Vec<(String, FormXObject)>,
+    },
+}
+
+/// The icon of a file attachment, see
+/// [Annotation::file_attachment](struct.Annotation.html#method.file_attachment).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachmentIcon {
+    /// A push pin.
+    PushPin,
+    /// A paperclip.
+    Paperclip,
+    /// A chart.
+    Graph,
+    /// A tag.
+    Tag,
+}
+
+impl Default for AttachmentIcon {
+    fn default() -> Self {
+        AttachmentIcon::PushPin
+    }
+}
+
+/// Objects that are written before an annotation, and referenced by
+/// it.
+#[derive(Clone, Debug, Default)]
+pub struct AnnotationRefs {
+    /// The normal appearance form.
+    pub appearance: Option<usize>,
+    /// The normal appearance forms of each state of a widget.
+    pub appearance_states: Vec<(String, usize)>,
+    /// The file specification of an attached file.
+    pub file: Option<usize>,
+    /// The field of a widget.
+    pub parent: Option<usize>,
+    /// The popup annotation showing the contents.
+    pub popup: Option<usize>,
+}
+
+/// The predefined rubber stamps, see
+/// [Annotation::stamp](struct.Annotation.html#method.stamp).
+///
+/// How each stamp looks is up to the viewer.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StampName {
+    Approved,
+    Experimental,
+    NotApproved,
+    AsIs,
+    Expired,
+    NotForPublicRelease,
+    Confidential,
+    Final,
+    Sold,
+    Departmental,
+    ForComment,
+    TopSecret,
+    Draft,
+    ForPublicRelease,
+}
+
+/// The kinds of text markup annotations, see
+/// [Annotation::markup](struct.Annotation.html#method.markup).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkupKind {
+    /// Highlight the text, as with a marker pen.
+    Highlight,
+    /// Underline the text.
+    Underline,
+    /// Underline the text with a wavy line.
+    Squiggly,
+    /// Strike out the text.
+    StrikeOut,
+}
+
+/// The icon of a sticky note, see
+/// [Annotation::note](struct.Annotation.html#method.note).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteIcon {
+    /// A speech bubble.
+    Comment,
+    /// A key.
+    Key,
+    /// A sticky note.
+    Note,
+    /// A question mark.
+    Help,
+    /// A new paragraph symbol.
+    NewParagraph,
+    /// A paragraph symbol.
+    Paragraph,
+    /// An insertion caret.
+    Insert,
+}
+
+impl Default for NoteIcon {
+    fn default() -> Self {
+        NoteIcon::Comment
+    }
+}
+
+impl Annotation {
+    /// Create a link, a rectangle that performs `action` when clicked.
+    ///
+    /// The link is invisible, draw whatever should show that it is
+    /// there on the canvas.
+    pub fn link(rect: Rect, action: Action) -> Self {
+        Annotation::new(rect, AnnotationKind::Link(action))
+    }
+
+    /// Create a sticky note, shown as an icon at the top left of
+    /// `rect`, that opens a window with `contents` when clicked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, NoteIcon, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let note = Annotation::note(
+    ///     Rect::new(150.0, 200.0, 20.0, 20.0),
+    ///     NoteIcon::Comment,
+    ///     "Check these numbers",
+    /// );
+    /// canvas.add_annotation(note.with_author("Reviewer").with_open(true));
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn note(rect: Rect, icon: NoteIcon, contents: &str) -> Self {
+        Annotation::new(rect, AnnotationKind::Text { icon, open: false })
+            .with_contents(contents)
+    }
+
+    /// Create a text markup annotation, such as a highlight, on the
+    /// given areas of text (typically one for each line).
+    ///
+    /// The color defaults to yellow for highlights and red for the
+    /// others, use [with_color](#method.with_color) for another color.
+    /// Return None if `areas` is empty.
+    ///
+    /// Use [Canvas::text_bounds](struct.Canvas.html#method.text_bounds)
+    /// to get the area of a text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, BuiltinFont, MarkupKind, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let font = BuiltinFont::Times_Roman;
+    /// let text = "An important sentence.";
+    /// canvas.left_text(10.0, 200.0, font, 12.0, text)?;
+    /// let area = canvas.text_bounds(10.0, 200.0, font, 12.0, text);
+    /// let highlight = Annotation::markup(MarkupKind::Highlight, &[area])
+    ///     .unwrap()
+    ///     .with_contents("Is this true?");
+    /// canvas.add_annotation(highlight);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn markup(kind: MarkupKind, areas: &[Rect]) -> Option<Self> {
+        let (first, rest) = areas.split_first()?;
+        let rect = rest.iter().fold(*first, |r, area| r.union(area));
+        let color = match kind {
+            MarkupKind::Highlight => Color::rgb(255, 255, 0),
+            _ => Color::rgb(255, 0, 0),
+        };
+        Some(
+            Annotation::new(
+                rect,
+                AnnotationKind::Markup(kind, areas.to_vec()),
+            )
+            .with_color(color),
+        )
+    }
+
+    /// Create a redaction annotation, marking the given areas for
+    /// removal.
+    ///
+    /// The content under the areas is not removed here, but by a
+    /// viewer that supports redaction, when the user applies it.
+    /// Set the color to fill the areas with after the redaction by
+    /// [with_interior_color](#method.with_interior_color), and a text
+    /// to show on them by [with_overlay_text](#method.with_overlay_text).
+    /// Return None if `areas` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, BuiltinFont, Pdf};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let font = BuiltinFont::Courier;
+    /// canvas.left_text(10.0, 200.0, font, 10.0, "Account: 1234-5678")?;
+    /// let area = canvas.text_bounds(64.0, 200.0, font, 10.0, "1234-5678");
+    /// let redaction = Annotation::redact(&[area])
+    ///     .unwrap()
+    ///     .with_interior_color(Color::gray(0))
+    ///     .with_overlay_text("REDACTED");
+    /// canvas.add_annotation(redaction);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn redact(areas: &[Rect]) -> Option<Self> {
+        let (first, rest) = areas.split_first()?;
+        let rect = rest.iter().fold(*first, |r, area| r.union(area));
+        Some(Annotation::new(
+            rect,
+            AnnotationKind::Redact {
+                areas: areas.to_vec(),
+                interior: None,
+                overlay: None,
+            },
+        ))
+    }
+
+    /// Set the text to show on the areas of a redaction annotation,
+    /// once it is applied.
+    /// This has no effect on other kinds of annotations.
+    pub fn with_overlay_text(mut self, text: &str) -> Self {
+        if let AnnotationKind::Redact {
+            ref mut overlay, ..
+        } = self.kind
+        {
+            *overlay = Some(text.to_string());
+        }
+        self
+    }
+
+    /// Create a rectangle annotation, drawn inside `rect`.
+    ///
+    /// The border color is set by [with_color](#method.with_color),
+    /// and the rectangle can be filled by
+    /// [with_interior_color](#method.with_interior_color).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, Pdf, Rect};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let problem = Annotation::square(Rect::new(20.0, 100.0, 60.0, 40.0))
+    ///     .with_color(Color::rgb(255, 0, 0))
+    ///     .with_border_width(2.0)
+    ///     .with_contents("Misaligned");
+    /// canvas.add_annotation(problem);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn square(rect: Rect) -> Self {
+        Annotation::new(
+            rect,
+            AnnotationKind::Shape {
+                circle: false,
+                interior: None,
+            },
+        )
+    }
+
+    /// Create an ellipse annotation, drawn inside `rect`.
+    ///
+    /// See [square](#method.square).
+    pub fn circle(rect: Rect) -> Self {
+        Annotation::new(
+            rect,
+            AnnotationKind::Shape {
+                circle: true,
+                interior: None,
+            },
+        )
+    }
+
+    /// Create a free text annotation, showing `text` directly on the
+    /// page in `rect`, still editable in viewers.
+    ///
+    /// The text is set in `font` and `size`, in `color`.
+    /// Note that this relies on the viewer to show the text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, BuiltinFont, Pdf, Rect};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let comment = Annotation::free_text(
+    ///     Rect::new(90.0, 180.0, 80.0, 30.0),
+    ///     "Use the new logo here",
+    ///     BuiltinFont::Helvetica,
+    ///     10.0,
+    ///     Color::rgb(0, 0, 160),
+    /// )
+    /// .with_callout(&[(40.0, 120.0), (90.0, 195.0)]);
+    /// canvas.add_annotation(comment);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn free_text(
+        rect: Rect,
+        text: &str,
+        font: BuiltinFont,
+        size: f32,
+        color: Color,
+    ) -> Self {
+        Annotation::new(
+            rect,
+            AnnotationKind::FreeText {
+                font,
+                size,
+                text_color: color,
+                callout: Vec::new(),
+            },
+        )
+        .with_contents(text)
+    }
+
+    /// Add a callout line to a free text annotation, from the first
+    /// point (where the line points to) to the last (at the text).
+    /// The line has two or three points, others are ignored.
+    /// This has no effect on other kinds of annotations.
+    pub fn with_callout(mut self, points: &[(f32, f32)]) -> Self {
+        if let AnnotationKind::FreeText {
+            ref mut callout, ..
+        } = self.kind
+        {
+            if points.len() == 2 || points.len() == 3 {
+                *callout = points.to_vec();
+            }
+        }
+        self
+    }
+
+    /// Create an ink annotation, freehand drawing with one or more
+    /// strokes, each given by its points.
+    ///
+    /// The line width and color are set by
+    /// [with_border_width](#method.with_border_width) and
+    /// [with_color](#method.with_color).
+    /// Return None if there are no points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, Pdf};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // A captured signature, with the pen lifted once.
+    /// let strokes = vec![
+    ///     vec![(20.0, 30.0), (30.0, 45.0), (40.0, 28.0), (50.0, 40.0)],
+    ///     vec![(55.0, 30.0), (70.0, 32.0)],
+    /// ];
+    /// let signature = Annotation::ink(&strokes)
+    ///     .unwrap()
+    ///     .with_color(Color::rgb(0, 0, 128))
+    ///     .with_border_width(1.5);
+    /// canvas.add_annotation(signature);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn ink(strokes: &[Vec<(f32, f32)>]) -> Option<Self> {
+        let points = strokes.iter().flatten().cloned().collect::<Vec<_>>();
+        if points.is_empty() {
+            return None;
+        }
+        Some(Annotation::new(
+            points_bounds(&points, 2.0),
+            AnnotationKind::Ink(strokes.to_vec()),
+        ))
+    }
+
+    /// Create a line annotation that measures the distance from
+    /// `start` to `end` in `scale`, for viewers that support
+    /// measuring.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, MeasureScale, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // A wall in a floor plan, drawn at 1:50.
+    /// canvas.line(20.0, 100.0, 160.0, 100.0)?;
+    /// canvas.stroke()?;
+    /// let scale = MeasureScale::metric(50.0);
+    /// let length =
+    ///     Annotation::distance((20.0, 110.0), (160.0, 110.0), scale);
+    /// canvas.add_annotation(length);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn distance(
+        start: (f32, f32),
+        end: (f32, f32),
+        scale: MeasureScale,
+    ) -> Self {
+        Annotation::measure(vec![start, end], false, scale)
+    }
+
+    /// Create a polygon annotation that measures the area inside
+    /// `points` in `scale`, for viewers that support measuring.
+    /// Return None if there are less than three points.
+    pub fn area(points: &[(f32, f32)], scale: MeasureScale) -> Option<Self> {
+        if points.len() < 3 {
+            return None;
+        }
+        Some(Annotation::measure(points.to_vec(), true, scale))
+    }
+
+    fn measure(
+        points: Vec<(f32, f32)>,
+        area: bool,
+        scale: MeasureScale,
+    ) -> Self {
+        // Leave room for the line width and the caption.
+        let rect = points_bounds(&points, 10.0);
+        Annotation::new(
+            rect,
+            AnnotationKind::Measure {
+                points,
+                area,
+                scale,
+            },
+        )
+    }
+
+    /// Create a rubber stamp annotation, with one of the predefined
+    /// stamps of the viewer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, Pdf, Rect, StampName};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let rect = Rect::new(100.0, 200.0, 70.0, 30.0);
+    /// canvas.add_annotation(Annotation::stamp(rect, StampName::Approved));
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn stamp(rect: Rect, name: StampName) -> Self {
+        Annotation::new(rect, AnnotationKind::Stamp(Some(name)))
+    }
+
+    /// Create a rubber stamp annotation that looks as drawn by
+    /// `render`, so it is the same in all viewers.
+    ///
+    /// The stamp is drawn on a canvas from (0, 0) to the size of
+    /// `rect`, with the y axis pointing up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, BuiltinFont, Pdf, Rect};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let rect = Rect::new(100.0, 200.0, 70.0, 30.0);
+    /// let checked = Annotation::custom_stamp(rect, |c| {
+    ///     c.set_stroke_color(Color::rgb(0, 128, 0))?;
+    ///     c.set_line_width(2.0)?;
+    ///     c.rectangle(1.0, 1.0, 68.0, 28.0)?;
+    ///     c.stroke()?;
+    ///     c.set_fill_color(Color::rgb(0, 128, 0))?;
+    ///     c.center_text(35.0, 10.0, BuiltinFont::Helvetica_Bold, 12.0, "CHECKED")
+    /// })?;
+    /// canvas.add_annotation(checked.with_author("QA"));
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn custom_stamp<F>(rect: Rect, render: F) -> io::Result<Self>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        Annotation::new(rect, AnnotationKind::Stamp(None))
+            .with_appearance(render)
+    }
+
+    /// Create a file attachment annotation, showing `icon` at `rect`,
+    /// that opens (or saves) `file` when double-clicked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, AttachmentIcon, EmbeddedFile, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let csv = EmbeddedFile::new("sales.csv", b"month,sales\nJan,17\n".to_vec())
+    ///     .with_mime_type("text/csv")
+    ///     .with_description("The data behind the chart");
+    /// let rect = Rect::new(160.0, 220.0, 12.0, 16.0);
+    /// let attachment =
+    ///     Annotation::file_attachment(rect, csv, AttachmentIcon::Paperclip);
+    /// canvas.add_annotation(attachment);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn file_attachment(
+        rect: Rect,
+        file: EmbeddedFile,
+        icon: AttachmentIcon,
+    ) -> Self {
+        let contents = file.name().to_string();
+        Annotation::new(rect, AnnotationKind::FileAttachment(file, icon))
+            .with_contents(&contents)
+    }
+
+    /// The attached file of the annotation, if any.
+    pub(crate) fn file(&self) -> Option<&EmbeddedFile> {
+        match self.kind {
+            AnnotationKind::FileAttachment(ref file, _) => Some(file),
+            _ => None,
+        }
+    }
+
+    /// Create a widget annotation for `field`, with the on state
+    /// `state`, if it has one.
+    pub(crate) fn widget(
+        rect: Rect,
+        field: Arc<FormField>,
+        state: Option<String>,
+    ) -> io::Result<Self> {
+        let appearances = field.widget_appearances(
+            rect.width,
+            rect.height,
+            state.as_deref(),
+        )?;
+        let appearance = field.widget_appearance(rect.width, rect.height)?;
+        let mut widget = Annotation::new(
+            rect,
+            AnnotationKind::Widget {
+                field,
+                state,
+                appearances,
+            },
+        );
+        widget.appearance = appearance;
+        Ok(widget)
+    }
+
+    /// The field of a widget annotation, if this is one.
+    pub(crate) fn field(&self) -> Option<&Arc<FormField>> {
+        match self.kind {
+            AnnotationKind::Widget { ref field, .. } => Some(field),
+            _ => None,
+        }
+    }
+
+    /// The position of the annotation in the tab order of its page, if
+    /// set.
+    pub(crate) fn tab_index(&self) -> Option<u32> {
+        self.field().and_then(|field| field.tab_index())
+    }
+
+    /// The forms of each appearance state of the annotation, if it
+    /// has several.
+    pub(crate) fn appearance_states(&self) -> &[(String, FormXObject)] {
+        match self.kind {
+            AnnotationKind::Widget {
+                ref appearances, ..
+            } => appearances,
+            _ => &[],
+        }
+    }
+
+    /// Set the normal appearance of the annotation, drawn by `render`,
+    /// so it looks the same in all viewers.
+    ///
+    /// The appearance is drawn on a canvas from (0, 0) to the size of
+    /// the annotation, with the y axis pointing up, and replaces the
+    /// appearance the viewer would otherwise create.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, NoteIcon, Pdf, Rect};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // A round, red badge instead of the icon of the viewer.
+    /// let rect = Rect::new(150.0, 210.0, 16.0, 16.0);
+    /// let badge = Annotation::note(rect, NoteIcon::Comment, "Check this")
+    ///     .with_appearance(|c| {
+    ///         c.set_fill_color(Color::rgb(200, 0, 0))?;
+    ///         c.circle(8.0, 8.0, 8.0)?;
+    ///         c.fill()
+    ///     })?;
+    /// canvas.add_annotation(badge);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn with_appearance<F>(mut self, render: F) -> io::Result<Self>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let form =
+            appearance_form(self.rect.width, self.rect.height, render)?;
+        self.appearance = Some(form);
+        Ok(self)
+    }
+
+    /// The form to use as the normal appearance of the annotation, if
+    /// any.
+    pub(crate) fn appearance(&self) -> Option<&FormXObject> {
+        self.appearance.as_ref()
+    }
+
+    /// The font used in the default appearance of the annotation, if
+    /// any.
+    pub(crate) fn default_font(&self) -> Option<BuiltinFont> {
+        match self.kind {
+            AnnotationKind::FreeText { font, .. } => Some(font),
+            AnnotationKind::Redact {
+                overlay: Some(_), ..
+            } => Some(REDACT_FONT),
+            AnnotationKind::Widget { ref field, .. } => field.text_font(),
+            _ => None,
+        }
+    }
+
+    fn new(rect: Rect, kind: AnnotationKind) -> Self {
+        Annotation {
+            rect,
+            kind,
+            contents: None,
+            author: None,
+            style: AnnotationStyle::default(),
+            appearance: None,
+            popup: None,
+        }
+    }
+
+    /// Set the border, color and flags of the annotation.
+    ///
+    /// This replaces any color or border width set before.
+    pub fn with_style(mut self, style: AnnotationStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the color of the annotation, used e.g. for the icon of a
+    /// note and the marking of a text markup annotation.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    /// Set the text of the annotation, shown by the viewer (for a
+    /// link, as an alternate description).
+    pub fn with_contents(mut self, contents: &str) -> Self {
+        self.contents = Some(contents.to_string());
+        self
+    }
+
+    /// Set the width of the border of the annotation.
+    pub fn with_border_width(mut self, width: f32) -> Self {
+        self.style.border_width = Some(width);
+        self
+    }
+
+    /// Set the color to fill the inside of a square or circle
+    /// annotation with, or the areas of a redaction annotation once it
+    /// is applied.
+    /// This has no effect on other kinds of annotations.
+    pub fn with_interior_color(mut self, color: Color) -> Self {
+        match self.kind {
+            AnnotationKind::Shape {
+                ref mut interior, ..
+            }
+            | AnnotationKind::Redact {
+                ref mut interior, ..
+            } => *interior = Some(color),
+            _ => (),
+        }
+        self
+    }
+
+    /// Add a popup window at `rect`, for viewing and editing the
+    /// contents of the annotation, such as the comment on a highlight.
+    ///
+    /// Viewers usually create a popup by themselves when needed, but
+    /// giving it here places it, and lets it be `open` when the
+    /// document is opened.
+    /// This has no effect on links and form fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Annotation, MarkupKind, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let area = Rect::new(10.0, 196.0, 80.0, 12.0);
+    /// let comment = Annotation::markup(MarkupKind::Underline, &[area])
+    ///     .unwrap()
+    ///     .with_contents("Needs a source.")
+    ///     .with_popup(Rect::new(100.0, 150.0, 70.0, 50.0), true);
+    /// canvas.add_annotation(comment);
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn with_popup(mut self, rect: Rect, open: bool) -> Self {
+        match self.kind {
+            AnnotationKind::Link(_) | AnnotationKind::Widget { .. } => (),
+            _ => self.popup = Some((rect, open)),
+        }
+        self
+    }
+
+    /// The rectangle of the popup of the annotation, and whether it is
+    /// open, if it has one.
+    pub(crate) fn popup(&self) -> Option<(Rect, bool)> {
+        self.popup
+    }
+
+    /// Set the name of the author of the annotation.
+    pub fn with_author(mut self, author: &str) -> Self {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    /// Set whether a note is initially open.
+    /// This has no effect on other kinds of annotations.
+    pub fn with_open(mut self, open: bool) -> Self {
+        if let AnnotationKind::Text {
+            open: ref mut o, ..
+        } = self.kind
+        {
+            *o = open;
+        }
+        self
+    }
+
+    /// The rectangle of the annotation.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Convert the coordinates of the annotation from a coordinate
+    /// system where y grows downwards from the top of a page `height`
+    /// high.
+    pub(crate) fn flip_y(&mut self, height: f32) {
+        let flip =
+            |r: &Rect| Rect::new(r.x, height - r.top(), r.width, r.height);
+        self.rect = flip(&self.rect);
+        if let Some((ref mut rect, _)) = self.popup {
+            *rect = flip(rect);
+        }
+        match self.kind {
+            AnnotationKind::Markup(_, ref mut areas)
+            | AnnotationKind::Redact { ref mut areas, .. } => {
+                for area in areas {
+                    *area = flip(area);
+                }
+            }
+            AnnotationKind::FreeText {
+                callout: ref mut points,
+                ..
+            }
+            | AnnotationKind::Measure { ref mut points, .. } => {
+                for point in points {
+                    point.1 = height - point.1;
+                }
+            }
+            AnnotationKind::Ink(ref mut strokes) => {
+                for point in strokes.iter_mut().flatten() {
+                    point.1 = height - point.1;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Write the annotation dictionary, for an annotation on the page
+    /// with object id `page_id`, referring to the already written
+    /// objects in `refs`.
+    pub(crate) fn write_dictionary(
+        &self,
+        output: &mut dyn Write,
+        page_id: usize,
+        refs: &AnnotationRefs,
+        pages: &[PageInfo],
+    ) -> io::Result<()> {
+        let r = self.rect;
+        write!(
+            output,
+            "<< /Type /Annot /Subtype /{}\n   \
+             /Rect [ {} {} {} {} ] /P {} 0 R\n   ",
+            self.subtype(),
+            r.x,
+            r.y,
+            r.right(),
+            r.top(),
+            page_id,
+        )?;
+        if let Some(ref contents) = self.contents {
+            write!(output, "/Contents {}\n   ", text_string(contents))?;
+        }
+        if let Some(ref author) = self.author {
+            write!(output, "/T {}\n   ", text_string(author))?;
+        }
+        let style = &self.style;
+        let flags = style.flags.bits();
+        if flags != 0 {
+            write!(output, "/F {}\n   ", flags)?;
+        }
+        if let Some(color) = style.color {
+            write!(output, "/C {}\n   ", color_array(color))?;
+        }
+        if style.border_width.is_some() || !style.border_dash.is_empty() {
+            write!(output, "/BS <<")?;
+            if let Some(width) = style.border_width {
+                write!(output, " /W {}", width)?;
+            }
+            if !style.border_dash.is_empty() {
+                write!(output, " /S /D /D [")?;
+                for length in &style.border_dash {
+                    write!(output, " {}", length)?;
+                }
+                write!(output, " ]")?;
+            }
+            write!(output, " >>\n   ")?;
+        }
+        if let Some(id) = refs.appearance {
+            write!(output, "/AP << /N {} 0 R >>\n   ", id)?;
+        }
+        if let Some(id) = refs.popup {
+            write!(output, "/Popup {} 0 R\n   ", id)?;
+        }
+        if !refs.appearance_states.is_empty() {
+            write!(output, "/AP << /N <<")?;
+            for (state, id) in &refs.appearance_states {
+                write!(output, " {} {} 0 R", name(state), id)?;
+            }
+            write!(output, " >> >>\n   ")?;
+        }
+        match self.kind {
+            AnnotationKind::Link(ref action) => {
+                write!(
+                    output,
+                    "/Border [ 0 0 0 ]\n   /A {}\n",
+                    action.to_pdf(pages)?,
+                )?;
+            }
+            AnnotationKind::Text { icon, open } => {
+                writeln!(output, "/Name /{:?} /Open {}", icon, open)?;
+            }
+            AnnotationKind::Markup(_, ref areas) => {
+                write_quad_points(output, areas)?;
+            }
+            AnnotationKind::Ink(ref strokes) => {
+                write!(output, "/InkList [")?;
+                for stroke in strokes {
+                    write!(output, " [")?;
+                    for &(x, y) in stroke {
+                        write!(output, " {} {}", x, y)?;
+                    }
+                    write!(output, " ]")?;
+                }
+                writeln!(output, " ]")?;
+            }
+            AnnotationKind::Measure {
+                ref points,
+                area,
+                ref scale,
+            } => {
+                let coordinates = points
+                    .iter()
+                    .map(|&(x, y)| format!(" {} {}", x, y))
+                    .collect::<String>();
+                if area {
+                    write!(
+                        output,
+                        "/Vertices [{} ] /IT /PolygonDimension",
+                        coordinates,
+                    )?;
+                } else {
+                    write!(
+                        output,
+                        "/L [{} ] /IT /LineDimension /Cap true",
+                        coordinates,
+                    )?;
+                }
+                writeln!(output, "\n   /Measure {}", scale.to_pdf())?;
+            }
+            AnnotationKind::Redact {
+                ref areas,
+                interior,
+                ref overlay,
+            } => {
+                write_quad_points(output, areas)?;
+                if let Some(color) = interior {
+                    writeln!(output, "   /IC {}", color_array(color))?;
+                }
+                if let Some(ref text) = *overlay {
+                    writeln!(
+                        output,
+                        "   /OverlayText {} /DA (/{} 10 Tf 1 g)",
+                        text_string(text),
+                        REDACT_FONT.pdf_name(),
+                    )?;
+                }
+            }
+            AnnotationKind::FreeText {
+                font,
+                size,
+                text_color,
+                ref callout,
+            } => {
+                write!(
+                    output,
+                    "/DA (/{} {} Tf {})",
+                    font.pdf_name(),
+                    size,
+                    fill_color_operator(text_color),
+                )?;
+                if !callout.is_empty() {
+                    write!(output, "\n   /IT /FreeTextCallout /CL [")?;
+                    for &(x, y) in callout {
+                        write!(output, " {} {}", x, y)?;
+                    }
+                    write!(output, " ] /LE /OpenArrow")?;
+                }
+                writeln!(output)?;
+            }
+            AnnotationKind::Stamp(name) => {
+                if let Some(name) = name {
+                    write!(output, "/Name /{:?}", name)?;
+                }
+                writeln!(output)?;
+            }
+            AnnotationKind::FileAttachment(_, icon) => {
+                write!(output, "/Name /{:?}", icon)?;
+                if let Some(id) = refs.file {
+                    write!(output, " /FS {} 0 R", id)?;
+                }
+                writeln!(output)?;
+            }
+            AnnotationKind::Widget {
+                ref field,
+                ref state,
+                ..
+            } => {
+                if let Some(ref state) = *state {
+                    let current = if field.is_on(state) {
+                        state.as_str()
+                    } else {
+                        "Off"
+                    };
+                    write!(output, "/AS {} ", name(current))?;
+                }
+                if let Some(id) = refs.parent {
+                    write!(output, "/Parent {} 0 R", id)?;
+                }
+                if let Some(mk) = field.widget_characteristics() {
+                    write!(output, "\n   /MK {}", mk)?;
+                }
+                if let Some(action) = field.widget_action(pages)? {
+                    write!(output, "\n   /A {}", action)?;
+                }
+                writeln!(output)?;
+            }
+            AnnotationKind::Shape { interior, .. } => {
+                if let Some(color) = interior {
+                    write!(output, "/IC {}", color_array(color))?;
+                }
+                writeln!(output)?;
+            }
+        }
+        writeln!(output, ">>")
+    }
+
+    /// Write the dictionary of the popup of the annotation, for an
+    /// annotation with object id `parent_id` on the page with object
+    /// id `page_id`.
+    pub(crate) fn write_popup(
+        &self,
+        output: &mut dyn Write,
+        page_id: usize,
+        parent_id: usize,
+    ) -> io::Result<()> {
+        let (r, open) = self.popup.unwrap_or((self.rect, false));
+        writeln!(
+            output,
+            "<< /Type /Annot /Subtype /Popup\n   \
+             /Rect [ {} {} {} {} ] /P {} 0 R\n   \
+             /Parent {} 0 R /Open {}\n\
+             >>",
+            r.x,
+            r.y,
+            r.right(),
+            r.top(),
+            page_id,
+            parent_id,
+            open,
+        )
+    }
+
+    fn subtype(&self) -> &'static str {
+        match self.kind {
+            AnnotationKind::Link(_) => "Link",
+            AnnotationKind::Text { .. } => "Text",
+            AnnotationKind::Markup(kind, _) => match kind {
+                MarkupKind::Highlight => "Highlight",
+                MarkupKind::Underline => "Underline",
+                MarkupKind::Squiggly => "Squiggly",
+                MarkupKind::StrikeOut => "StrikeOut",
+            },
+            AnnotationKind::FreeText { .. } => "FreeText",
+            AnnotationKind::Stamp(_) => "Stamp",
+            AnnotationKind::FileAttachment(..) => "FileAttachment",
+            AnnotationKind::Redact { .. } => "Redact",
+            AnnotationKind::Ink(_) => "Ink",
+            AnnotationKind::Measure { area: false, .. } => "Line",
+            AnnotationKind::Measure { area: true, .. } => "Polygon",
+            AnnotationKind::Widget { .. } => "Widget",
+            AnnotationKind::Shape { circle: false, .. } => "Square",
+            AnnotationKind::Shape { circle: true, .. } => "Circle",
+        }
+    }
+}
+
+/// The font of the overlay text of redaction annotations.
+const REDACT_FONT: BuiltinFont = BuiltinFont::Helvetica_Bold;
+
+/// The rectangle around `points`, which must not be empty, with a
+/// `margin` on all sides.
+fn points_bounds(points: &[(f32, f32)], margin: f32) -> Rect {
+    let (x, y) = points[0];
+    let rect = points.iter().fold(Rect::new(x, y, 0.0, 0.0), |r, p| {
+        r.union(&Rect::new(p.0, p.1, 0.0, 0.0))
+    });
+    Rect::new(
+        rect.x - margin,
+        rect.y - margin,
+        rect.width + 2.0 * margin,
+        rect.height + 2.0 * margin,
+    )
+}
+
+/// Write the /QuadPoints of an annotation covering `areas`.
+fn write_quad_points(
+    output: &mut dyn Write,
+    areas: &[Rect],
+) -> io::Result<()> {
+    // Each quadrilateral is given as its upper left, upper right,
+    // lower left and lower right corners, as expected by viewers.
+    write!(output, "/QuadPoints [")?;
+    for a in areas {
+        write!(
+            output,
+            " {} {} {} {} {} {} {} {}",
+            a.x,
+            a.top(),
+            a.right(),
+            a.top(),
+            a.x,
+            a.y,
+            a.right(),
+            a.y,
+        )?;
+    }
+    writeln!(output, " ]")
+}
+
+/// Create a form `width` by `height`, drawn by `render`, to use as
+/// the appearance of an annotation.
+pub(crate) fn appearance_form<F>(
+    width: f32,
+    height: f32,
+    render: F,
+) -> io::Result<FormXObject>
+where
+    F: FnOnce(&mut Canvas) -> io::Result<()>,
+{
+    let bounds = Rect::new(0.0, 0.0, width, height);
+    let mut outline_items = Vec::new();
+    let (content, resources) = render_content(
+        bounds,
+        false,
+        &mut outline_items,
+        &HashMap::new(),
+        render,
+    )?;
+    let bbox = [0.0, 0.0, width, height];
+    Ok(FormXObject::new(bbox, content, resources))
+}
+
+/// The components of a color, in the range 0 to 1.
+fn color_components(color: Color) -> Vec<f32> {
+    let components = match color {
+        Color::Gray { gray } => vec![gray],
+        Color::RGB { red, green, blue } => vec![red, green, blue],
+        Color::CMYK {
+            cyan,
+            magenta,
+            yellow,
+            black,
+        } => vec![cyan, magenta, yellow, black],
+    };
+    components
+        .into_iter()
+        .map(|c| f32::from(c) / 255.0)
+        .collect()
+}
+
+/// A color as an array of components.
+fn color_array(color: Color) -> String {
+    let mut result = String::from("[");
+    for c in color_components(color) {
+        result.push_str(&format!(" {}", c));
+    }
+    result.push_str(" ]");
+    result
+}
+
+/// The content stream operator to set `color` for filling.
+fn fill_color_operator(color: Color) -> String {
+    let operator = match color {
+        Color::Gray { .. } => "g",
+        Color::RGB { .. } => "rg",
+        Color::CMYK { .. } => "k",
+    };
+    let mut result = String::new();
+    for c in color_components(color) {
+        result.push_str(&format!("{} ", c));
+    }
+    result.push_str(operator);
+    result
+}
+
+#[test]
+fn test_annotation_style() {
+    assert_eq!(4, AnnotationFlags::default().bits());
+    let flags = AnnotationFlags {
+        print: false,
+        no_view: true,
+        locked: true,
+        ..AnnotationFlags::default()
+    };
+    assert_eq!(32 + 128, flags.bits());
+    let style = AnnotationStyle {
+        border_dash: vec![3.0],
+        flags,
+        ..AnnotationStyle::default()
+    };
+    let square = Annotation::square(Rect::new(0.0, 0.0, 10.0, 10.0))
+        .with_style(style)
+        .with_border_width(2.0);
+    let mut output = Vec::new();
+    square
+        .write_dictionary(&mut output, 3, &AnnotationRefs::default(), &[])
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("/F 160\n"));
+    assert!(output.contains("/BS << /W 2 /S /D /D [ 3 ] >>"));
+}
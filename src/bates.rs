@@ -0,0 +1,126 @@
+use canvas::Canvas;
+use fontsource::BuiltinFont;
+use rect::{Align, Margins};
+use std::io;
+
+/// Bates numbers, the sequential numbers stamped on every page of
+/// documents produced in legal proceedings, as set by
+/// [Pdf::set_bates_numbering](struct.Pdf.html#method.set_bates_numbering).
+///
+/// Each number has a prefix followed by the page number, padded with
+/// zeros to a fixed number of digits, e.g. `ACME000042`.
+/// The numbers follow the final order of the pages, when the
+/// document is finished.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Align, BatesNumbering, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.set_bates_numbering(
+///     BatesNumbering::new("ACME")
+///         .with_start(1200)
+///         .with_digits(8)
+///         .with_position(Align::BottomLeft),
+/// );
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatesNumbering {
+    prefix: String,
+    start: u64,
+    digits: usize,
+    position: Align,
+    margin: f32,
+    font: BuiltinFont,
+    size: f32,
+}
+
+impl BatesNumbering {
+    /// Numbers with the given prefix, starting at 1 with six digits,
+    /// in the bottom right corner of the page.
+    pub fn new(prefix: &str) -> Self {
+        BatesNumbering {
+            prefix: prefix.to_string(),
+            start: 1,
+            digits: 6,
+            position: Align::BottomRight,
+            margin: 18.0,
+            font: BuiltinFont::Helvetica,
+            size: 10.0,
+        }
+    }
+
+    /// Set the number of the first page, e.g. to continue the numbers
+    /// of an earlier production.
+    pub fn with_start(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Set the number of digits that the numbers are padded to.
+    pub fn with_digits(mut self, digits: usize) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    /// Set where on the page the number is stamped.
+    pub fn with_position(mut self, position: Align) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Set the distance from the edges of the page to the number, in
+    /// points (default 18).
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Set the font and font size of the numbers.
+    pub fn with_font(mut self, font: BuiltinFont, size: f32) -> Self {
+        self.font = font;
+        self.size = size;
+        self
+    }
+
+    /// The Bates number of the page at `index`.
+    pub fn label(&self, index: usize) -> String {
+        format!(
+            "{}{:0digits$}",
+            self.prefix,
+            self.start + index as u64,
+            digits = self.digits,
+        )
+    }
+
+    /// Stamp the Bates number of the page at `index` on `canvas`.
+    pub(crate) fn draw(
+        &self,
+        canvas: &mut Canvas,
+        index: usize,
+    ) -> io::Result<()> {
+        let label = self.label(index);
+        let width = canvas.get_font(self.font).get_width(self.size, &label);
+        let area = canvas.bounds().inside(Margins::uniform(self.margin));
+        let (x, y) = self.position.fractions();
+        canvas.left_text(
+            area.x + (area.width - width) * x,
+            area.y + (area.height - self.size) * y,
+            self.font,
+            self.size,
+            &label,
+        )
+    }
+}
+
+#[test]
+fn test_label() {
+    let numbering = BatesNumbering::new("ACME").with_start(41);
+    assert_eq!("ACME000041", numbering.label(0));
+    assert_eq!("ACME000043", numbering.label(2));
+    let short = BatesNumbering::new("X").with_digits(2);
+    assert_eq!("X01", short.label(0));
+    assert_eq!("X1234567", short.label(1234566));
+}
@@ -1,10 +1,26 @@
+use action::{Action, Destination};
+use annotation::Annotation;
+use colorspace::ColorSpace;
+use extgstate::{ExtGState, SoftMask};
 use fontref::FontRef;
 use fontsource::{BuiltinFont, FontSource};
+use formfield::FormField;
+use formxobject::FormXObject;
 use graphicsstate::*;
+use imagexobject::{Image, ImageColorSpace, ImageSource};
+use layer::Layer;
 use outline::OutlineItem;
+use path::{Path, PathSegment};
+use pattern::{Paint, PatternResource, TilingPattern};
+use recording::{recording_form, Recording};
+use rect::{Align, Fit, Rect};
+use resources::Resources;
+use shading::{GradientSpec, Shading};
 use std::collections::HashMap;
+use std::f32::consts::PI;
 use std::io::{self, Write};
 use std::sync::Arc;
+use structure::{StructElement, StructureType, TableScope};
 use textobject::TextObject;
 
 /// A visual area where content can be drawn (a page).
@@ -16,24 +32,176 @@ use textobject::TextObject;
 /// `FontSource` instead.
 pub struct Canvas<'a> {
     output: &'a mut Write,
-    fonts: &'a mut HashMap<BuiltinFont, FontRef>,
+    bounds: Rect,
+    content_box: Rect,
+    y_down: bool,
+    resources: &'a mut Resources,
     outline_items: &'a mut Vec<OutlineItem>,
+    annotations: &'a mut Vec<Annotation>,
+    xobjects: &'a HashMap<String, Recording>,
+    /// The structure elements tagged on the page, or None for content
+    /// that is not directly on a page.
+    structure: Option<&'a mut Vec<StructElement>>,
+    /// The structure element being drawn, as an index in `structure`.
+    current_element: Option<usize>,
+}
+
+/// Where the origin of the coordinate system of a page is, as set by
+/// [Pdf::set_origin](struct.Pdf.html#method.set_origin).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// The origin is in the bottom left corner and y grows upwards, as
+    /// usual in PDF.
+    BottomLeft,
+    /// The origin is in the top left corner and y grows downwards, as
+    /// usual in screen graphics.
+    ///
+    /// Text is still upright, and positioned by its baseline.
+    /// Note that positive angles rotate clockwise in this mode, and
+    /// that a `Rect` has its top edge at `y`.
+    TopLeft,
+}
+
+impl Default for Origin {
+    fn default() -> Self {
+        Origin::BottomLeft
+    }
+}
+
+/// How a page is rotated when it is shown or printed, as set by
+/// [Pdf::set_rotation](struct.Pdf.html#method.set_rotation).
+///
+/// The page is rendered as usual, in its unrotated coordinate
+/// system, and rotated clockwise by the viewer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// Show the page upright.
+    None,
+    /// Rotate the page 90 degrees clockwise.
+    Clockwise90,
+    /// Turn the page upside down.
+    Rotate180,
+    /// Rotate the page 90 degrees counterclockwise.
+    Counterclockwise90,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation::None
+    }
+}
+
+impl Rotation {
+    /// The rotation in degrees, clockwise, as used by /Rotate.
+    pub(crate) fn degrees(self) -> u32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 90,
+            Rotation::Rotate180 => 180,
+            Rotation::Counterclockwise90 => 270,
+        }
+    }
 }
 
 // Should not be called by user code.
+#[allow(clippy::too_many_arguments)]
 pub fn create_canvas<'a>(
     output: &'a mut Write,
-    fonts: &'a mut HashMap<BuiltinFont, FontRef>,
+    bounds: Rect,
+    content_box: Rect,
+    y_down: bool,
+    resources: &'a mut Resources,
     outline_items: &'a mut Vec<OutlineItem>,
+    annotations: &'a mut Vec<Annotation>,
+    xobjects: &'a HashMap<String, Recording>,
+    structure: Option<&'a mut Vec<StructElement>>,
 ) -> Canvas<'a> {
     Canvas {
         output,
-        fonts,
+        bounds,
+        content_box,
+        y_down,
+        resources,
         outline_items,
+        annotations,
+        xobjects,
+        structure,
+        current_element: None,
     }
 }
 
+// Should not be called by user code.
+// Render content by `render` on a new canvas, for form xobjects and
+// similar.  Return the content stream and the resources used.
+// The content stream lacks the prologue of `Pdf::content_prologue`,
+// which is added when it is written.
+// Annotations can't be part of such content, and are ignored.
+// With `y_down`, text is flipped to be upright in a user space where
+// the y axis points downward.
+pub fn render_content<F>(
+    bounds: Rect,
+    y_down: bool,
+    outline_items: &mut Vec<OutlineItem>,
+    xobjects: &HashMap<String, Recording>,
+    render: F,
+) -> io::Result<(Vec<u8>, Resources)>
+where
+    F: FnOnce(&mut Canvas) -> io::Result<()>,
+{
+    let mut content = Vec::new();
+    let mut resources = Resources::new();
+    let mut annotations = Vec::new();
+    render(&mut create_canvas(
+        &mut content,
+        bounds,
+        bounds,
+        y_down,
+        &mut resources,
+        outline_items,
+        &mut annotations,
+        xobjects,
+        None,
+    ))?;
+    Ok((content, resources))
+}
+
 impl<'a> Canvas<'a> {
+    /// Get the width of the page (or other area drawn on).
+    pub fn width(&self) -> f32 {
+        self.bounds.width
+    }
+    /// Get the height of the page (or other area drawn on).
+    pub fn height(&self) -> f32 {
+        self.bounds.height
+    }
+    /// Get the rectangle of the whole page (or other area drawn on,
+    /// such as the tile of a pattern).
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+    /// Get the part of the page inside the margins set by
+    /// [Pdf::set_margins](struct.Pdf.html#method.set_margins).
+    ///
+    /// With a top left [Origin](enum.Origin.html), `y` of the box is
+    /// the top margin.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Margins, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_margins(Margins::uniform(20.0));
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     let content = canvas.content_box();
+    ///     assert_eq!(140.0, content.width);
+    ///     let font = BuiltinFont::Helvetica;
+    ///     canvas.left_text(content.x, content.top() - 12.0, font, 12.0, "Hi")
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn content_box(&self) -> Rect {
+        self.content_box
+    }
     /// Append a closed rectangle with a corner at (x, y) and
     /// extending width × height to the to the current path.
     pub fn rectangle(
@@ -79,32 +247,559 @@ impl<'a> Canvas<'a> {
     /// Set color for stroking operations.
     pub fn set_stroke_color(&mut self, color: Color) -> io::Result<()> {
         let norm = |c| f32::from(c) / 255.0;
+        if let Color::RGB { .. } = color {
+            self.resources.uses_rgb = true;
+        }
         match color {
             Color::RGB { red, green, blue } => writeln!(
                 self.output,
-                "{} {} {} SC",
+                "{} {} {} RG",
                 norm(red),
                 norm(green),
                 norm(blue),
             ),
             Color::Gray { gray } => writeln!(self.output, "{} G", norm(gray)),
+            Color::CMYK {
+                cyan,
+                magenta,
+                yellow,
+                black,
+            } => writeln!(
+                self.output,
+                "{} {} {} {} K",
+                norm(cyan),
+                norm(magenta),
+                norm(yellow),
+                norm(black),
+            ),
         }
     }
     /// Set color for non-stroking operations.
     pub fn set_fill_color(&mut self, color: Color) -> io::Result<()> {
         let norm = |c| f32::from(c) / 255.0;
+        if let Color::RGB { .. } = color {
+            self.resources.uses_rgb = true;
+        }
         match color {
             Color::RGB { red, green, blue } => writeln!(
                 self.output,
-                "{} {} {} sc",
+                "{} {} {} rg",
                 norm(red),
                 norm(green),
                 norm(blue),
             ),
             Color::Gray { gray } => writeln!(self.output, "{} g", norm(gray)),
+            Color::CMYK {
+                cyan,
+                magenta,
+                yellow,
+                black,
+            } => writeln!(
+                self.output,
+                "{} {} {} {} k",
+                norm(cyan),
+                norm(magenta),
+                norm(yellow),
+                norm(black),
+            ),
         }
     }
 
+    /// Set a tint of a spot color for stroking operations.
+    ///
+    /// The `tint` ranges from 0.0 (no ink) to 1.0 (full ink).
+    /// See [SpotColor](graphicsstate/struct.SpotColor.html) for an
+    /// example.
+    pub fn set_stroke_spot(
+        &mut self,
+        spot: &SpotColor,
+        tint: f32,
+    ) -> io::Result<()> {
+        let name = self
+            .resources
+            .color_space_name(ColorSpace::Separation(spot.clone()));
+        writeln!(self.output, "{} CS {} SCN", name, tint.clamp(0.0, 1.0))
+    }
+    /// Set a tint of a spot color for non-stroking operations.
+    ///
+    /// The `tint` ranges from 0.0 (no ink) to 1.0 (full ink).
+    /// See [SpotColor](graphicsstate/struct.SpotColor.html) for an
+    /// example.
+    pub fn set_fill_spot(
+        &mut self,
+        spot: &SpotColor,
+        tint: f32,
+    ) -> io::Result<()> {
+        let name = self
+            .resources
+            .color_space_name(ColorSpace::Separation(spot.clone()));
+        writeln!(self.output, "{} cs {} scn", name, tint.clamp(0.0, 1.0))
+    }
+
+    /// Set a device independent color for stroking operations.
+    ///
+    /// See [CieColor](graphicsstate/struct.CieColor.html) for an
+    /// example.
+    pub fn set_stroke_cie(&mut self, color: CieColor) -> io::Result<()> {
+        let name = self
+            .resources
+            .color_space_name(ColorSpace::Cie(color.get_space()));
+        let c = color.get_components();
+        writeln!(self.output, "{} CS {} {} {} SCN", name, c[0], c[1], c[2])
+    }
+    /// Set a device independent color for non-stroking operations.
+    ///
+    /// See [CieColor](graphicsstate/struct.CieColor.html) for an
+    /// example.
+    pub fn set_fill_cie(&mut self, color: CieColor) -> io::Result<()> {
+        let name = self
+            .resources
+            .color_space_name(ColorSpace::Cie(color.get_space()));
+        let c = color.get_components();
+        writeln!(self.output, "{} cs {} {} {} scn", name, c[0], c[1], c[2])
+    }
+
+    /// Set the constant opacity for non-stroking operations.
+    ///
+    /// The `alpha` value ranges from 0.0 (fully transparent) to 1.0
+    /// (opaque).
+    /// This, and other settings requiring an extended graphics state,
+    /// are written as a /ExtGState resource of the page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_fill_color(Color::rgb(255, 255, 0))?;
+    /// canvas.set_fill_alpha(0.4)?;
+    /// canvas.rectangle(10.0, 10.0, 100.0, 20.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_fill_alpha(&mut self, alpha: f32) -> io::Result<()> {
+        self.set_ext_gstate(ExtGState::fill_alpha(alpha))
+    }
+    /// Set the constant opacity for stroking operations.
+    ///
+    /// The `alpha` value ranges from 0.0 (fully transparent) to 1.0
+    /// (opaque).
+    pub fn set_stroke_alpha(&mut self, alpha: f32) -> io::Result<()> {
+        self.set_ext_gstate(ExtGState::stroke_alpha(alpha))
+    }
+    /// Set the constant opacity for both filling and stroking
+    /// operations.
+    ///
+    /// This is the same as calling both
+    /// [set_fill_alpha](#method.set_fill_alpha) and
+    /// [set_stroke_alpha](#method.set_stroke_alpha), but uses a single
+    /// resource.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_opacity(0.3)?;
+    /// canvas.set_line_width(4.0)?;
+    /// canvas.rectangle(10.0, 10.0, 100.0, 20.0)?;
+    /// canvas.stroke()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_opacity(&mut self, alpha: f32) -> io::Result<()> {
+        self.set_ext_gstate(ExtGState::alpha(alpha))
+    }
+    /// Set the blend mode used when painting on the page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::{BlendMode, Color};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_fill_color(Color::rgb(0, 128, 255))?;
+    /// canvas.rectangle(10.0, 10.0, 100.0, 40.0)?;
+    /// canvas.fill()?;
+    /// canvas.set_blend_mode(BlendMode::Multiply)?;
+    /// canvas.set_fill_color(Color::rgb(255, 128, 0))?;
+    /// canvas.rectangle(60.0, 30.0, 100.0, 40.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_blend_mode(&mut self, mode: BlendMode) -> io::Result<()> {
+        self.set_ext_gstate(ExtGState::blend_mode(mode))
+    }
+    /// Set a soft mask, that modulates the opacity of everything
+    /// painted after it.
+    ///
+    /// The mask is defined by the content drawn by `render` within the
+    /// rectangle with a corner at (x, y) and extending width × height.
+    /// With `SoftMaskType::Luminosity`, white areas of the mask are
+    /// opaque, black areas (and everything outside of the rectangle)
+    /// are fully transparent, and gray areas are somewhere in between.
+    /// With `SoftMaskType::Alpha`, only the opacity of the mask content
+    /// matters.
+    ///
+    /// The mask is part of the graphics state, so it can be limited by
+    /// [with_state](#method.with_state), or removed by
+    /// [clear_soft_mask](#method.clear_soft_mask).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::{Color, SoftMaskType};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.with_state(|c| {
+    ///     // Fade out towards the right, in ten steps.
+    ///     c.set_soft_mask(SoftMaskType::Luminosity, 0., 0., 100., 50., |m| {
+    ///         for i in 0..10 {
+    ///             m.set_fill_color(Color::gray(255 - 25 * i))?;
+    ///             m.rectangle(f32::from(i) * 10., 0., 10., 50.)?;
+    ///             m.fill()?;
+    ///         }
+    ///         Ok(())
+    ///     })?;
+    ///     c.set_fill_color(Color::rgb(0, 0, 255))?;
+    ///     c.rectangle(0., 0., 100., 50.)?;
+    ///     c.fill()
+    /// })
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_soft_mask<F>(
+        &mut self,
+        mask_type: SoftMaskType,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        render: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let mut form =
+            self.create_form([x, y, x + width, y + height], render)?;
+        form.set_group(TransparencyGroup::default());
+        let form = self.resources.add_form(form);
+        self.set_ext_gstate(ExtGState::soft_mask(SoftMask::Form(
+            mask_type, form,
+        )))
+    }
+    /// Draw the content created by `render` as a transparency group.
+    ///
+    /// The content is clipped to the rectangle with a corner at (x, y)
+    /// and extending width × height.
+    /// See [TransparencyGroup](graphicsstate/struct.TransparencyGroup.html)
+    /// for details and an example.
+    pub fn transparency_group<F>(
+        &mut self,
+        group: TransparencyGroup,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        render: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let mut form =
+            self.create_form([x, y, x + width, y + height], render)?;
+        form.set_group(group);
+        let name = self.resources.add_form_xobject(form);
+        writeln!(self.output, "{} Do", name)
+    }
+    /// Replay the content of a [Recording](struct.Recording.html), with
+    /// its origin at the origin of the current coordinate system.
+    pub fn replay(&mut self, recording: &Recording) -> io::Result<()> {
+        let name = self.resources.add_form_xobject(recording_form(recording));
+        writeln!(self.output, "{} Do", name)
+    }
+    /// Draw a named form XObject, as defined by
+    /// [Pdf::define_xobject](struct.Pdf.html#method.define_xobject),
+    /// transformed by `matrix`.
+    ///
+    /// The form is only stored once in the pdf file, regardless of how
+    /// many times it is drawn.
+    /// Named forms are available on pages, and in transparency groups
+    /// and soft masks on pages, but not in patterns and recordings.
+    pub fn draw_xobject(
+        &mut self,
+        name: &str,
+        matrix: Matrix,
+    ) -> io::Result<()> {
+        let form = match self.xobjects.get(name) {
+            Some(recording) => recording_form(recording),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("No xobject named {:?} is defined", name),
+                ))
+            }
+        };
+        let name = self.resources.add_form_xobject(form);
+        writeln!(self.output, "q {} cm {} Do Q", matrix, name)
+    }
+    /// Draw an image, scaled to fill a rectangle.
+    ///
+    /// The image can be an [Image](struct.Image.html), or anything
+    /// else that implements [ImageSource](trait.ImageSource.html), such
+    /// as the path of an image file.
+    /// See [Image](struct.Image.html) for an example.
+    ///
+    /// Note that an `Image` is stored only once in the pdf file, while
+    /// e.g. a path is read (and stored) again each time it is drawn.
+    pub fn draw_image<I>(&mut self, image: &I, rect: Rect) -> io::Result<()>
+    where
+        I: ImageSource + ?Sized,
+    {
+        let placement = Matrix::scale(rect.width, rect.height)
+            * Matrix::translate(rect.x, rect.y);
+        self.place_image(&image.to_image()?, placement)
+    }
+    /// Draw an image at its natural size for a resolution of `dpi`
+    /// pixels per inch, with its corner at (x, y), as for a
+    /// [Rect](struct.Rect.html).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, ImageColorSpace, Pdf};
+    /// let scan = Image::raw(300, 150, ImageColorSpace::DeviceGray, &[128; 45000])
+    ///     .unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // One inch (72 pt) wide, and half an inch high.
+    /// canvas.draw_image_at_dpi(&scan, 10.0, 10.0, 300.0)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn draw_image_at_dpi<I>(
+        &mut self,
+        image: &I,
+        x: f32,
+        y: f32,
+        dpi: f32,
+    ) -> io::Result<()>
+    where
+        I: ImageSource + ?Sized,
+    {
+        let image = image.to_image()?;
+        let scale = 72.0 / dpi;
+        let placement = Matrix::scale(
+            image.width() as f32 * scale,
+            image.height() as f32 * scale,
+        ) * Matrix::translate(x, y);
+        self.place_image(&image, placement)
+    }
+    /// Draw an image in the unit square, transformed by `matrix`, e.g.
+    /// to rotate or shear it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, ImageColorSpace, Pdf};
+    /// # use pdf_canvas::graphicsstate::Matrix;
+    /// let image = Image::raw(2, 1, ImageColorSpace::DeviceGray, &[0, 255])
+    ///     .unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // 100 × 50 large, centered at (90, 120), rotated 30 degrees.
+    /// let matrix = Matrix::translate(-0.5, -0.5)
+    ///     * Matrix::scale(100.0, 50.0)
+    ///     * Matrix::rotate_deg(30.0)
+    ///     * Matrix::translate(90.0, 120.0);
+    /// canvas.draw_image_transformed(&image, matrix)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn draw_image_transformed<I>(
+        &mut self,
+        image: &I,
+        matrix: Matrix,
+    ) -> io::Result<()>
+    where
+        I: ImageSource + ?Sized,
+    {
+        self.place_image(&image.to_image()?, matrix)
+    }
+    /// Draw an image in a rectangle, keeping its aspect ratio.
+    ///
+    /// The image is sized to `rect` as given by `fit`, and placed in it
+    /// as given by `align`.
+    /// With `Fit::Cover`, the parts of the image outside of `rect` are
+    /// clipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Align, Fit, Image, ImageColorSpace, Pdf, Rect};
+    /// let logo = Image::raw(4, 1, ImageColorSpace::DeviceGray, &[0, 80, 160, 240])
+    ///     .unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let header = Rect::new(10.0, 200.0, 160.0, 30.0);
+    /// canvas.draw_image_fit(&logo, header, Fit::Contain, Align::Left)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn draw_image_fit<I>(
+        &mut self,
+        image: &I,
+        rect: Rect,
+        fit: Fit,
+        align: Align,
+    ) -> io::Result<()>
+    where
+        I: ImageSource + ?Sized,
+    {
+        let image = image.to_image()?;
+        // Visual top is at low y when the y axis points down.
+        let align = if self.y_down {
+            align.flip_vertical()
+        } else {
+            align
+        };
+        let placed =
+            rect.fit(image.width() as f32, image.height() as f32, fit, align);
+        let placement = Matrix::scale(placed.width, placed.height)
+            * Matrix::translate(placed.x, placed.y);
+        if fit == Fit::Cover {
+            writeln!(
+                self.output,
+                "q {} {} {} {} re W n",
+                rect.x, rect.y, rect.width, rect.height,
+            )?;
+            self.place_image(&image, placement)?;
+            writeln!(self.output, "Q")
+        } else {
+            self.place_image(&image, placement)
+        }
+    }
+    /// Draw an image from raw pixel data, as for
+    /// [Image::raw](struct.Image.html#method.raw).
+    ///
+    /// The image is drawn in the unit square, transformed by `matrix`.
+    /// For example, `Matrix::scale(w, h) * Matrix::translate(x, y)`
+    /// draws the image w × h large at (x, y).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{ImageColorSpace, Pdf};
+    /// # use pdf_canvas::graphicsstate::Matrix;
+    /// // A horizontal gray ramp, 256 × 1 pixels.
+    /// let ramp: Vec<u8> = (0..=255).collect();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.image_raw(
+    ///     256,
+    ///     1,
+    ///     ImageColorSpace::DeviceGray,
+    ///     &ramp,
+    ///     Matrix::scale(160.0, 20.0) * Matrix::translate(10.0, 10.0),
+    /// )
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn image_raw(
+        &mut self,
+        width_px: u32,
+        height_px: u32,
+        color_space: ImageColorSpace,
+        data: &[u8],
+        matrix: Matrix,
+    ) -> io::Result<()> {
+        let image = Image::raw(width_px, height_px, color_space, data)?;
+        self.place_image(&image, matrix)
+    }
+    /// Draw an image in the unit square transformed by `matrix`.
+    /// With a top left origin, the image is flipped to be upright, and
+    /// it is turned to its EXIF orientation, if any.
+    fn place_image(
+        &mut self,
+        image: &Image,
+        matrix: Matrix,
+    ) -> io::Result<()> {
+        let matrix = if self.y_down {
+            Matrix::from_array([1., 0., 0., -1., 0., 1.]) * matrix
+        } else {
+            matrix
+        };
+        let matrix = image.orientation_matrix() * matrix;
+        let name = self.resources.add_image(image);
+        writeln!(self.output, "q {} cm {} Do Q", matrix, name)
+    }
+    /// Remove the current soft mask, if any.
+    pub fn clear_soft_mask(&mut self) -> io::Result<()> {
+        self.set_ext_gstate(ExtGState::soft_mask(SoftMask::None))
+    }
+    fn set_ext_gstate(&mut self, state: ExtGState) -> io::Result<()> {
+        let name = self.resources.ext_gstate_name(state);
+        writeln!(self.output, "{} gs", name)
+    }
+    /// Create a form with `bbox` as [left, bottom, right, top] and
+    /// content drawn by `render` on a canvas of its own.
+    fn create_form<F>(
+        &mut self,
+        bbox: [f32; 4],
+        render: F,
+    ) -> io::Result<FormXObject>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let bounds = Rect::from_corners(bbox[0], bbox[1], bbox[2], bbox[3]);
+        let (content, resources) = render_content(
+            bounds,
+            self.y_down,
+            self.outline_items,
+            self.xobjects,
+            render,
+        )?;
+        Ok(FormXObject::new(bbox, content, resources))
+    }
+
+    /// Write a comment to the content stream.
+    ///
+    /// Comments have no effect on the rendered page, but can make the
+    /// generated content streams easier to read and compare while
+    /// developing.
+    /// Each line of `text` is written as a separate `%` comment line,
+    /// so line breaks in `text` can not end the comment early.
+    /// Content streams are not compressed, and the comments are always
+    /// written, so remove any calls that are not needed in production.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.comment("Header bar")?;
+    /// canvas.rectangle(0.0, 200.0, 180.0, 40.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn comment(&mut self, text: &str) -> io::Result<()> {
+        // A pending path operator may lack a line break.
+        writeln!(self.output)?;
+        for line in text.split(['\r', '\n']) {
+            writeln!(self.output, "% {}", line)?;
+        }
+        Ok(())
+    }
+
     /// Modify the current transformation matrix for coordinates by
     /// concatenating the specified matrix.
     pub fn concat(&mut self, m: Matrix) -> io::Result<()> {
@@ -166,6 +861,481 @@ impl<'a> Canvas<'a> {
         self.curve_to(right, up, rightp, top, x, top)?;
         Ok(())
     }
+    /// Append a closed rectangle with rounded corners to the current
+    /// path.
+    ///
+    /// The rectangle has a corner at (x, y) and extends width × height,
+    /// and all corners are rounded with the given radius.
+    /// The radius is reduced if needed to fit the rectangle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.rounded_rectangle(10.0, 10.0, 120.0, 30.0, 6.0)?;
+    /// canvas.stroke()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn rounded_rectangle(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+    ) -> io::Result<()> {
+        self.rounded_rectangle_corners(x, y, width, height, [radius; 4])
+    }
+    /// Paint a shadow for a rectangle with rounded corners, as given to
+    /// [rounded_rectangle](#method.rounded_rectangle).
+    ///
+    /// Only the shadow is painted, draw the shape itself afterwards to
+    /// put it on top of the shadow.
+    /// The soft edge is made by filling a few progressively smaller
+    /// translucent rectangles, so it works in any pdf viewer.
+    /// The graphics state is restored afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::{Color, Shadow};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.drop_shadow(20.0, 140.0, 140.0, 80.0, 6.0, Shadow::default())?;
+    /// canvas.set_fill_color(Color::gray(255))?;
+    /// canvas.rounded_rectangle(20.0, 140.0, 140.0, 80.0, 6.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn drop_shadow(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        shadow: Shadow,
+    ) -> io::Result<()> {
+        let layers = (shadow.blur.max(0.0).ceil() as usize).clamp(1, 8);
+        // The alpha of each layer, so all layers together give the
+        // requested alpha.
+        let alpha = 1.0
+            - (1.0 - shadow.alpha.clamp(0.0, 1.0)).powf(1.0 / layers as f32);
+        let (x, y) = (x + shadow.offset.0, y + shadow.offset.1);
+        self.with_state(|c| {
+            c.set_fill_color(shadow.color)?;
+            c.set_fill_alpha(alpha)?;
+            for i in 0..layers {
+                let grow = if layers > 1 {
+                    shadow.blur * (0.5 - i as f32 / (layers - 1) as f32)
+                } else {
+                    0.0
+                };
+                c.rounded_rectangle(
+                    x - grow,
+                    y - grow,
+                    width + 2.0 * grow,
+                    height + 2.0 * grow,
+                    (radius + grow).max(0.0),
+                )?;
+                c.fill()?;
+            }
+            Ok(())
+        })
+    }
+    /// Append a closed rectangle with individually rounded corners to
+    /// the current path.
+    ///
+    /// The radii are given in the order lower left, lower right, upper
+    /// right, upper left (as the path runs).
+    /// A radius of zero gives a sharp corner.
+    /// If adjacent radii don't fit along a side, all radii are scaled
+    /// down by the same factor, so the shape remains consistent.
+    pub fn rounded_rectangle_corners(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radii: [f32; 4],
+    ) -> io::Result<()> {
+        let r: Vec<f32> = radii.iter().map(|r| r.max(0.0)).collect();
+        let (w, h) = (width.abs(), height.abs());
+        let mut scale = 1.0f32;
+        for &(a, b, side) in &[
+            (r[0], r[1], w),
+            (r[1], r[2], h),
+            (r[2], r[3], w),
+            (r[3], r[0], h),
+        ] {
+            if a + b > side {
+                scale = scale.min(side / (a + b));
+            }
+        }
+        let r: Vec<f32> = r.iter().map(|r| r * scale).collect();
+        let (left, bottom) = (x.min(x + width), y.min(y + height));
+        let (right, top) = (left + w, bottom + h);
+        // Distance from the corner to the control points of each
+        // quarter circle, see `circle`.
+        let c = 1.0 - 0.551_915;
+        self.move_to(left + r[0], bottom)?;
+        self.line_to(right - r[1], bottom)?;
+        if r[1] > 0.0 {
+            self.curve_to(
+                right - r[1] * c,
+                bottom,
+                right,
+                bottom + r[1] * c,
+                right,
+                bottom + r[1],
+            )?;
+        }
+        self.line_to(right, top - r[2])?;
+        if r[2] > 0.0 {
+            self.curve_to(
+                right,
+                top - r[2] * c,
+                right - r[2] * c,
+                top,
+                right - r[2],
+                top,
+            )?;
+        }
+        self.line_to(left + r[3], top)?;
+        if r[3] > 0.0 {
+            self.curve_to(
+                left + r[3] * c,
+                top,
+                left,
+                top - r[3] * c,
+                left,
+                top - r[3],
+            )?;
+        }
+        self.line_to(left, bottom + r[0])?;
+        if r[0] > 0.0 {
+            self.curve_to(
+                left,
+                bottom + r[0] * c,
+                left + r[0] * c,
+                bottom,
+                left + r[0],
+                bottom,
+            )?;
+        }
+        self.close_path()
+    }
+    /// Begin a new subpath with a smooth curve through all the given
+    /// points.
+    ///
+    /// The curve is a Catmull-Rom spline, converted to a cubic Bézier
+    /// curve between each pair of adjacent points.
+    /// The tangent at each inner point is parallel to the line between
+    /// its neighbours, so the curve has no corners.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let data = [(10.0, 50.0), (50.0, 90.0), (90.0, 70.0), (170.0, 130.0)];
+    /// canvas.smooth_curve(&data)?;
+    /// canvas.stroke()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn smooth_curve(&mut self, points: &[(f32, f32)]) -> io::Result<()> {
+        let n = points.len();
+        if n == 0 {
+            return Ok(());
+        }
+        self.move_to(points[0].0, points[0].1)?;
+        for i in 1..n {
+            let p0 = points[if i > 1 { i - 2 } else { 0 }];
+            let (p1, p2) = (points[i - 1], points[i]);
+            let p3 = points[if i + 1 < n { i + 1 } else { n - 1 }];
+            self.curve_to(
+                p1.0 + (p2.0 - p0.0) / 6.,
+                p1.1 + (p2.1 - p0.1) / 6.,
+                p2.0 - (p3.0 - p1.0) / 6.,
+                p2.1 - (p3.1 - p1.1) / 6.,
+                p2.0,
+                p2.1,
+            )?;
+        }
+        Ok(())
+    }
+    /// Append a closed pie slice (circular sector) to the current path.
+    ///
+    /// The sector is part of a circle with center (x, y) and radius r,
+    /// from the angle `start_deg` to `end_deg`.
+    /// Angles are in degrees, counterclockwise from the positive x
+    /// axis; if `end_deg` is less than `start_deg` the arc goes
+    /// clockwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // A pie chart of 50%, 30% and 20%.
+    /// let mut start = 90.0;
+    /// for &(share, gray) in &[(0.5, 64), (0.3, 128), (0.2, 192)] {
+    ///     let end = start - share * 360.0;
+    ///     canvas.set_fill_color(Color::gray(gray))?;
+    ///     canvas.pie_sector(90.0, 120.0, 60.0, start, end)?;
+    ///     canvas.fill()?;
+    ///     start = end;
+    /// }
+    /// # Ok(())
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn pie_sector(
+        &mut self,
+        x: f32,
+        y: f32,
+        r: f32,
+        start_deg: f32,
+        end_deg: f32,
+    ) -> io::Result<()> {
+        self.move_to(x, y)?;
+        let start = start_deg.to_radians();
+        self.line_to(x + r * start.cos(), y + r * start.sin())?;
+        self.arc_to(x, y, r, start, end_deg.to_radians())?;
+        self.close_path()
+    }
+    /// Append a circular arc, with center (x, y) and radius r, from the
+    /// angle `start` to `end` (in radians) to the current path.
+    /// The current point is assumed to be at the start of the arc.
+    ///
+    /// The arc is approximated by one cubic Bézier curve for each
+    /// started quarter circle.
+    fn arc_to(
+        &mut self,
+        x: f32,
+        y: f32,
+        r: f32,
+        start: f32,
+        end: f32,
+    ) -> io::Result<()> {
+        let sweep = (end - start).clamp(-2. * PI, 2. * PI);
+        let n = (sweep.abs() / (PI / 2.) - 1e-4).ceil().max(1.);
+        let step = sweep / n;
+        let k = r * 4. / 3. * (step / 4.).tan();
+        let mut a = start;
+        for _ in 0..n as usize {
+            let b = a + step;
+            let (sa, ca, sb, cb) = (a.sin(), a.cos(), b.sin(), b.cos());
+            self.curve_to(
+                x + r * ca - k * sa,
+                y + r * sa + k * ca,
+                x + r * cb + k * sb,
+                y + r * sb - k * cb,
+                x + r * cb,
+                y + r * sb,
+            )?;
+            a = b;
+        }
+        Ok(())
+    }
+    /// Draw an arrow from `from` to `to`, with heads as given by
+    /// `style`.
+    ///
+    /// The line and open heads are stroked with the current stroke
+    /// color and line width, while filled heads are painted with the
+    /// current fill color.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::{ArrowHead, ArrowStyle, Color};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_stroke_color(Color::rgb(0, 0, 160))?;
+    /// canvas.set_fill_color(Color::rgb(0, 0, 160))?;
+    /// canvas.arrow((20.0, 20.0), (160.0, 220.0), ArrowStyle::default())?;
+    /// let open = ArrowStyle {
+    ///     end: ArrowHead::Open,
+    ///     start: ArrowHead::Circle,
+    ///     ..Default::default()
+    /// };
+    /// canvas.arrow((20.0, 220.0), (160.0, 20.0), open)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn arrow(
+        &mut self,
+        from: (f32, f32),
+        to: (f32, f32),
+        style: ArrowStyle,
+    ) -> io::Result<()> {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return Ok(());
+        }
+        let dir = (dx / len, dy / len);
+        // Where the line should end, to not poke out of a filled head.
+        let inset = |head: ArrowHead| match head {
+            ArrowHead::None | ArrowHead::Open => 0.0,
+            ArrowHead::Filled => style.length,
+            ArrowHead::Circle => style.width / 2.0,
+        };
+        let a = inset(style.start).min(len);
+        let b = inset(style.end).min(len - a);
+        self.line(
+            from.0 + dir.0 * a,
+            from.1 + dir.1 * a,
+            to.0 - dir.0 * b,
+            to.1 - dir.1 * b,
+        )?;
+        self.stroke()?;
+        self.arrow_head(from, (-dir.0, -dir.1), style.start, &style)?;
+        self.arrow_head(to, dir, style.end, &style)
+    }
+    /// Draw one head of an arrow, with the tip at `tip` and pointing in
+    /// the direction of the unit vector `dir`.
+    fn arrow_head(
+        &mut self,
+        tip: (f32, f32),
+        dir: (f32, f32),
+        head: ArrowHead,
+        style: &ArrowStyle,
+    ) -> io::Result<()> {
+        let (l, w) = (style.length, style.width / 2.0);
+        let base = (tip.0 - dir.0 * l, tip.1 - dir.1 * l);
+        let left = (base.0 - dir.1 * w, base.1 + dir.0 * w);
+        let right = (base.0 + dir.1 * w, base.1 - dir.0 * w);
+        match head {
+            ArrowHead::None => Ok(()),
+            ArrowHead::Open => {
+                self.move_to(left.0, left.1)?;
+                self.line_to(tip.0, tip.1)?;
+                self.line_to(right.0, right.1)?;
+                self.stroke()
+            }
+            ArrowHead::Filled => {
+                self.move_to(left.0, left.1)?;
+                self.line_to(tip.0, tip.1)?;
+                self.line_to(right.0, right.1)?;
+                self.close_path()?;
+                self.fill()
+            }
+            ArrowHead::Circle => {
+                self.circle(tip.0, tip.1, w)?;
+                self.fill()
+            }
+        }
+    }
+    /// Draw a grid with coordinate labels over the canvas, as an aid
+    /// while positioning content.
+    ///
+    /// Thin lines are drawn `spacing` points apart, with every fifth
+    /// line thicker and labeled with its coordinate.
+    /// The graphics state is restored afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.circle(90.0, 120.0, 30.0)?;
+    /// canvas.fill()?;
+    /// canvas.debug_grid(10.0)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn debug_grid(&mut self, spacing: f32) -> io::Result<()> {
+        if spacing <= 0.0 {
+            return Ok(());
+        }
+        let b = self.bounds;
+        let first = |start: f32| (start / spacing).ceil() as i64;
+        let last = |end: f32| (end / spacing).floor() as i64;
+        self.with_state(|c| {
+            c.set_stroke_color(Color::rgb(128, 192, 255))?;
+            c.set_fill_color(Color::rgb(0, 96, 192))?;
+            for &major in &[false, true] {
+                c.set_line_width(if major { 0.5 } else { 0.2 })?;
+                for i in first(b.x)..=last(b.right()) {
+                    if (i % 5 == 0) == major {
+                        let x = i as f32 * spacing;
+                        c.line(x, b.y, x, b.top())?;
+                    }
+                }
+                for i in first(b.y)..=last(b.top()) {
+                    if (i % 5 == 0) == major {
+                        let y = i as f32 * spacing;
+                        c.line(b.x, y, b.right(), y)?;
+                    }
+                }
+                c.stroke()?;
+            }
+            let helvetica = BuiltinFont::Helvetica;
+            for i in first(b.x)..=last(b.right()) {
+                if i % 5 == 0 {
+                    let x = i as f32 * spacing;
+                    let label = format!("{}", x);
+                    c.left_text(x + 1.0, b.y + 1.0, helvetica, 5.0, &label)?;
+                }
+            }
+            for i in first(b.y)..=last(b.top()) {
+                if i % 5 == 0 && i != 0 {
+                    let y = i as f32 * spacing;
+                    let label = format!("{}", y);
+                    c.left_text(b.x + 1.0, y + 1.0, helvetica, 5.0, &label)?;
+                }
+            }
+            Ok(())
+        })
+    }
+    /// Append all segments of a [Path](struct.Path.html) to the current
+    /// path.
+    pub fn add_path(&mut self, path: &Path) -> io::Result<()> {
+        for segment in path.segments() {
+            match *segment {
+                PathSegment::MoveTo(x, y) => self.move_to(x, y)?,
+                PathSegment::LineTo(x, y) => self.line_to(x, y)?,
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    self.curve_to(x1, y1, x2, y2, x3, y3)?
+                }
+                PathSegment::Close => self.close_path()?,
+            }
+        }
+        Ok(())
+    }
+    /// Stroke a [Path](struct.Path.html).
+    pub fn stroke_path(&mut self, path: &Path) -> io::Result<()> {
+        self.add_path(path)?;
+        self.stroke()
+    }
+    /// Fill a [Path](struct.Path.html).
+    pub fn fill_path(&mut self, path: &Path) -> io::Result<()> {
+        self.add_path(path)?;
+        self.fill()
+    }
+    /// Intersect the clipping path with a [Path](struct.Path.html).
+    /// See [clip](#method.clip).
+    pub fn clip_path(&mut self, path: &Path) -> io::Result<()> {
+        self.add_path(path)?;
+        self.clip()
+    }
+    /// Close the current subpath, by a straight line to its start
+    /// point.
+    pub fn close_path(&mut self) -> io::Result<()> {
+        writeln!(self.output, "h")
+    }
     /// Stroke the current path.
     pub fn stroke(&mut self) -> io::Result<()> {
         writeln!(self.output, "S")
@@ -178,20 +1348,154 @@ impl<'a> Canvas<'a> {
     pub fn fill(&mut self) -> io::Result<()> {
         writeln!(self.output, "f")
     }
+    /// Intersect the current clipping path with the current path,
+    /// using the nonzero winding number rule.
+    ///
+    /// Everything painted after this is limited to the inside of the
+    /// path.
+    /// The path itself is ended without being painted.
+    /// To limit the clipping, use [with_state](#method.with_state).
+    pub fn clip(&mut self) -> io::Result<()> {
+        writeln!(self.output, "W n")
+    }
+    /// Paint a shading over the current clipping region.
+    ///
+    /// See [Shading](struct.Shading.html) for an example.
+    pub fn shade(&mut self, shading: Shading) -> io::Result<()> {
+        let name = self.resources.add_shading(shading);
+        writeln!(self.output, "{} sh", name)
+    }
+    /// Paint a linear (axial) gradient from `from` to `to` over the
+    /// current clipping region.
+    ///
+    /// This is a shorthand for `shade(Shading::axial(from, to, stops))`.
+    pub fn shade_axial(
+        &mut self,
+        from: (f32, f32),
+        to: (f32, f32),
+        stops: &[(f32, Color)],
+    ) -> io::Result<()> {
+        self.shade(Shading::axial(from, to, stops))
+    }
+    /// Fill a rectangle with a gradient.
+    ///
+    /// The clipping and graphics state is restored afterwards, so this
+    /// does not affect anything painted later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{GradientSpec, Pdf, Rect};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let header = GradientSpec::vertical(&[
+    ///     (0.0, Color::rgb(0, 64, 128)),
+    ///     (1.0, Color::rgb(64, 160, 255)),
+    /// ]);
+    /// canvas.fill_rect_gradient(Rect::new(0.0, 200.0, 180.0, 40.0), &header)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn fill_rect_gradient(
+        &mut self,
+        rect: Rect,
+        gradient: &GradientSpec,
+    ) -> io::Result<()> {
+        self.with_state(|c| {
+            c.rectangle(rect.x, rect.y, rect.width, rect.height)?;
+            c.clip()?;
+            c.shade(gradient.to_shading(rect))
+        })
+    }
+    /// Set what to paint with for non-stroking operations.
+    ///
+    /// For shadings and patterns, this uses the /Pattern color space.
+    /// Note that shadings and patterns are placed in the default
+    /// coordinate system of the page; they are not affected by
+    /// [concat](#method.concat).
+    /// See [Paint](enum.Paint.html) for an example.
+    pub fn set_fill(&mut self, paint: Paint) -> io::Result<()> {
+        match paint {
+            Paint::Color(color) => self.set_fill_color(color),
+            paint => {
+                let name = self.add_paint_pattern(paint);
+                writeln!(self.output, "/Pattern cs {} scn", name)
+            }
+        }
+    }
+    /// Set what to paint with for stroking operations.
+    ///
+    /// See [set_fill](#method.set_fill) for details.
+    pub fn set_stroke(&mut self, paint: Paint) -> io::Result<()> {
+        match paint {
+            Paint::Color(color) => self.set_stroke_color(color),
+            paint => {
+                let name = self.add_paint_pattern(paint);
+                writeln!(self.output, "/Pattern CS {} SCN", name)
+            }
+        }
+    }
+    fn add_paint_pattern(&mut self, paint: Paint) -> String {
+        self.resources.add_pattern(match paint {
+            Paint::Shading(shading) => PatternResource::Shading(shading),
+            Paint::Pattern(tiling) => PatternResource::Tiling(tiling),
+            Paint::Color(_) => unreachable!("Colors are not patterns"),
+        })
+    }
+    /// Use a shading as the color for non-stroking operations.
+    ///
+    /// This is a shorthand for `set_fill(Paint::Shading(shading))`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Pdf, Shading};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_fill_shading(Shading::radial(
+    ///     (90.0, 120.0),
+    ///     0.0,
+    ///     (90.0, 120.0),
+    ///     80.0,
+    ///     &[(0.0, Color::rgb(255, 255, 0)), (1.0, Color::rgb(255, 0, 0))],
+    /// ))?;
+    /// canvas.circle(90.0, 120.0, 80.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_fill_shading(&mut self, shading: Shading) -> io::Result<()> {
+        self.set_fill(Paint::Shading(shading))
+    }
+    /// Use a shading as the color for stroking operations.
+    ///
+    /// This is a shorthand for `set_stroke(Paint::Shading(shading))`.
+    pub fn set_stroke_shading(&mut self, shading: Shading) -> io::Result<()> {
+        self.set_stroke(Paint::Shading(shading))
+    }
+    /// Use a tiling pattern as the color for non-stroking operations.
+    ///
+    /// A pattern used several times is only written once to the pdf
+    /// file.
+    /// See [TilingPattern](struct.TilingPattern.html) for an example.
+    pub fn set_fill_pattern(
+        &mut self,
+        pattern: &TilingPattern,
+    ) -> io::Result<()> {
+        self.set_fill(Paint::Pattern(pattern.clone()))
+    }
+    /// Use a tiling pattern as the color for stroking operations.
+    pub fn set_stroke_pattern(
+        &mut self,
+        pattern: &TilingPattern,
+    ) -> io::Result<()> {
+        self.set_stroke(Paint::Pattern(pattern.clone()))
+    }
     /// Get a FontRef for a specific font.
     pub fn get_font(&mut self, font: BuiltinFont) -> FontRef {
-        use fontref::create_font_ref;
-        let next_n = self.fonts.len();
-        self.fonts
-            .entry(font)
-            .or_insert_with(|| {
-                create_font_ref(
-                    next_n,
-                    font.get_encoding().clone(),
-                    Arc::new(font.get_metrics()),
-                )
-            })
-            .clone()
+        self.resources.font_ref(font)
     }
 
     /// Create a text object.
@@ -206,10 +1510,241 @@ impl<'a> Canvas<'a> {
     {
         use textobject::create_text_object;
         writeln!(self.output, "BT")?;
-        let result = render_text(&mut create_text_object(self.output))?;
+        let mut text = create_text_object(self.output, self.y_down)?;
+        let result = render_text(&mut text)?;
+        self.resources.uses_rgb |= text.uses_rgb();
         writeln!(self.output, "ET")?;
         Ok(result)
     }
+
+    /// Draw content with `render`, tagged as a structure element of
+    /// type `kind`, to give the document a logical structure.
+    ///
+    /// Tagged elements follow each other in the order they are drawn,
+    /// and elements drawn inside a grouping element, such as a
+    /// [Table](enum.StructureType.html#variant.Table), are its kids.
+    /// Other elements can't contain elements, trying to tag content
+    /// inside them gives an error.
+    /// See [StructureType](enum.StructureType.html) for an example.
+    ///
+    /// Tagging is ignored in patterns, recordings and other content
+    /// that is not directly on a page.
+    pub fn tagged<F, T>(
+        &mut self,
+        kind: StructureType,
+        render: F,
+    ) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        self.tag(StructElement::new(kind), render)
+    }
+
+    /// Draw content with `render`, tagged as a structure element of
+    /// type `kind`, like [tagged](#method.tagged), in another
+    /// natural language than the rest of the document.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf, StructureType};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_language("en-US");
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let font = BuiltinFont::Times_Roman;
+    /// canvas.tagged(StructureType::P, |c| {
+    ///     c.left_text(10.0, 200.0, font, 12.0, "The Germans say:")
+    /// })?;
+    /// canvas.tagged_with_language(StructureType::P, "de-DE", |c| {
+    ///     c.left_text(10.0, 180.0, font, 12.0, "Alles hat ein Ende.")
+    /// })
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn tagged_with_language<F, T>(
+        &mut self,
+        kind: StructureType,
+        language: &str,
+        render: F,
+    ) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        self.tag(StructElement::new(kind).with_language(language), render)
+    }
+
+    /// Draw a table header cell with `render`, tagged as a
+    /// [TH](enum.StructureType.html#variant.TH) element that is a
+    /// header of the cells in its row, its column or both, so screen
+    /// readers can tell what the data cells mean.
+    ///
+    /// The rows and cells of a table are tagged as usual, see
+    /// [tagged](#method.tagged).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf, StructureType, TableScope};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let font = BuiltinFont::Helvetica;
+    /// let rows = [("Fish", "Count"), ("Cod", "3"), ("Sole", "5")];
+    /// canvas.tagged(StructureType::Table, |c| {
+    ///     for (i, &(name, count)) in rows.iter().enumerate() {
+    ///         let y = 200.0 - 14.0 * i as f32;
+    ///         c.tagged(StructureType::TR, |c| {
+    ///             if i == 0 {
+    ///                 c.header_cell(TableScope::Column, |c| {
+    ///                     c.left_text(10.0, y, font, 12.0, name)
+    ///                 })?;
+    ///                 c.header_cell(TableScope::Column, |c| {
+    ///                     c.left_text(90.0, y, font, 12.0, count)
+    ///                 })
+    ///             } else {
+    ///                 c.tagged(StructureType::TD, |c| {
+    ///                     c.left_text(10.0, y, font, 12.0, name)
+    ///                 })?;
+    ///                 c.tagged(StructureType::TD, |c| {
+    ///                     c.left_text(90.0, y, font, 12.0, count)
+    ///                 })
+    ///             }
+    ///         })?;
+    ///     }
+    ///     Ok(())
+    /// })
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn header_cell<F, T>(
+        &mut self,
+        scope: TableScope,
+        render: F,
+    ) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        self.tag(
+            StructElement::new(StructureType::TH).with_scope(scope),
+            render,
+        )
+    }
+
+    /// Draw a figure, such as a drawing or an image, with `render`,
+    /// tagged as a
+    /// [Figure](enum.StructureType.html#variant.Figure) with an
+    /// alternative text, for screen readers to describe it.
+    ///
+    /// See [tagged](#method.tagged) for the details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.figure("A red circle", |c| {
+    ///     c.set_fill_color(Color::rgb(255, 0, 0))?;
+    ///     c.circle(90.0, 120.0, 50.0)?;
+    ///     c.fill()
+    /// })
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn figure<F, T>(&mut self, alt_text: &str, render: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        self.tag(
+            StructElement::new(StructureType::Figure).with_alt(alt_text),
+            render,
+        )
+    }
+
+    /// Draw content with `render`, tagged as `element`.
+    fn tag<F, T>(
+        &mut self,
+        element: StructElement,
+        render: F,
+    ) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        let kind = element.kind;
+        let parent = self.current_element;
+        let index = match self.structure {
+            Some(ref mut structure) => {
+                if let Some(p) = parent {
+                    if !structure[p].kind.is_grouping() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "A {:?} element can't contain a {:?} element",
+                                structure[p].kind, kind,
+                            ),
+                        ));
+                    }
+                }
+                let mcid = if kind.is_grouping() {
+                    None
+                } else {
+                    Some(
+                        structure.iter().filter(|e| e.mcid.is_some()).count(),
+                    )
+                };
+                structure.push(StructElement {
+                    parent,
+                    mcid,
+                    ..element
+                });
+                if let Some(mcid) = mcid {
+                    writeln!(
+                        self.output,
+                        "/{:?} << /MCID {} >> BDC",
+                        kind, mcid
+                    )?;
+                }
+                Some(structure.len() - 1)
+            }
+            None => return render(self),
+        };
+        self.current_element = index;
+        let result = render(self);
+        self.current_element = parent;
+        let result = result?;
+        if !kind.is_grouping() {
+            writeln!(self.output, "EMC")?;
+        }
+        Ok(result)
+    }
+
+    /// Draw content with `render` on a `layer`, so it is only shown
+    /// when the layer is visible.
+    ///
+    /// See [Layer](struct.Layer.html) for an example.
+    pub fn layer<F, T>(&mut self, layer: Layer, render: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        let name = self.resources.layer_name(layer);
+        writeln!(self.output, "/OC {} BDC", name)?;
+        let result = render(self)?;
+        writeln!(self.output, "EMC")?;
+        Ok(result)
+    }
+
+    /// Draw content with `render`, marked as an artifact, i.e. not
+    /// part of the logical structure of the document, such as page
+    /// numbers, headers and decorations.
+    pub fn artifact<F, T>(&mut self, render: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        writeln!(self.output, "/Artifact BMC")?;
+        let result = render(self)?;
+        writeln!(self.output, "EMC")?;
+        Ok(result)
+    }
     /// Utility method for placing a string of text.
     pub fn left_text(
         &mut self,
@@ -261,15 +1796,183 @@ impl<'a> Canvas<'a> {
         })
     }
 
+    /// Get the area covered by `text` in `font` and `size`, drawn with
+    /// the start of its baseline at (x, y) as by
+    /// [left_text](#method.left_text).
+    ///
+    /// The area extends from 20 % of the size below the baseline to
+    /// 80 % above it, which covers most characters of the builtin
+    /// fonts.
+    /// This is useful for placing annotations on text.
+    pub fn text_bounds(
+        &self,
+        x: f32,
+        y: f32,
+        font: BuiltinFont,
+        size: f32,
+        text: &str,
+    ) -> Rect {
+        let width = font.get_width(size, text);
+        if self.y_down {
+            Rect::new(x, y - 0.8 * size, width, size)
+        } else {
+            Rect::new(x, y - 0.2 * size, width, size)
+        }
+    }
+
     /// Add an item for this page in the document outline.
     ///
     /// An outline item associates a name (contained in an ordered
-    /// tree) with a location in the document.  The PDF standard
-    /// supports several ways to specify an exact location on a page,
-    /// but this implementation currently only supports linking to a
-    /// specific page (the page that this Canvas is for).
+    /// tree) with a location in the document, here the top of the
+    /// page that this Canvas is for.  Use
+    /// [add_outline_item](#method.add_outline_item) to go to another
+    /// position on the page.
     pub fn add_outline(&mut self, title: &str) {
-        self.outline_items.push(OutlineItem::new(title));
+        self.add_outline_level(0, title);
+    }
+
+    /// Add an item for this page in the document outline, nested at
+    /// the given level.
+    ///
+    /// Level 0 is the top level of the outline.  An item with a
+    /// higher level is nested below the last item before it (in this
+    /// page or earlier pages) with a lower level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     canvas.add_outline_level(0, "Chapter 1");
+    ///     canvas.add_outline_level(1, "Section 1.1");
+    ///     Ok(())
+    /// }).unwrap();
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     canvas.add_outline_level(1, "Section 1.2");
+    ///     canvas.add_outline_level(2, "Section 1.2.1");
+    ///     Ok(())
+    /// }).unwrap();
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     canvas.add_outline("Chapter 2");
+    ///     Ok(())
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn add_outline_level(&mut self, level: usize, title: &str) {
+        self.add_outline_item(OutlineItem::new(title).with_level(level));
+    }
+
+    /// Add an item for this page in the document outline.
+    ///
+    /// See [OutlineItem](struct.OutlineItem.html) for an example.
+    pub fn add_outline_item(&mut self, item: OutlineItem) {
+        self.outline_items.push(item);
+    }
+
+    /// Add an annotation, such as a link, to this page.
+    ///
+    /// The rectangle of the annotation is given in the coordinate
+    /// system of the page (including its
+    /// [Origin](enum.Origin.html)), regardless of any transformations
+    /// on the canvas.
+    /// Annotations added in patterns, recordings and other content
+    /// that is not directly on a page are ignored.
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        let mut annotation = annotation;
+        if self.y_down {
+            annotation.flip_y(self.bounds.height);
+        }
+        self.annotations.push(annotation);
+    }
+
+    /// Add a form field, with all its widgets, to this page.
+    ///
+    /// The widgets are placed like annotations, see
+    /// [add_annotation](#method.add_annotation).
+    /// The appearances of the widgets are created here, which is why
+    /// this can fail.
+    pub fn add_field(&mut self, field: FormField) -> io::Result<()> {
+        let mut field = field;
+        let widgets = field.take_widgets();
+        let field = Arc::new(field);
+        for (rect, state) in widgets {
+            self.add_annotation(Annotation::widget(
+                rect,
+                field.clone(),
+                state,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Add a link to `destination`, in this document, on `rect` of
+    /// this page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Destination, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     let font = BuiltinFont::Helvetica;
+    ///     canvas.left_text(10.0, 200.0, font, 12.0, "See page 2")?;
+    ///     let rect = Rect::new(10.0, 196.0, 70.0, 16.0);
+    ///     canvas.link(rect, Destination::at(1, 0.0, 240.0));
+    ///     Ok(())
+    /// }).unwrap();
+    /// document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn link(&mut self, rect: Rect, destination: Destination) {
+        self.add_annotation(Annotation::link(rect, Action::GoTo(destination)))
+    }
+
+    /// Draw `text` as a link to `url`, in blue and underlined, with
+    /// the start of its baseline at (x, y) as by
+    /// [left_text](#method.left_text).
+    ///
+    /// The link covers the area given by
+    /// [text_bounds](#method.text_bounds), so it is only placed
+    /// correctly on a canvas without transformations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, FontSource, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let font = BuiltinFont::Helvetica;
+    /// canvas.left_text(10.0, 200.0, font, 10.0, "More at")?;
+    /// let x = 10.0 + font.get_width(10.0, "More at ");
+    /// canvas.link_text(x, 200.0, font, 10.0, "example.com", "https://example.com/")
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn link_text(
+        &mut self,
+        x: f32,
+        y: f32,
+        font: BuiltinFont,
+        size: f32,
+        text: &str,
+        url: &str,
+    ) -> io::Result<()> {
+        let bounds = self.text_bounds(x, y, font, size, text);
+        self.with_state(|c| {
+            let blue = Color::rgb(0, 0, 238);
+            c.set_fill_color(blue)?;
+            c.left_text(x, y, font, size, text)?;
+            // The underline is a tenth of the size below the baseline.
+            let offset = if c.y_down { 0.1 * size } else { -0.1 * size };
+            c.set_stroke_color(blue)?;
+            c.set_line_width(0.05 * size)?;
+            c.line(x, y + offset, x + bounds.width, y + offset)?;
+            c.stroke()
+        })?;
+        let link = Annotation::link(bounds, Action::Uri(url.to_string()));
+        self.add_annotation(link);
+        Ok(())
     }
 
     /// Save the current graphics state.
@@ -282,4 +1985,53 @@ impl<'a> Canvas<'a> {
     pub fn grestore(&mut self) -> io::Result<()> {
         writeln!(self.output, "Q")
     }
+    /// Save the current graphics state.
+    ///
+    /// Same as [gsave](#method.gsave).
+    /// Each call must be matched by a call to
+    /// [restore_state](#method.restore_state), consider using
+    /// [with_state](#method.with_state) instead.
+    pub fn save_state(&mut self) -> io::Result<()> {
+        self.gsave()
+    }
+    /// Restore the graphics state saved by the latest
+    /// [save_state](#method.save_state).
+    pub fn restore_state(&mut self) -> io::Result<()> {
+        self.grestore()
+    }
+    /// Apply `render` with a saved graphics state.
+    ///
+    /// Changes to the graphics state made by `render` (transformation,
+    /// colors, line styles, etc) only apply until `render` returns.
+    /// The state is restored even if `render` returns an error.
+    /// On success, return the value returned by `render`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # use pdf_canvas::graphicsstate::{Color, Matrix};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.with_state(|c| {
+    ///     c.concat(Matrix::rotate_deg(30.0))?;
+    ///     c.set_fill_color(Color::rgb(255, 0, 0))?;
+    ///     c.rectangle(50.0, 50.0, 40.0, 20.0)?;
+    ///     c.fill()
+    /// })?;
+    /// // Not rotated, and not red.
+    /// canvas.rectangle(50.0, 100.0, 40.0, 20.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn with_state<F, T>(&mut self, render: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<T>,
+    {
+        self.save_state()?;
+        let result = render(self);
+        self.restore_state()?;
+        result
+    }
 }
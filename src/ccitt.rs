@@ -0,0 +1,433 @@
+//! An encoder for CCITT Group 4 (T.6) compression of bilevel images,
+//! as used by the CCITTFaxDecode filter with /K -1.
+
+/// Encode a bilevel image.
+///
+/// The data has one bit per pixel, with each row starting on a new
+/// byte, the most significant bit first, and 1 for black.
+pub fn encode_g4(width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+    let width = width as usize;
+    let row_bytes = (width + 7) / 8;
+    let mut out = BitWriter::default();
+    // The reference line for the first row is an imaginary white row.
+    let mut reference = vec![false; width];
+    let mut coding = vec![false; width];
+    for row in data.chunks(row_bytes).take(height as usize) {
+        for (x, pixel) in coding.iter_mut().enumerate() {
+            *pixel = row[x / 8] & (0x80 >> (x % 8)) != 0;
+        }
+        encode_row(&mut out, &reference, &coding);
+        std::mem::swap(&mut reference, &mut coding);
+    }
+    // End of facsimile block.
+    out.put(1, 12);
+    out.put(1, 12);
+    out.finish()
+}
+
+/// Encode one row, using the previous row as reference.
+fn encode_row(out: &mut BitWriter, reference: &[bool], coding: &[bool]) {
+    let width = coding.len();
+    let mut a0: isize = -1;
+    let mut color = false;
+    while a0 < width as isize {
+        let a1 = next_change(coding, a0);
+        let mut b1 = next_change(reference, a0);
+        if b1 < width && reference[b1] == color {
+            b1 = next_change(reference, b1 as isize);
+        }
+        let b2 = next_change(reference, b1 as isize);
+        if b2 < a1 {
+            // Pass mode.
+            out.put(0b0001, 4);
+            a0 = b2 as isize;
+        } else if (a1 as isize - b1 as isize).abs() <= 3 {
+            // Vertical mode.
+            let (code, len) = match a1 as isize - b1 as isize {
+                0 => (0b1, 1),
+                1 => (0b011, 3),
+                2 => (0b000011, 6),
+                3 => (0b0000011, 7),
+                -1 => (0b010, 3),
+                -2 => (0b000010, 6),
+                _ => (0b0000010, 7),
+            };
+            out.put(code, len);
+            a0 = a1 as isize;
+            color = !color;
+        } else {
+            // Horizontal mode.
+            let a2 = next_change(coding, a1 as isize);
+            out.put(0b001, 3);
+            put_run(out, color, a1 - a0.max(0) as usize);
+            put_run(out, !color, a2 - a1);
+            a0 = a2 as isize;
+        }
+    }
+}
+
+/// Find the first changing element after `start` in `line`, that is
+/// the first pixel with another color than the pixel before it.
+/// The pixel before the line is white, and if there is no change, the
+/// result is the width of the line.
+fn next_change(line: &[bool], start: isize) -> usize {
+    let from = (start + 1).max(0) as usize;
+    (from..line.len())
+        .find(|&x| line[x] != (x > 0 && line[x - 1]))
+        .unwrap_or(line.len())
+}
+
+/// Write the code for a run of `len` pixels of one color.
+fn put_run(out: &mut BitWriter, black: bool, mut len: usize) {
+    let (terminating, makeup) = if black {
+        (&BLACK_TERMINATING, &BLACK_MAKEUP)
+    } else {
+        (&WHITE_TERMINATING, &WHITE_MAKEUP)
+    };
+    while len >= 2560 {
+        let (code, bits) = EXTENDED_MAKEUP[EXTENDED_MAKEUP.len() - 1];
+        out.put(code, bits);
+        len -= 2560;
+    }
+    if len >= 64 {
+        let (code, bits) = if len >= 1792 {
+            EXTENDED_MAKEUP[len / 64 - 28]
+        } else {
+            makeup[len / 64 - 1]
+        };
+        out.put(code, bits);
+        len %= 64;
+    }
+    let (code, bits) = terminating[len];
+    out.put(code, bits);
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    /// Write the `len` least significant bits of `code`.
+    fn put(&mut self, code: u16, len: u8) {
+        self.current = self.current << len | u32::from(code);
+        self.bits += len;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.current >> self.bits) as u8);
+        }
+        self.current &= (1 << self.bits) - 1;
+    }
+
+    /// Pad the last byte with zero bits and return the bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            let pad = 8 - self.bits;
+            self.put(0, pad);
+        }
+        self.bytes
+    }
+}
+
+/// Codes for white runs of 0 to 63 pixels.
+const WHITE_TERMINATING: [(u16, u8); 64] = [
+    (0b00110101, 8), // 0
+    (0b000111, 6),   // 1
+    (0b0111, 4),     // 2
+    (0b1000, 4),     // 3
+    (0b1011, 4),     // 4
+    (0b1100, 4),     // 5
+    (0b1110, 4),     // 6
+    (0b1111, 4),     // 7
+    (0b10011, 5),    // 8
+    (0b10100, 5),    // 9
+    (0b00111, 5),    // 10
+    (0b01000, 5),    // 11
+    (0b001000, 6),   // 12
+    (0b000011, 6),   // 13
+    (0b110100, 6),   // 14
+    (0b110101, 6),   // 15
+    (0b101010, 6),   // 16
+    (0b101011, 6),   // 17
+    (0b0100111, 7),  // 18
+    (0b0001100, 7),  // 19
+    (0b0001000, 7),  // 20
+    (0b0010111, 7),  // 21
+    (0b0000011, 7),  // 22
+    (0b0000100, 7),  // 23
+    (0b0101000, 7),  // 24
+    (0b0101011, 7),  // 25
+    (0b0010011, 7),  // 26
+    (0b0100100, 7),  // 27
+    (0b0011000, 7),  // 28
+    (0b00000010, 8), // 29
+    (0b00000011, 8), // 30
+    (0b00011010, 8), // 31
+    (0b00011011, 8), // 32
+    (0b00010010, 8), // 33
+    (0b00010011, 8), // 34
+    (0b00010100, 8), // 35
+    (0b00010101, 8), // 36
+    (0b00010110, 8), // 37
+    (0b00010111, 8), // 38
+    (0b00101000, 8), // 39
+    (0b00101001, 8), // 40
+    (0b00101010, 8), // 41
+    (0b00101011, 8), // 42
+    (0b00101100, 8), // 43
+    (0b00101101, 8), // 44
+    (0b00000100, 8), // 45
+    (0b00000101, 8), // 46
+    (0b00001010, 8), // 47
+    (0b00001011, 8), // 48
+    (0b01010010, 8), // 49
+    (0b01010011, 8), // 50
+    (0b01010100, 8), // 51
+    (0b01010101, 8), // 52
+    (0b00100100, 8), // 53
+    (0b00100101, 8), // 54
+    (0b01011000, 8), // 55
+    (0b01011001, 8), // 56
+    (0b01011010, 8), // 57
+    (0b01011011, 8), // 58
+    (0b01001010, 8), // 59
+    (0b01001011, 8), // 60
+    (0b00110010, 8), // 61
+    (0b00110011, 8), // 62
+    (0b00110100, 8), // 63
+];
+
+/// Codes for white runs of 64 to 1728 pixels, in steps of 64.
+const WHITE_MAKEUP: [(u16, u8); 27] = [
+    (0b11011, 5),     // 64
+    (0b10010, 5),     // 128
+    (0b010111, 6),    // 192
+    (0b0110111, 7),   // 256
+    (0b00110110, 8),  // 320
+    (0b00110111, 8),  // 384
+    (0b01100100, 8),  // 448
+    (0b01100101, 8),  // 512
+    (0b01101000, 8),  // 576
+    (0b01100111, 8),  // 640
+    (0b011001100, 9), // 704
+    (0b011001101, 9), // 768
+    (0b011010010, 9), // 832
+    (0b011010011, 9), // 896
+    (0b011010100, 9), // 960
+    (0b011010101, 9), // 1024
+    (0b011010110, 9), // 1088
+    (0b011010111, 9), // 1152
+    (0b011011000, 9), // 1216
+    (0b011011001, 9), // 1280
+    (0b011011010, 9), // 1344
+    (0b011011011, 9), // 1408
+    (0b010011000, 9), // 1472
+    (0b010011001, 9), // 1536
+    (0b010011010, 9), // 1600
+    (0b011000, 6),    // 1664
+    (0b010011011, 9), // 1728
+];
+
+/// Codes for black runs of 0 to 63 pixels.
+const BLACK_TERMINATING: [(u16, u8); 64] = [
+    (0b0000110111, 10),   // 0
+    (0b010, 3),           // 1
+    (0b11, 2),            // 2
+    (0b10, 2),            // 3
+    (0b011, 3),           // 4
+    (0b0011, 4),          // 5
+    (0b0010, 4),          // 6
+    (0b00011, 5),         // 7
+    (0b000101, 6),        // 8
+    (0b000100, 6),        // 9
+    (0b0000100, 7),       // 10
+    (0b0000101, 7),       // 11
+    (0b0000111, 7),       // 12
+    (0b00000100, 8),      // 13
+    (0b00000111, 8),      // 14
+    (0b000011000, 9),     // 15
+    (0b0000010111, 10),   // 16
+    (0b0000011000, 10),   // 17
+    (0b0000001000, 10),   // 18
+    (0b00001100111, 11),  // 19
+    (0b00001101000, 11),  // 20
+    (0b00001101100, 11),  // 21
+    (0b00000110111, 11),  // 22
+    (0b00000101000, 11),  // 23
+    (0b00000010111, 11),  // 24
+    (0b00000011000, 11),  // 25
+    (0b000011001010, 12), // 26
+    (0b000011001011, 12), // 27
+    (0b000011001100, 12), // 28
+    (0b000011001101, 12), // 29
+    (0b000001101000, 12), // 30
+    (0b000001101001, 12), // 31
+    (0b000001101010, 12), // 32
+    (0b000001101011, 12), // 33
+    (0b000011010010, 12), // 34
+    (0b000011010011, 12), // 35
+    (0b000011010100, 12), // 36
+    (0b000011010101, 12), // 37
+    (0b000011010110, 12), // 38
+    (0b000011010111, 12), // 39
+    (0b000001101100, 12), // 40
+    (0b000001101101, 12), // 41
+    (0b000011011010, 12), // 42
+    (0b000011011011, 12), // 43
+    (0b000001010100, 12), // 44
+    (0b000001010101, 12), // 45
+    (0b000001010110, 12), // 46
+    (0b000001010111, 12), // 47
+    (0b000001100100, 12), // 48
+    (0b000001100101, 12), // 49
+    (0b000001010010, 12), // 50
+    (0b000001010011, 12), // 51
+    (0b000000100100, 12), // 52
+    (0b000000110111, 12), // 53
+    (0b000000111000, 12), // 54
+    (0b000000100111, 12), // 55
+    (0b000000101000, 12), // 56
+    (0b000001011000, 12), // 57
+    (0b000001011001, 12), // 58
+    (0b000000101011, 12), // 59
+    (0b000000101100, 12), // 60
+    (0b000001011010, 12), // 61
+    (0b000001100110, 12), // 62
+    (0b000001100111, 12), // 63
+];
+
+/// Codes for black runs of 64 to 1728 pixels, in steps of 64.
+const BLACK_MAKEUP: [(u16, u8); 27] = [
+    (0b0000001111, 10),    // 64
+    (0b000011001000, 12),  // 128
+    (0b000011001001, 12),  // 192
+    (0b000001011011, 12),  // 256
+    (0b000000110011, 12),  // 320
+    (0b000000110100, 12),  // 384
+    (0b000000110101, 12),  // 448
+    (0b0000001101100, 13), // 512
+    (0b0000001101101, 13), // 576
+    (0b0000001001010, 13), // 640
+    (0b0000001001011, 13), // 704
+    (0b0000001001100, 13), // 768
+    (0b0000001001101, 13), // 832
+    (0b0000001110010, 13), // 896
+    (0b0000001110011, 13), // 960
+    (0b0000001110100, 13), // 1024
+    (0b0000001110101, 13), // 1088
+    (0b0000001110110, 13), // 1152
+    (0b0000001110111, 13), // 1216
+    (0b0000001010010, 13), // 1280
+    (0b0000001010011, 13), // 1344
+    (0b0000001010100, 13), // 1408
+    (0b0000001010101, 13), // 1472
+    (0b0000001011010, 13), // 1536
+    (0b0000001011011, 13), // 1600
+    (0b0000001100100, 13), // 1664
+    (0b0000001100101, 13), // 1728
+];
+
+/// Codes for runs of 1792 to 2560 pixels of either color, in
+/// steps of 64.
+const EXTENDED_MAKEUP: [(u16, u8); 13] = [
+    (0b00000001000, 11),  // 1792
+    (0b00000001100, 11),  // 1856
+    (0b00000001101, 11),  // 1920
+    (0b000000010010, 12), // 1984
+    (0b000000010011, 12), // 2048
+    (0b000000010100, 12), // 2112
+    (0b000000010101, 12), // 2176
+    (0b000000010110, 12), // 2240
+    (0b000000010111, 12), // 2304
+    (0b000000011100, 12), // 2368
+    (0b000000011101, 12), // 2432
+    (0b000000011110, 12), // 2496
+    (0b000000011111, 12), // 2560
+];
+
+#[test]
+fn test_encode_all_white() {
+    // A white row is a single V0, followed by the end of block.
+    assert_eq!(
+        vec![0b1000_0000, 0b0000_1000, 0b0000_0000, 0b1000_0000],
+        encode_g4(16, 1, &[0, 0]),
+    );
+}
+
+/// Pack a string of `0` and `1` into bytes, padded with zero bits.
+/// Spaces are ignored.
+#[cfg(test)]
+fn pack(bits: &str) -> Vec<u8> {
+    let bits = bits.bytes().filter(|&b| b != b' ').collect::<Vec<_>>();
+    bits.chunks(8)
+        .map(|byte| {
+            byte.iter().enumerate().fold(0, |acc, (i, &b)| {
+                acc | (u8::from(b == b'1') << (7 - i))
+            })
+        })
+        .collect()
+}
+
+/// End of facsimile block.
+#[cfg(test)]
+const EOFB: &str = "000000000001 000000000001";
+
+#[test]
+fn test_encode_vertical() {
+    // The second row is the same as the first, so each of its changes
+    // is coded as V0.
+    let data = [0b0001_1000, 0b0001_1000];
+    let expected = format!("001 1000 11 1  1 1 1  {}", EOFB);
+    assert_eq!(pack(&expected), encode_g4(8, 2, &data));
+}
+
+#[test]
+fn test_encode_mixed_rows() {
+    let data = [0b1100_0000, 0b0110_0000, 0b1000_0000, 0b0000_0000];
+    let expected = format!(
+        "{} {} {} {} {}",
+        // Horizontal: white 0, black 2, then V0 at the end.
+        "001 00110101 11 1",
+        // VR1 twice, V0.
+        "011 011 1",
+        // VL1, VL2, V0.
+        "010 000010 1",
+        // Pass the black run above, and V0.
+        "0001 1",
+        EOFB,
+    );
+    assert_eq!(pack(&expected), encode_g4(8, 4, &data));
+}
+
+#[test]
+fn test_encode_long_runs() {
+    // A row of `white` white pixels and then `black` black pixels.
+    let row = |white: usize, black: usize| {
+        let width = white + black;
+        let mut row = vec![0; (width + 7) / 8];
+        for x in white..width {
+            row[x / 8] |= 0x80 >> (x % 8);
+        }
+        (width as u32, row)
+    };
+    // Runs above 1728 use the makeup codes shared by both colors.
+    let (width, data) = row(1800, 200);
+    let expected =
+        format!("001 00000001000 10011 000011001001 000101 {}", EOFB);
+    assert_eq!(pack(&expected), encode_g4(width, 1, &data));
+    // Runs above 2560 repeat the code for 2560.
+    let (width, data) = row(2600, 400);
+    let expected = format!(
+        "001 000000011111 00101001 000000110100 0000010111 {}",
+        EOFB,
+    );
+    assert_eq!(pack(&expected), encode_g4(width, 1, &data));
+    let (width, data) = row(5200, 8);
+    let expected = format!(
+        "001 000000011111 000000011111 11011 101010 000101 {}",
+        EOFB,
+    );
+    assert_eq!(pack(&expected), encode_g4(width, 1, &data));
+}
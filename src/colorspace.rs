@@ -0,0 +1,167 @@
+use graphicsstate::{CieColorSpace, Color, SpotColor};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use Pdf;
+
+/// A color space used in a content stream, that needs a resource.
+#[derive(Clone, Debug)]
+pub enum ColorSpace {
+    Separation(SpotColor),
+    Cie(CieColorSpace),
+}
+
+impl ColorSpace {
+    /// The parameters of this color space, with f32 compared by bit
+    /// pattern so the color space can be used as a hash key.
+    fn key(&self) -> (Option<&SpotColor>, Vec<u32>) {
+        match *self {
+            ColorSpace::Separation(ref spot) => (Some(spot), vec![]),
+            ColorSpace::Cie(CieColorSpace::Lab { white_point, range }) => (
+                None,
+                white_point
+                    .iter()
+                    .chain(&range)
+                    .map(|v| v.to_bits())
+                    .collect(),
+            ),
+            ColorSpace::Cie(CieColorSpace::CalRGB {
+                white_point,
+                gamma,
+                matrix,
+            }) => (
+                None,
+                // Not ambiguous with Lab, since the length differs.
+                white_point
+                    .iter()
+                    .chain(&gamma)
+                    .chain(&matrix)
+                    .map(|v| v.to_bits())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl PartialEq for ColorSpace {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ColorSpace {}
+
+impl Hash for ColorSpace {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl ColorSpace {
+    /// Write the color space array as a new object in the pdf.
+    pub fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        pdf.write_new_object(|object_id, pdf| {
+            match *self {
+                ColorSpace::Separation(ref spot) => {
+                    let (alternate, no_ink, full_ink) =
+                        tint_range(spot.get_alternate());
+                    write!(
+                        pdf.output,
+                        "[ /Separation /{} /{}\n  \
+                         << /FunctionType 2 /Domain [ 0 1 ] /C0 [",
+                        escape_name(spot.get_name()),
+                        alternate,
+                    )?;
+                    for c in &no_ink {
+                        write!(pdf.output, " {}", c)?;
+                    }
+                    write!(pdf.output, " ] /C1 [")?;
+                    for c in &full_ink {
+                        write!(pdf.output, " {}", c)?;
+                    }
+                    writeln!(pdf.output, " ] /N 1 >> ]")?;
+                }
+                ColorSpace::Cie(CieColorSpace::Lab {
+                    white_point,
+                    range,
+                }) => {
+                    writeln!(
+                        pdf.output,
+                        "[ /Lab << /WhitePoint [ {} ] /Range [ {} ] >> ]",
+                        join(&white_point),
+                        join(&range),
+                    )?;
+                }
+                ColorSpace::Cie(CieColorSpace::CalRGB {
+                    white_point,
+                    gamma,
+                    matrix,
+                }) => {
+                    writeln!(
+                        pdf.output,
+                        "[ /CalRGB << /WhitePoint [ {} ] /Gamma [ {} ]\n   \
+                         /Matrix [ {} ] >> ]",
+                        join(&white_point),
+                        join(&gamma),
+                        join(&matrix),
+                    )?;
+                }
+            }
+            Ok(object_id)
+        })
+    }
+}
+
+/// Get the name of the device color space of `color`, and the
+/// components for no ink (white) and for `color` in that color space,
+/// for the tint transform function of a separation.
+fn tint_range(color: Color) -> (&'static str, Vec<f32>, Vec<f32>) {
+    let norm = |c| f32::from(c) / 255.0;
+    match color {
+        Color::RGB { red, green, blue } => (
+            "DeviceRGB",
+            vec![1., 1., 1.],
+            vec![norm(red), norm(green), norm(blue)],
+        ),
+        Color::Gray { gray } => ("DeviceGray", vec![1.], vec![norm(gray)]),
+        Color::CMYK {
+            cyan,
+            magenta,
+            yellow,
+            black,
+        } => (
+            "DeviceCMYK",
+            vec![0., 0., 0., 0.],
+            vec![norm(cyan), norm(magenta), norm(yellow), norm(black)],
+        ),
+    }
+}
+
+fn join(values: &[f32]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Escape a string to be usable as a PDF name (without the leading
+/// slash), as described in section 7.3.5 of the PDF specification.
+pub fn escape_name(name: &str) -> String {
+    let mut result = String::new();
+    for &b in name.as_bytes() {
+        match b {
+            b'!'..=b'~' if !b"#%()/<>[]{}".contains(&b) => {
+                result.push(char::from(b))
+            }
+            b => result.push_str(&format!("#{:02X}", b)),
+        }
+    }
+    result
+}
+
+#[test]
+fn test_escape_name() {
+    assert_eq!("PANTONE#20300#20C", escape_name("PANTONE 300 C"));
+    assert_eq!("A#2FB#23C", escape_name("A/B#C"));
+    assert_eq!("R#C3#A4d", escape_name("Räd"));
+}
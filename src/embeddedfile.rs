@@ -0,0 +1,210 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use pdfstring::{ascii_string, date, name, text_string};
+use std::io::{self, Write};
+use std::sync::Arc;
+use time;
+use Pdf;
+
+/// A file embedded in the pdf, as described in section 7.11.4 of the
+/// PDF specification.
+///
+/// The file can be attached to a place on a page with
+/// [Annotation::file_attachment](struct.Annotation.html#method.file_attachment).
+/// The data is shared by clones of the file.
+#[derive(Clone, Debug)]
+pub struct EmbeddedFile {
+    name: String,
+    data: Arc<Vec<u8>>,
+    mime_type: Option<String>,
+    description: Option<String>,
+    relationship: Option<FileRelationship>,
+    modified: Option<time::Tm>,
+}
+
+/// How an embedded file relates to the document, for files associated
+/// with the whole document, as in PDF/A-3.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{EmbeddedFile, FileRelationship, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// // The machine readable version of an invoice.
+/// let xml = b"<Invoice><Total>17.00</Total></Invoice>".to_vec();
+/// document.add_embedded_file(
+///     EmbeddedFile::new("factur-x.xml", xml)
+///         .with_mime_type("text/xml")
+///         .with_relationship(FileRelationship::Alternative),
+/// );
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileRelationship {
+    /// The original source of the document, e.g. a word processor file.
+    Source,
+    /// The data that the document shows, e.g. a table as csv.
+    Data,
+    /// An alternative representation of the document.
+    Alternative,
+    /// Additional content for the document.
+    Supplement,
+    /// Some other or unknown relationship.
+    Unspecified,
+}
+
+impl EmbeddedFile {
+    /// Create a file with `name` (as shown to the user) and contents
+    /// `data`.
+    pub fn new(name: &str, data: Vec<u8>) -> Self {
+        EmbeddedFile {
+            name: name.to_string(),
+            data: Arc::new(data),
+            mime_type: None,
+            description: None,
+            relationship: None,
+            modified: None,
+        }
+    }
+
+    /// Set the MIME type of the file, such as `text/csv`.
+    pub fn with_mime_type(mut self, mime_type: &str) -> Self {
+        self.mime_type = Some(mime_type.to_string());
+        self
+    }
+
+    /// Set a description of the file, shown by viewers.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Associate the file with the whole document, in the given
+    /// relationship, rather than just attaching it.
+    ///
+    /// This only has effect for files added to the document with
+    /// [Pdf::add_embedded_file](struct.Pdf.html#method.add_embedded_file).
+    pub fn with_relationship(
+        mut self,
+        relationship: FileRelationship,
+    ) -> Self {
+        self.relationship = Some(relationship);
+        self
+    }
+
+    /// Set when the file was last modified, in seconds since the Unix
+    /// epoch (UTC).
+    ///
+    /// By default, the creation time of the document is used, or no
+    /// time at all in a
+    /// [deterministic](struct.Pdf.html#method.set_deterministic)
+    /// document.
+    /// PDF/A-3 validators expect a modification date on files
+    /// associated with the document, such as the xml of an electronic
+    /// invoice, so set it for those in deterministic documents.
+    ///
+    /// ```
+    /// # use pdf_canvas::{EmbeddedFile, FileRelationship, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_deterministic(true);
+    /// let xml = b"<Invoice><Total>17.00</Total></Invoice>".to_vec();
+    /// document.add_embedded_file(
+    ///     EmbeddedFile::new("factur-x.xml", xml)
+    ///         .with_relationship(FileRelationship::Alternative)
+    ///         .with_modification_time(1_700_000_000),
+    /// );
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn with_modification_time(mut self, seconds: i64) -> Self {
+        self.modified = Some(time::at_utc(time::Timespec::new(seconds, 0)));
+        self
+    }
+
+    /// Get the name of the file.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the relationship of the file to the document, if it is
+    /// associated with the document.
+    pub(crate) fn relationship(&self) -> Option<FileRelationship> {
+        self.relationship
+    }
+
+    /// Write the embedded file stream and its file specification.
+    /// Return the object id of the file specification.
+    pub(crate) fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&self.data)?;
+        let compressed = encoder.finish()?;
+        let stream_id = pdf.write_new_object(|object_id, pdf| {
+            write!(pdf.output, "<< /Type /EmbeddedFile")?;
+            if let Some(ref mime_type) = self.mime_type {
+                write!(pdf.output, " /Subtype {}", name(mime_type))?;
+            }
+            write!(pdf.output, "\n   /Params << /Size {}", self.data.len())?;
+            if let Some(time) = self.modified.or_else(|| pdf.creation_time())
+            {
+                write!(pdf.output, " /ModDate {}", date(&time))?;
+            }
+            writeln!(
+                pdf.output,
+                " >>\n   \
+                 /Filter /FlateDecode /Length {} >>\n\
+                 stream",
+                compressed.len(),
+            )?;
+            pdf.output.write_all(&compressed)?;
+            writeln!(pdf.output, "\nendstream")?;
+            Ok(object_id)
+        })?;
+        pdf.write_new_object(|object_id, pdf| {
+            write!(
+                pdf.output,
+                "<< /Type /Filespec /F {} /UF {}\n   \
+                 /EF << /F {} 0 R >>",
+                ascii_string(&self.name),
+                text_string(&self.name),
+                stream_id,
+            )?;
+            if let Some(ref description) = self.description {
+                write!(
+                    pdf.output,
+                    "\n   /Desc {}",
+                    text_string(description)
+                )?;
+            }
+            if let Some(relationship) = self.relationship {
+                write!(
+                    pdf.output,
+                    "\n   /AFRelationship /{:?}",
+                    relationship
+                )?;
+            }
+            writeln!(pdf.output, " >>")?;
+            Ok(object_id)
+        })
+    }
+}
+
+#[test]
+fn test_modification_time() {
+    let filename = "test_modification_time.pdf";
+    let mut document = Pdf::create(filename).unwrap();
+    document.set_deterministic(true);
+    for &(name, seconds) in &[("a.txt", 0), ("b.txt", 86400)] {
+        let file = EmbeddedFile::new(name, b"data".to_vec());
+        document.add_embedded_file(file.with_modification_time(seconds));
+    }
+    document.add_embedded_file(EmbeddedFile::new("c.txt", b"c".to_vec()));
+    document.finish().unwrap();
+    let output = ::std::fs::read(filename).unwrap();
+    ::std::fs::remove_file(filename).unwrap();
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("/ModDate (D:19700101000000+00'00')"));
+    assert!(output.contains("/ModDate (D:19700102000000+00'00')"));
+    // Without a time of its own, no time is written for the file.
+    assert_eq!(2, output.matches("/ModDate").count());
+}
@@ -0,0 +1,149 @@
+use graphicsstate::{BlendMode, SoftMaskType};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use Pdf;
+
+/// A set of graphics state parameters that can only be set through a
+/// named /ExtGState resource, as described in section 8.4.5 of the
+/// PDF specification.
+///
+/// User code sets these with methods on the Canvas, such as
+/// `set_fill_alpha`.
+#[derive(Clone, Debug, Default)]
+pub struct ExtGState {
+    fill_alpha: Option<f32>,
+    stroke_alpha: Option<f32>,
+    blend_mode: Option<BlendMode>,
+    soft_mask: Option<SoftMask>,
+}
+
+/// The soft mask part of an ExtGState.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoftMask {
+    /// No soft mask, removes any soft mask set earlier.
+    None,
+    /// A mask defined by a form, given as an index in the `forms` of
+    /// the resources using this state.
+    Form(SoftMaskType, usize),
+}
+
+impl ExtGState {
+    pub fn fill_alpha(alpha: f32) -> Self {
+        ExtGState {
+            fill_alpha: Some(alpha.clamp(0.0, 1.0)),
+            ..Default::default()
+        }
+    }
+
+    pub fn stroke_alpha(alpha: f32) -> Self {
+        ExtGState {
+            stroke_alpha: Some(alpha.clamp(0.0, 1.0)),
+            ..Default::default()
+        }
+    }
+
+    pub fn alpha(alpha: f32) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        ExtGState {
+            fill_alpha: Some(alpha),
+            stroke_alpha: Some(alpha),
+            ..Default::default()
+        }
+    }
+
+    pub fn blend_mode(mode: BlendMode) -> Self {
+        ExtGState {
+            blend_mode: Some(mode),
+            ..Default::default()
+        }
+    }
+
+    pub fn soft_mask(mask: SoftMask) -> Self {
+        ExtGState {
+            soft_mask: Some(mask),
+            ..Default::default()
+        }
+    }
+
+    /// True if this state makes anything transparent, or blends it
+    /// with the backdrop.
+    pub fn is_transparent(&self) -> bool {
+        self.fill_alpha.map_or(false, |a| a < 1.0)
+            || self.stroke_alpha.map_or(false, |a| a < 1.0)
+            || self.blend_mode.map_or(false, |m| m != BlendMode::Normal)
+            || matches!(self.soft_mask, Some(SoftMask::Form(..)))
+    }
+
+    /// True if this state refers to no objects specific for the
+    /// resources it is used in, so it can be shared between pages.
+    pub fn is_shareable(&self) -> bool {
+        !matches!(self.soft_mask, Some(SoftMask::Form(..)))
+    }
+
+    /// Write the ExtGState dictionary as a new object in the pdf.
+    ///
+    /// The `forms` are the object ids of the forms in the resources
+    /// using this state.
+    pub fn write_object(
+        &self,
+        pdf: &mut Pdf,
+        forms: &[usize],
+    ) -> io::Result<usize> {
+        pdf.write_new_object(|object_id, pdf| {
+            write!(pdf.output, "<< /Type /ExtGState")?;
+            if let Some(alpha) = self.fill_alpha {
+                write!(pdf.output, " /ca {}", alpha)?;
+            }
+            if let Some(alpha) = self.stroke_alpha {
+                write!(pdf.output, " /CA {}", alpha)?;
+            }
+            if let Some(mode) = self.blend_mode {
+                write!(pdf.output, " /BM /{:?}", mode)?;
+            }
+            match self.soft_mask {
+                Some(SoftMask::None) => write!(pdf.output, " /SMask /None")?,
+                Some(SoftMask::Form(mask_type, i)) => write!(
+                    pdf.output,
+                    " /SMask << /Type /Mask /S /{:?} /G {} 0 R >>",
+                    mask_type, forms[i],
+                )?,
+                None => (),
+            }
+            writeln!(pdf.output, " >>")?;
+            Ok(object_id)
+        })
+    }
+
+    /// The values of this state, with f32 compared by bit pattern so
+    /// the state can be used as a hash key.
+    #[allow(clippy::type_complexity)]
+    fn key(
+        &self,
+    ) -> (
+        Option<u32>,
+        Option<u32>,
+        Option<BlendMode>,
+        Option<SoftMask>,
+    ) {
+        (
+            self.fill_alpha.map(f32::to_bits),
+            self.stroke_alpha.map(f32::to_bits),
+            self.blend_mode,
+            self.soft_mask,
+        )
+    }
+}
+
+impl PartialEq for ExtGState {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ExtGState {}
+
+impl Hash for ExtGState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
@@ -0,0 +1,631 @@
+use action::Action;
+use annotation::appearance_form;
+use canvas::Canvas;
+use fontsource::{BuiltinFont, FontSource};
+use formxobject::FormXObject;
+use graphicsstate::Color;
+use pdfstring::{name, text_string};
+use rect::Rect;
+use std::io::{self, Write};
+use PageInfo;
+
+/// An interactive form field, as described in section 12.7 of the PDF
+/// specification.
+///
+/// A field has one or more widgets, the areas on the page where it is
+/// shown and edited.
+/// Add a field to a page with
+/// [Canvas::add_field](struct.Canvas.html#method.add_field).
+/// Each field in a document should have a unique name, which must not
+/// contain a period.
+#[derive(Clone)]
+pub struct FormField {
+    name: String,
+    kind: FieldKind,
+    tooltip: Option<String>,
+    read_only: bool,
+    required: bool,
+    font: BuiltinFont,
+    font_size: f32,
+    tab_index: Option<u32>,
+    widgets: Vec<(Rect, Option<String>)>,
+}
+
+/// The order in which the user tabs through the annotations (such as
+/// form fields) of a page, as set by
+/// [Pdf::set_tab_order](struct.Pdf.html#method.set_tab_order).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabOrder {
+    /// The order of the annotations on the page, which is the order
+    /// they are added in, unless a field has a
+    /// [tab index](struct.FormField.html#method.with_tab_index).
+    Annotations,
+    /// Row by row, from the top of the page.
+    Row,
+    /// Column by column, from the left of the page.
+    Column,
+    /// The order of the logical structure of the document.
+    Structure,
+}
+
+impl Default for TabOrder {
+    fn default() -> Self {
+        TabOrder::Annotations
+    }
+}
+
+impl TabOrder {
+    /// The value of the /Tabs entry of the page, if any.
+    pub(crate) fn to_pdf(self) -> Option<&'static str> {
+        match self {
+            TabOrder::Annotations => None,
+            TabOrder::Row => Some("/R"),
+            TabOrder::Column => Some("/C"),
+            TabOrder::Structure => Some("/S"),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum FieldKind {
+    Radio {
+        selected: Option<String>,
+    },
+    Choice {
+        options: Vec<String>,
+        selected: Option<String>,
+        combo: bool,
+        editable: bool,
+    },
+    Button {
+        label: String,
+        action: Action,
+    },
+    Signature,
+}
+
+impl FormField {
+    /// Create a group of radio buttons, of which at most one can be
+    /// selected at a time.
+    ///
+    /// Each button is given by its rectangle and its export value, the
+    /// value of the field when the button is selected.
+    /// The export values should be distinct.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, FormField, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let font = BuiltinFont::Helvetica;
+    /// let mut buttons = Vec::new();
+    /// for (i, size) in ["Small", "Medium", "Large"].iter().enumerate() {
+    ///     let y = 200.0 - 20.0 * i as f32;
+    ///     canvas.left_text(30.0, y, font, 10.0, size)?;
+    ///     buttons.push((Rect::new(10.0, y - 2.0, 12.0, 12.0), *size));
+    /// }
+    /// let group = FormField::radio_group("size", &buttons, Some("Medium"));
+    /// canvas.add_field(group)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn radio_group(
+        name: &str,
+        buttons: &[(Rect, &str)],
+        selected: Option<&str>,
+    ) -> Self {
+        let widgets = buttons
+            .iter()
+            .map(|&(rect, value)| (rect, Some(value.to_string())))
+            .collect();
+        FormField::new(
+            name,
+            FieldKind::Radio {
+                selected: selected.map(|s| s.to_string()),
+            },
+            widgets,
+        )
+    }
+
+    /// Create a combo box, a drop-down list of `options` at `rect`,
+    /// with the option `selected` chosen by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{FormField, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let rect = Rect::new(10.0, 200.0, 100.0, 16.0);
+    /// let colors = ["Red", "Green", "Blue"];
+    /// let field = FormField::combo_box("color", rect, &colors, Some("Green"))
+    ///     .with_editable(true);
+    /// canvas.add_field(field)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn combo_box(
+        name: &str,
+        rect: Rect,
+        options: &[&str],
+        selected: Option<&str>,
+    ) -> Self {
+        FormField::choice(name, rect, options, selected, true)
+    }
+
+    /// Create a list box, a scrollable list of `options` at `rect`,
+    /// with the option `selected` chosen by default.
+    pub fn list_box(
+        name: &str,
+        rect: Rect,
+        options: &[&str],
+        selected: Option<&str>,
+    ) -> Self {
+        FormField::choice(name, rect, options, selected, false)
+    }
+
+    /// Create a push button at `rect`, showing `label`, that does
+    /// `action` when clicked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Action, FormField, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let rect = Rect::new(10.0, 10.0, 60.0, 20.0);
+    /// let help = Action::Uri("https://example.com/help".to_string());
+    /// canvas.add_field(FormField::push_button("help", rect, "Help", help))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn push_button(
+        name: &str,
+        rect: Rect,
+        label: &str,
+        action: Action,
+    ) -> Self {
+        FormField::new(
+            name,
+            FieldKind::Button {
+                label: label.to_string(),
+                action,
+            },
+            vec![(rect, None)],
+        )
+    }
+
+    /// Create an empty signature field at `rect`, a place for a
+    /// signing tool to put a digital signature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{FormField, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let rect = Rect::new(10.0, 10.0, 120.0, 40.0);
+    /// let field = FormField::signature("approval", rect)
+    ///     .with_tooltip("Signature of the approver");
+    /// canvas.add_field(field)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn signature(name: &str, rect: Rect) -> Self {
+        FormField::new(name, FieldKind::Signature, vec![(rect, None)])
+    }
+
+    fn choice(
+        name: &str,
+        rect: Rect,
+        options: &[&str],
+        selected: Option<&str>,
+        combo: bool,
+    ) -> Self {
+        FormField::new(
+            name,
+            FieldKind::Choice {
+                options: options.iter().map(|o| o.to_string()).collect(),
+                selected: selected.map(|s| s.to_string()),
+                combo,
+                editable: false,
+            },
+            vec![(rect, None)],
+        )
+    }
+
+    fn new(
+        name: &str,
+        kind: FieldKind,
+        widgets: Vec<(Rect, Option<String>)>,
+    ) -> Self {
+        FormField {
+            name: name.to_string(),
+            kind,
+            tooltip: None,
+            read_only: false,
+            required: false,
+            font: BuiltinFont::Helvetica,
+            font_size: 10.0,
+            tab_index: None,
+            widgets,
+        }
+    }
+
+    /// Set a description of the field, shown by viewers when the
+    /// mouse is over it.
+    pub fn with_tooltip(mut self, tooltip: &str) -> Self {
+        self.tooltip = Some(tooltip.to_string());
+        self
+    }
+
+    /// Set whether the user is prevented from changing the field.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set whether the field must have a value when the form is
+    /// submitted.
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Set whether the user can enter a value that is not among the
+    /// options of a combo box.
+    /// This has no effect on other kinds of fields.
+    pub fn with_editable(mut self, editable: bool) -> Self {
+        if let FieldKind::Choice {
+            combo: true,
+            editable: ref mut e,
+            ..
+        } = self.kind
+        {
+            *e = editable;
+        }
+        self
+    }
+
+    /// Set the font and size of the text of the field.
+    /// The default is 10 pt Helvetica.
+    pub fn with_font(mut self, font: BuiltinFont, size: f32) -> Self {
+        self.font = font;
+        self.font_size = size;
+        self
+    }
+
+    /// Set the position of the field in the tab order of its page.
+    ///
+    /// The widgets of a page are ordered by their tab index, before
+    /// any widgets without one, which keep the order they were added
+    /// in.
+    /// This is the order that viewers follow when the
+    /// [TabOrder](enum.TabOrder.html) of the page is `Annotations`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{FormField, Pdf, Rect};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// let no = Rect::new(60.0, 10.0, 40.0, 20.0);
+    /// let yes = Rect::new(10.0, 10.0, 40.0, 20.0);
+    /// // Tab to "Yes" first, even though it is added last.
+    /// canvas.add_field(FormField::signature("no", no).with_tab_index(2))?;
+    /// canvas.add_field(FormField::signature("yes", yes).with_tab_index(1))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn with_tab_index(mut self, index: u32) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
+    /// The position of the field in the tab order, if set.
+    pub(crate) fn tab_index(&self) -> Option<u32> {
+        self.tab_index
+    }
+
+    /// The name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Take the widgets of the field, each given by its rectangle and
+    /// its on state, if it has one.
+    pub(crate) fn take_widgets(&mut self) -> Vec<(Rect, Option<String>)> {
+        ::std::mem::take(&mut self.widgets)
+    }
+
+    /// Create the normal appearance of a widget of this field, of size
+    /// `width` by `height`, unless it has appearance states.
+    pub(crate) fn widget_appearance(
+        &self,
+        width: f32,
+        height: f32,
+    ) -> io::Result<Option<FormXObject>> {
+        let (font, size) = (self.font, self.font_size);
+        let form = match self.kind {
+            FieldKind::Radio { .. } => return Ok(None),
+            FieldKind::Button { ref label, .. } => {
+                appearance_form(width, height, |c| {
+                    field_box(c, width, height, BUTTON_COLOR)?;
+                    c.set_fill_color(Color::gray(0))?;
+                    let y = baseline(0.0, height, size);
+                    c.center_text(width / 2.0, y, font, size, label)
+                })?
+            }
+            FieldKind::Choice {
+                ref options,
+                ref selected,
+                combo,
+                ..
+            } => appearance_form(width, height, |c| {
+                field_box(c, width, height, Color::gray(255))?;
+                c.rectangle(1.0, 1.0, width - 2.0, height - 2.0)?;
+                c.clip()?;
+                if combo {
+                    if let Some(ref value) = *selected {
+                        c.set_fill_color(Color::gray(0))?;
+                        let y = baseline(0.0, height, size);
+                        c.left_text(2.0, y, font, size, value)?;
+                    }
+                    return Ok(());
+                }
+                let line = 1.15 * size;
+                for (i, option) in options.iter().enumerate() {
+                    let bottom = height - 1.0 - (i + 1) as f32 * line;
+                    if bottom + line < 0.0 {
+                        break;
+                    }
+                    if selected.as_ref() == Some(option) {
+                        c.set_fill_color(SELECTION_COLOR)?;
+                        c.rectangle(1.0, bottom, width - 2.0, line)?;
+                        c.fill()?;
+                    }
+                    c.set_fill_color(Color::gray(0))?;
+                    let y = baseline(bottom, line, size);
+                    c.left_text(2.0, y, font, size, option)?;
+                }
+                Ok(())
+            })?,
+            FieldKind::Signature => appearance_form(width, height, |c| {
+                c.set_stroke_color(Color::gray(128))?;
+                c.rectangle(0.5, 0.5, width - 1.0, height - 1.0)?;
+                c.stroke()
+            })?,
+        };
+        Ok(Some(form))
+    }
+
+    /// The action of the widgets of the field, if any.
+    pub(crate) fn widget_action(
+        &self,
+        pages: &[PageInfo],
+    ) -> io::Result<Option<String>> {
+        match self.kind {
+            FieldKind::Button { ref action, .. } => {
+                action.to_pdf(pages).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The appearance characteristics of the widgets of the field, if
+    /// any.
+    pub(crate) fn widget_characteristics(&self) -> Option<String> {
+        match self.kind {
+            FieldKind::Button { ref label, .. } => Some(format!(
+                "<< /BC [ 0 ] /BG [ {} ] /CA {} >>",
+                f32::from(BUTTON_GRAY) / 255.0,
+                text_string(label),
+            )),
+            FieldKind::Choice { .. } => {
+                Some("<< /BC [ 0 ] /BG [ 1 ] >>".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Create the appearance states of a widget of this field, of
+    /// size `width` by `height`, with the on state `state`.
+    pub(crate) fn widget_appearances(
+        &self,
+        width: f32,
+        height: f32,
+        state: Option<&str>,
+    ) -> io::Result<Vec<(String, FormXObject)>> {
+        match self.kind {
+            FieldKind::Choice { .. }
+            | FieldKind::Button { .. }
+            | FieldKind::Signature => Ok(Vec::new()),
+            FieldKind::Radio { .. } => {
+                let mut states = Vec::new();
+                if let Some(state) = state {
+                    let on = radio_appearance(width, height, true)?;
+                    states.push((state.to_string(), on));
+                }
+                let off = radio_appearance(width, height, false)?;
+                states.push(("Off".to_string(), off));
+                Ok(states)
+            }
+        }
+    }
+
+    /// The font used for the text of the field, if it has any text.
+    pub(crate) fn text_font(&self) -> Option<BuiltinFont> {
+        match self.kind {
+            FieldKind::Radio { .. } | FieldKind::Signature => None,
+            FieldKind::Choice { .. } | FieldKind::Button { .. } => {
+                Some(self.font)
+            }
+        }
+    }
+
+    /// Whether a widget with the on state `state` is currently on.
+    pub(crate) fn is_on(&self, state: &str) -> bool {
+        match self.kind {
+            FieldKind::Choice { .. }
+            | FieldKind::Button { .. }
+            | FieldKind::Signature => false,
+            FieldKind::Radio { ref selected } => {
+                selected.as_deref() == Some(state)
+            }
+        }
+    }
+
+    /// Write the field dictionary, with the (already written) widget
+    /// annotations `kids`.
+    pub(crate) fn write_dictionary(
+        &self,
+        output: &mut dyn Write,
+        kids: &[usize],
+    ) -> io::Result<()> {
+        let mut flags = 0;
+        if self.read_only {
+            flags |= 1;
+        }
+        if self.required {
+            flags |= 1 << 1;
+        }
+        write!(output, "<< /T {}\n   ", text_string(&self.name))?;
+        if let Some(ref tooltip) = self.tooltip {
+            write!(output, "/TU {}\n   ", text_string(tooltip))?;
+        }
+        match self.kind {
+            FieldKind::Radio { ref selected } => {
+                // Radio and NoToggleToOff
+                flags |= 1 << 15 | 1 << 14;
+                write!(output, "/FT /Btn")?;
+                match *selected {
+                    Some(ref value) => write!(output, " /V {}", name(value))?,
+                    None => write!(output, " /V /Off")?,
+                }
+            }
+            FieldKind::Choice {
+                ref options,
+                ref selected,
+                combo,
+                editable,
+            } => {
+                if combo {
+                    flags |= 1 << 17;
+                }
+                if editable {
+                    flags |= 1 << 18;
+                }
+                write!(output, "/FT /Ch /Opt [")?;
+                for option in options {
+                    write!(output, " {}", text_string(option))?;
+                }
+                write!(output, " ]")?;
+                if let Some(ref value) = *selected {
+                    let string = text_string(value);
+                    write!(output, "\n   /V {} /DV {}", string, string)?;
+                    if let Some(i) = options.iter().position(|o| o == value) {
+                        write!(output, " /I [ {} ]", i)?;
+                    }
+                }
+            }
+            FieldKind::Button { .. } => {
+                // Pushbutton
+                flags |= 1 << 16;
+                write!(output, "/FT /Btn")?;
+            }
+            FieldKind::Signature => write!(output, "/FT /Sig")?,
+        }
+        if let Some(font) = self.text_font() {
+            write!(
+                output,
+                "\n   /DA (/{} {} Tf 0 g)",
+                font.pdf_name(),
+                self.font_size,
+            )?;
+        }
+        write!(output, " /Ff {}\n   /Kids [", flags)?;
+        for kid in kids {
+            write!(output, " {} 0 R", kid)?;
+        }
+        writeln!(output, " ]\n>>")
+    }
+}
+
+/// The gray level of the background of push buttons.
+const BUTTON_GRAY: u8 = 192;
+const BUTTON_COLOR: Color = Color::Gray { gray: BUTTON_GRAY };
+
+/// The color of the selected option in list boxes.
+const SELECTION_COLOR: Color = Color::RGB {
+    red: 153,
+    green: 193,
+    blue: 218,
+};
+
+/// Draw the background and border of a field.
+fn field_box(
+    c: &mut Canvas,
+    width: f32,
+    height: f32,
+    background: Color,
+) -> io::Result<()> {
+    c.set_fill_color(background)?;
+    c.rectangle(0.0, 0.0, width, height)?;
+    c.fill()?;
+    c.set_stroke_color(Color::gray(0))?;
+    c.rectangle(0.5, 0.5, width - 1.0, height - 1.0)?;
+    c.stroke()
+}
+
+/// The baseline for text of `size`, vertically centered in a line
+/// from `bottom` and `height` high.
+fn baseline(bottom: f32, height: f32, size: f32) -> f32 {
+    // Center the capitals, which are about 70 % of the size.
+    bottom + (height - 0.7 * size) / 2.0
+}
+
+/// The appearance of a radio button, a circle with a dot in it when
+/// it is selected.
+fn radio_appearance(
+    width: f32,
+    height: f32,
+    selected: bool,
+) -> io::Result<FormXObject> {
+    appearance_form(width, height, |c| {
+        let r = width.min(height) / 2.0;
+        let (x, y) = (width / 2.0, height / 2.0);
+        c.set_fill_color(Color::gray(255))?;
+        c.set_stroke_color(Color::gray(0))?;
+        c.circle(x, y, r - 0.5)?;
+        c.fill()?;
+        c.circle(x, y, r - 0.5)?;
+        c.stroke()?;
+        if selected {
+            c.set_fill_color(Color::gray(0))?;
+            c.circle(x, y, r / 2.0)?;
+            c.fill()?;
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn test_radio_group_dictionary() {
+    let buttons = [
+        (Rect::new(0.0, 0.0, 10.0, 10.0), "a b"),
+        (Rect::new(0.0, 20.0, 10.0, 10.0), "c"),
+    ];
+    let mut group = FormField::radio_group("choice", &buttons, Some("a b"))
+        .with_required(true);
+    assert_eq!(2, group.take_widgets().len());
+    assert!(group.is_on("a b"));
+    assert!(!group.is_on("c"));
+    let mut output = Vec::new();
+    group.write_dictionary(&mut output, &[4, 5]).unwrap();
+    assert_eq!(
+        "<< /T (choice)\n   /FT /Btn /V /a#20b /Ff 49154\n   \
+         /Kids [ 4 0 R 5 0 R ]\n>>\n",
+        String::from_utf8(output).unwrap(),
+    );
+}
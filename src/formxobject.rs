@@ -0,0 +1,98 @@
+use graphicsstate::TransparencyGroup;
+use resources::Resources;
+use std::io::{self, Write};
+use std::sync::Arc;
+use Pdf;
+
+/// A form XObject, a self-contained content stream with its own
+/// resources, as described in section 8.10 of the PDF specification.
+///
+/// Forms are created by rendering to a separate canvas, and written
+/// to the pdf file after the content stream that uses them.
+/// The content is shared by clones of a form.
+#[derive(Clone)]
+pub struct FormXObject {
+    bbox: [f32; 4],
+    group: Option<TransparencyGroup>,
+    content: Arc<Vec<u8>>,
+    resources: Arc<Resources>,
+    shared_id: Option<usize>,
+}
+
+impl FormXObject {
+    pub fn new(
+        bbox: [f32; 4],
+        content: Vec<u8>,
+        resources: Resources,
+    ) -> Self {
+        FormXObject {
+            bbox,
+            group: None,
+            content: Arc::new(content),
+            resources: Arc::new(resources),
+            shared_id: None,
+        }
+    }
+
+    /// Mark this form as shared, so it is only written once in the pdf
+    /// file, regardless of how many content streams uses it.
+    /// The `id` should be unique for the form content.
+    pub fn set_shared_id(&mut self, id: usize) {
+        self.shared_id = Some(id);
+    }
+
+    /// The id of the form, if it is shared.
+    pub fn shared_id(&self) -> Option<usize> {
+        self.shared_id
+    }
+
+    /// True if this form is a transparency group.
+    pub fn is_group(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// Make this form a transparency group.
+    pub fn set_group(&mut self, group: TransparencyGroup) {
+        self.group = Some(group);
+    }
+
+    /// Write the form, and any resources it needs, to the pdf.
+    /// Return the object id of the form.
+    pub fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        let resources = pdf.write_resources(&self.resources)?;
+        pdf.write_new_object(|object_id, pdf| {
+            let b = self.bbox;
+            write!(
+                pdf.output,
+                "<< /Type /XObject /Subtype /Form\n   \
+                 /BBox [ {} {} {} {} ]\n   \
+                 /Resources {}\n   ",
+                b[0], b[1], b[2], b[3], resources,
+            )?;
+            if let Some(group) = self.group {
+                write!(
+                    pdf.output,
+                    "/Group << /S /Transparency /CS /DeviceRGB"
+                )?;
+                if group.isolated {
+                    write!(pdf.output, " /I true")?;
+                }
+                if group.knockout {
+                    write!(pdf.output, " /K true")?;
+                }
+                write!(pdf.output, " >>\n   ")?;
+            }
+            let prologue = pdf.content_prologue();
+            writeln!(
+                pdf.output,
+                "/Length {} >>\n\
+                 stream",
+                prologue.len() + self.content.len(),
+            )?;
+            pdf.output.write_all(prologue)?;
+            pdf.output.write_all(&self.content)?;
+            writeln!(pdf.output, "endstream")?;
+            Ok(object_id)
+        })
+    }
+}
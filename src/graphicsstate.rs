@@ -30,13 +30,208 @@ pub enum CapStyle {
     ProjectingSquare,
 }
 
+/// Shapes for the ends of an arrow drawn by
+/// [Canvas::arrow](../struct.Canvas.html#method.arrow).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrowHead {
+    /// No decoration, the line just ends.
+    None,
+    /// Two stroked lines forming an open "V".
+    Open,
+    /// A filled triangle.
+    Filled,
+    /// A filled circle.
+    Circle,
+}
+
+/// The style of an arrow drawn by
+/// [Canvas::arrow](../struct.Canvas.html#method.arrow).
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::Pdf;
+/// # use pdf_canvas::graphicsstate::{ArrowHead, ArrowStyle};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// // A dimension line, with arrows in both ends.
+/// let style = ArrowStyle {
+///     start: ArrowHead::Filled,
+///     length: 6.0,
+///     ..Default::default()
+/// };
+/// canvas.arrow((20.0, 40.0), (160.0, 40.0), style)
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArrowStyle {
+    /// The decoration at the start point of the arrow.
+    pub start: ArrowHead,
+    /// The decoration at the end point of the arrow.
+    pub end: ArrowHead,
+    /// The length of the heads, along the line.
+    pub length: f32,
+    /// The width of the heads, across the line.
+    pub width: f32,
+}
+
+impl Default for ArrowStyle {
+    /// A plain arrow, with a filled triangle at the end point.
+    fn default() -> Self {
+        ArrowStyle {
+            start: ArrowHead::None,
+            end: ArrowHead::Filled,
+            length: 8.0,
+            width: 6.0,
+        }
+    }
+}
+
+/// The look of a shadow drawn by
+/// [Canvas::drop_shadow](../struct.Canvas.html#method.drop_shadow).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    /// How far the shadow is moved from the shape, in x and y.
+    pub offset: (f32, f32),
+    /// The width of the soft edge of the shadow.
+    /// Zero gives a shadow with a sharp edge.
+    pub blur: f32,
+    /// The color of the shadow.
+    pub color: Color,
+    /// The opacity of the shadow, from 0.0 (invisible) to 1.0.
+    pub alpha: f32,
+}
+
+impl Default for Shadow {
+    /// A soft black shadow, down and to the right.
+    fn default() -> Self {
+        Shadow {
+            offset: (2.0, -2.0),
+            blur: 4.0,
+            color: Color::gray(0),
+            alpha: 0.3,
+        }
+    }
+}
+
+/// Blend modes, as described in section 11.3.5 of the PDF
+/// specification.
+///
+/// The blend mode decides how colors painted on the page are combined
+/// with what is already there.
+/// The separable modes work on each color component by itself, while
+/// `Hue`, `Saturation`, `Color` and `Luminosity` are non-separable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Paint the source color, ignoring the backdrop.  This is the
+    /// default.
+    Normal,
+    /// Multiply the backdrop and source colors.  The result is never
+    /// lighter than either of them.
+    Multiply,
+    /// Multiply the complements of the backdrop and source colors.
+    /// The result is never darker than either of them.
+    Screen,
+    /// Multiply or screen, depending on the backdrop color.
+    Overlay,
+    /// Select the darker of the backdrop and source colors.
+    Darken,
+    /// Select the lighter of the backdrop and source colors.
+    Lighten,
+    /// Brighten the backdrop to reflect the source color.
+    ColorDodge,
+    /// Darken the backdrop to reflect the source color.
+    ColorBurn,
+    /// Multiply or screen, depending on the source color.
+    HardLight,
+    /// Darken or lighten, depending on the source color.
+    SoftLight,
+    /// Subtract the darker of the colors from the lighter one.
+    Difference,
+    /// Like `Difference`, but with lower contrast.
+    Exclusion,
+    /// The hue of the source with the saturation and luminosity of
+    /// the backdrop.
+    Hue,
+    /// The saturation of the source with the hue and luminosity of
+    /// the backdrop.
+    Saturation,
+    /// The hue and saturation of the source with the luminosity of
+    /// the backdrop.
+    Color,
+    /// The luminosity of the source with the hue and saturation of
+    /// the backdrop.
+    Luminosity,
+}
+
+/// How a soft mask is derived from the content defining it, as
+/// described in section 11.6.5 of the PDF specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoftMaskType {
+    /// The mask is the luminosity of the mask content; white is
+    /// opaque and black is fully transparent.
+    Luminosity,
+    /// The mask is the alpha (opacity) of the mask content.
+    Alpha,
+}
+
+/// Options for a transparency group, as described in section 11.4.7
+/// of the PDF specification.
+///
+/// Content drawn in a transparency group is composited by itself
+/// first, and the result is then painted on the page as a single
+/// object, using the alpha and blend mode that were current when the
+/// group was started.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::Pdf;
+/// # use pdf_canvas::graphicsstate::{Color, TransparencyGroup};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// let group = TransparencyGroup {
+///     knockout: true,
+///     ..Default::default()
+/// };
+/// canvas.set_fill_alpha(0.5)?;
+/// // The overlapping circles will not show through each other.
+/// canvas.transparency_group(group, 0., 0., 180., 120., |c| {
+///     c.set_fill_color(Color::rgb(255, 0, 0))?;
+///     c.circle(60., 60., 40.)?;
+///     c.fill()?;
+///     c.circle(100., 60., 40.)?;
+///     c.fill()
+/// })
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TransparencyGroup {
+    /// If true, the group is composited on a fully transparent
+    /// backdrop, rather than on what is already painted on the page.
+    pub isolated: bool,
+    /// If true, each object in the group is composited with the
+    /// backdrop of the group rather than with earlier objects in the
+    /// group, so objects knock out rather than show through each other.
+    pub knockout: bool,
+}
+
 /// Any color (or grayscale) value that this library can make PDF represent.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Color {
     #[doc(hidden)]
     RGB { red: u8, green: u8, blue: u8 },
     #[doc(hidden)]
     Gray { gray: u8 },
+    #[doc(hidden)]
+    CMYK {
+        cyan: u8,
+        magenta: u8,
+        yellow: u8,
+        black: u8,
+    },
 }
 
 impl Color {
@@ -65,6 +260,436 @@ impl Color {
     pub fn gray(gray: u8) -> Self {
         Color::Gray { gray }
     }
+
+    /// Return a color from a hexadecimal RGB notation, as used in CSS.
+    ///
+    /// Both the six digit (`#rrggbb`) and three digit (`#rgb`) forms
+    /// are accepted, and the leading `#` is optional.
+    /// Return None if `hex` is not a valid color.
+    ///
+    /// # Example
+    /// ````
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// assert_eq!(Some(Color::rgb(26, 115, 232)), Color::from_hex("#1a73e8"));
+    /// assert_eq!(Some(Color::rgb(255, 204, 0)), Color::from_hex("fc0"));
+    /// assert_eq!(None, Color::from_hex("#12345"));
+    /// ````
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+        let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 => Some(Color::rgb(
+                digit(0)? * 17,
+                digit(1)? * 17,
+                digit(2)? * 17,
+            )),
+            6 => Some(Color::rgb(pair(0)?, pair(2)?, pair(4)?)),
+            _ => None,
+        }
+    }
+
+    /// Return a color by its name, for the named colors in CSS.
+    ///
+    /// The name is case insensitive.
+    /// Return None if `name` is not a known color name.
+    ///
+    /// # Example
+    /// ````
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// assert_eq!(Some(Color::rgb(102, 51, 153)),
+    ///            Color::from_name("RebeccaPurple"));
+    /// assert_eq!(Some(Color::rgb(255, 165, 0)), Color::from_name("orange"));
+    /// assert_eq!(None, Color::from_name("blurple"));
+    /// ````
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        NAMED_COLORS
+            .binary_search_by(|&(n, _, _, _)| n.cmp(&name[..]))
+            .ok()
+            .map(|i| {
+                let (_, red, green, blue) = NAMED_COLORS[i];
+                Color::rgb(red, green, blue)
+            })
+    }
+
+    /// Return a color from a CSS-style color specification, either a
+    /// hexadecimal RGB value or a color name.
+    ///
+    /// # Example
+    /// ````
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// assert_eq!(Color::from_css("#ff0000"), Color::from_css("Red"));
+    /// ````
+    pub fn from_css(spec: &str) -> Option<Self> {
+        Color::from_hex(spec).or_else(|| Color::from_name(spec))
+    }
+
+    /// Return a color from a CMYK colorspace, for print.
+    ///
+    /// Each component ranges from 0 (no ink) to 255 (full ink).
+    /// Colors created this way are painted in the DeviceCMYK color
+    /// space, so they are passed unconverted to the printing device.
+    ///
+    /// # Example
+    /// ````
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// let rich_black = Color::cmyk(153, 102, 102, 255);
+    /// let cyan       = Color::cmyk(255, 0, 0, 0);
+    /// let white      = Color::cmyk(0, 0, 0, 0);
+    /// ````
+    pub fn cmyk(cyan: u8, magenta: u8, yellow: u8, black: u8) -> Self {
+        Color::CMYK {
+            cyan,
+            magenta,
+            yellow,
+            black,
+        }
+    }
+}
+
+/// The CSS named colors, sorted by name.
+const NAMED_COLORS: &[(&str, u8, u8, u8)] = &[
+    ("aliceblue", 0xf0, 0xf8, 0xff),
+    ("antiquewhite", 0xfa, 0xeb, 0xd7),
+    ("aqua", 0x00, 0xff, 0xff),
+    ("aquamarine", 0x7f, 0xff, 0xd4),
+    ("azure", 0xf0, 0xff, 0xff),
+    ("beige", 0xf5, 0xf5, 0xdc),
+    ("bisque", 0xff, 0xe4, 0xc4),
+    ("black", 0x00, 0x00, 0x00),
+    ("blanchedalmond", 0xff, 0xeb, 0xcd),
+    ("blue", 0x00, 0x00, 0xff),
+    ("blueviolet", 0x8a, 0x2b, 0xe2),
+    ("brown", 0xa5, 0x2a, 0x2a),
+    ("burlywood", 0xde, 0xb8, 0x87),
+    ("cadetblue", 0x5f, 0x9e, 0xa0),
+    ("chartreuse", 0x7f, 0xff, 0x00),
+    ("chocolate", 0xd2, 0x69, 0x1e),
+    ("coral", 0xff, 0x7f, 0x50),
+    ("cornflowerblue", 0x64, 0x95, 0xed),
+    ("cornsilk", 0xff, 0xf8, 0xdc),
+    ("crimson", 0xdc, 0x14, 0x3c),
+    ("cyan", 0x00, 0xff, 0xff),
+    ("darkblue", 0x00, 0x00, 0x8b),
+    ("darkcyan", 0x00, 0x8b, 0x8b),
+    ("darkgoldenrod", 0xb8, 0x86, 0x0b),
+    ("darkgray", 0xa9, 0xa9, 0xa9),
+    ("darkgreen", 0x00, 0x64, 0x00),
+    ("darkgrey", 0xa9, 0xa9, 0xa9),
+    ("darkkhaki", 0xbd, 0xb7, 0x6b),
+    ("darkmagenta", 0x8b, 0x00, 0x8b),
+    ("darkolivegreen", 0x55, 0x6b, 0x2f),
+    ("darkorange", 0xff, 0x8c, 0x00),
+    ("darkorchid", 0x99, 0x32, 0xcc),
+    ("darkred", 0x8b, 0x00, 0x00),
+    ("darksalmon", 0xe9, 0x96, 0x7a),
+    ("darkseagreen", 0x8f, 0xbc, 0x8f),
+    ("darkslateblue", 0x48, 0x3d, 0x8b),
+    ("darkslategray", 0x2f, 0x4f, 0x4f),
+    ("darkslategrey", 0x2f, 0x4f, 0x4f),
+    ("darkturquoise", 0x00, 0xce, 0xd1),
+    ("darkviolet", 0x94, 0x00, 0xd3),
+    ("deeppink", 0xff, 0x14, 0x93),
+    ("deepskyblue", 0x00, 0xbf, 0xff),
+    ("dimgray", 0x69, 0x69, 0x69),
+    ("dimgrey", 0x69, 0x69, 0x69),
+    ("dodgerblue", 0x1e, 0x90, 0xff),
+    ("firebrick", 0xb2, 0x22, 0x22),
+    ("floralwhite", 0xff, 0xfa, 0xf0),
+    ("forestgreen", 0x22, 0x8b, 0x22),
+    ("fuchsia", 0xff, 0x00, 0xff),
+    ("gainsboro", 0xdc, 0xdc, 0xdc),
+    ("ghostwhite", 0xf8, 0xf8, 0xff),
+    ("gold", 0xff, 0xd7, 0x00),
+    ("goldenrod", 0xda, 0xa5, 0x20),
+    ("gray", 0x80, 0x80, 0x80),
+    ("green", 0x00, 0x80, 0x00),
+    ("greenyellow", 0xad, 0xff, 0x2f),
+    ("grey", 0x80, 0x80, 0x80),
+    ("honeydew", 0xf0, 0xff, 0xf0),
+    ("hotpink", 0xff, 0x69, 0xb4),
+    ("indianred", 0xcd, 0x5c, 0x5c),
+    ("indigo", 0x4b, 0x00, 0x82),
+    ("ivory", 0xff, 0xff, 0xf0),
+    ("khaki", 0xf0, 0xe6, 0x8c),
+    ("lavender", 0xe6, 0xe6, 0xfa),
+    ("lavenderblush", 0xff, 0xf0, 0xf5),
+    ("lawngreen", 0x7c, 0xfc, 0x00),
+    ("lemonchiffon", 0xff, 0xfa, 0xcd),
+    ("lightblue", 0xad, 0xd8, 0xe6),
+    ("lightcoral", 0xf0, 0x80, 0x80),
+    ("lightcyan", 0xe0, 0xff, 0xff),
+    ("lightgoldenrodyellow", 0xfa, 0xfa, 0xd2),
+    ("lightgray", 0xd3, 0xd3, 0xd3),
+    ("lightgreen", 0x90, 0xee, 0x90),
+    ("lightgrey", 0xd3, 0xd3, 0xd3),
+    ("lightpink", 0xff, 0xb6, 0xc1),
+    ("lightsalmon", 0xff, 0xa0, 0x7a),
+    ("lightseagreen", 0x20, 0xb2, 0xaa),
+    ("lightskyblue", 0x87, 0xce, 0xfa),
+    ("lightslategray", 0x77, 0x88, 0x99),
+    ("lightslategrey", 0x77, 0x88, 0x99),
+    ("lightsteelblue", 0xb0, 0xc4, 0xde),
+    ("lightyellow", 0xff, 0xff, 0xe0),
+    ("lime", 0x00, 0xff, 0x00),
+    ("limegreen", 0x32, 0xcd, 0x32),
+    ("linen", 0xfa, 0xf0, 0xe6),
+    ("magenta", 0xff, 0x00, 0xff),
+    ("maroon", 0x80, 0x00, 0x00),
+    ("mediumaquamarine", 0x66, 0xcd, 0xaa),
+    ("mediumblue", 0x00, 0x00, 0xcd),
+    ("mediumorchid", 0xba, 0x55, 0xd3),
+    ("mediumpurple", 0x93, 0x70, 0xdb),
+    ("mediumseagreen", 0x3c, 0xb3, 0x71),
+    ("mediumslateblue", 0x7b, 0x68, 0xee),
+    ("mediumspringgreen", 0x00, 0xfa, 0x9a),
+    ("mediumturquoise", 0x48, 0xd1, 0xcc),
+    ("mediumvioletred", 0xc7, 0x15, 0x85),
+    ("midnightblue", 0x19, 0x19, 0x70),
+    ("mintcream", 0xf5, 0xff, 0xfa),
+    ("mistyrose", 0xff, 0xe4, 0xe1),
+    ("moccasin", 0xff, 0xe4, 0xb5),
+    ("navajowhite", 0xff, 0xde, 0xad),
+    ("navy", 0x00, 0x00, 0x80),
+    ("oldlace", 0xfd, 0xf5, 0xe6),
+    ("olive", 0x80, 0x80, 0x00),
+    ("olivedrab", 0x6b, 0x8e, 0x23),
+    ("orange", 0xff, 0xa5, 0x00),
+    ("orangered", 0xff, 0x45, 0x00),
+    ("orchid", 0xda, 0x70, 0xd6),
+    ("palegoldenrod", 0xee, 0xe8, 0xaa),
+    ("palegreen", 0x98, 0xfb, 0x98),
+    ("paleturquoise", 0xaf, 0xee, 0xee),
+    ("palevioletred", 0xdb, 0x70, 0x93),
+    ("papayawhip", 0xff, 0xef, 0xd5),
+    ("peachpuff", 0xff, 0xda, 0xb9),
+    ("peru", 0xcd, 0x85, 0x3f),
+    ("pink", 0xff, 0xc0, 0xcb),
+    ("plum", 0xdd, 0xa0, 0xdd),
+    ("powderblue", 0xb0, 0xe0, 0xe6),
+    ("purple", 0x80, 0x00, 0x80),
+    ("rebeccapurple", 0x66, 0x33, 0x99),
+    ("red", 0xff, 0x00, 0x00),
+    ("rosybrown", 0xbc, 0x8f, 0x8f),
+    ("royalblue", 0x41, 0x69, 0xe1),
+    ("saddlebrown", 0x8b, 0x45, 0x13),
+    ("salmon", 0xfa, 0x80, 0x72),
+    ("sandybrown", 0xf4, 0xa4, 0x60),
+    ("seagreen", 0x2e, 0x8b, 0x57),
+    ("seashell", 0xff, 0xf5, 0xee),
+    ("sienna", 0xa0, 0x52, 0x2d),
+    ("silver", 0xc0, 0xc0, 0xc0),
+    ("skyblue", 0x87, 0xce, 0xeb),
+    ("slateblue", 0x6a, 0x5a, 0xcd),
+    ("slategray", 0x70, 0x80, 0x90),
+    ("slategrey", 0x70, 0x80, 0x90),
+    ("snow", 0xff, 0xfa, 0xfa),
+    ("springgreen", 0x00, 0xff, 0x7f),
+    ("steelblue", 0x46, 0x82, 0xb4),
+    ("tan", 0xd2, 0xb4, 0x8c),
+    ("teal", 0x00, 0x80, 0x80),
+    ("thistle", 0xd8, 0xbf, 0xd8),
+    ("tomato", 0xff, 0x63, 0x47),
+    ("turquoise", 0x40, 0xe0, 0xd0),
+    ("violet", 0xee, 0x82, 0xee),
+    ("wheat", 0xf5, 0xde, 0xb3),
+    ("white", 0xff, 0xff, 0xff),
+    ("whitesmoke", 0xf5, 0xf5, 0xf5),
+    ("yellow", 0xff, 0xff, 0x00),
+    ("yellowgreen", 0x9a, 0xcd, 0x32),
+];
+
+/// A named spot color, such as a Pantone ink.
+///
+/// A spot color is painted in a /Separation color space, so a printer
+/// can use a separate plate with the actual named ink.
+/// Devices without the named ink (such as screens) use the alternate
+/// color instead.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::Pdf;
+/// # use pdf_canvas::graphicsstate::{Color, SpotColor};
+/// let blue = SpotColor::new("PANTONE 300 C", Color::cmyk(255, 110, 0, 0));
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// canvas.set_fill_spot(&blue, 1.0)?;
+/// canvas.rectangle(10.0, 10.0, 50.0, 50.0)?;
+/// canvas.fill()?;
+/// // A 40% tint of the same ink.
+/// canvas.set_fill_spot(&blue, 0.4)?;
+/// canvas.rectangle(70.0, 10.0, 50.0, 50.0)?;
+/// canvas.fill()
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SpotColor {
+    name: String,
+    alternate: Color,
+}
+
+impl SpotColor {
+    /// Create a spot color with the given ink name.
+    ///
+    /// The `alternate` is the color of the full-strength ink, for
+    /// devices that cannot use the named ink.
+    pub fn new(name: &str, alternate: Color) -> Self {
+        SpotColor {
+            name: name.to_string(),
+            alternate,
+        }
+    }
+
+    /// Get the name of the ink.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the alternate color.
+    pub fn get_alternate(&self) -> Color {
+        self.alternate
+    }
+}
+
+/// A device independent (CIE-based) color space, as described in
+/// section 8.6.5 of the PDF specification.
+///
+/// Colors in these color spaces are defined relative to a white point,
+/// rather than to the properties of some output device, so they can
+/// be reproduced accurately by color managed viewers and printers.
+#[derive(Clone, Copy, Debug)]
+pub enum CieColorSpace {
+    #[doc(hidden)]
+    Lab {
+        white_point: [f32; 3],
+        range: [f32; 4],
+    },
+    #[doc(hidden)]
+    CalRGB {
+        white_point: [f32; 3],
+        gamma: [f32; 3],
+        matrix: [f32; 9],
+    },
+}
+
+/// The CIE XYZ coordinates of the D50 standard illuminant.
+const D50: [f32; 3] = [0.9642, 1.0, 0.8249];
+/// The CIE XYZ coordinates of the D65 standard illuminant.
+const D65: [f32; 3] = [0.9505, 1.0, 1.089];
+
+impl CieColorSpace {
+    /// A L*a*b* color space relative to the given white point (as CIE
+    /// XYZ coordinates), with a* and b* limited to -128 to 127.
+    pub fn lab(white_point: [f32; 3]) -> Self {
+        CieColorSpace::Lab {
+            white_point,
+            range: [-128., 127., -128., 127.],
+        }
+    }
+
+    /// A L*a*b* color space with the D50 white point, as used by ICC
+    /// profiles.
+    pub fn lab_d50() -> Self {
+        Self::lab(D50)
+    }
+
+    /// A L*a*b* color space with the D65 white point.
+    pub fn lab_d65() -> Self {
+        Self::lab(D65)
+    }
+
+    /// A calibrated RGB color space with the given white point, gamma
+    /// for each component and matrix (column by column) from linear
+    /// RGB to CIE XYZ.
+    pub fn cal_rgb(
+        white_point: [f32; 3],
+        gamma: [f32; 3],
+        matrix: [f32; 9],
+    ) -> Self {
+        CieColorSpace::CalRGB {
+            white_point,
+            gamma,
+            matrix,
+        }
+    }
+
+    /// A calibrated RGB color space approximating sRGB, with the D65
+    /// white point, sRGB primaries and a gamma of 2.2.
+    pub fn srgb() -> Self {
+        Self::cal_rgb(
+            D65,
+            [2.2, 2.2, 2.2],
+            [
+                0.4124, 0.2126, 0.0193, 0.3576, 0.7152, 0.1192, 0.1805,
+                0.0722, 0.9505,
+            ],
+        )
+    }
+}
+
+/// A color in a device independent color space.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::Pdf;
+/// # use pdf_canvas::graphicsstate::CieColor;
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// // A mid-tone teal, specified perceptually.
+/// canvas.set_fill_cie(CieColor::lab(60.0, -35.0, -10.0))?;
+/// canvas.rectangle(10.0, 10.0, 50.0, 50.0)?;
+/// canvas.fill()?;
+/// canvas.set_fill_cie(CieColor::cal_rgb(0.9, 0.5, 0.1))?;
+/// canvas.rectangle(70.0, 10.0, 50.0, 50.0)?;
+/// canvas.fill()
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CieColor {
+    space: CieColorSpace,
+    components: [f32; 3],
+}
+
+impl CieColor {
+    /// Create a color with the given components in `space`.
+    ///
+    /// For a L*a*b* space, the components are L* (0 to 100), a* and
+    /// b*.
+    /// For a calibrated RGB space, the components are red, green and
+    /// blue, from 0.0 to 1.0.
+    pub fn new(space: CieColorSpace, components: [f32; 3]) -> Self {
+        CieColor { space, components }
+    }
+
+    /// Create a L*a*b* color, relative to the D50 white point.
+    pub fn lab(l: f32, a: f32, b: f32) -> Self {
+        Self::new(CieColorSpace::lab_d50(), [l, a, b])
+    }
+
+    /// Create a color in the sRGB-like calibrated RGB space, with
+    /// components from 0.0 to 1.0.
+    pub fn cal_rgb(red: f32, green: f32, blue: f32) -> Self {
+        Self::new(CieColorSpace::srgb(), [red, green, blue])
+    }
+
+    /// Get the color space of this color.
+    pub fn get_space(&self) -> CieColorSpace {
+        self.space
+    }
+
+    /// Get the components of this color.
+    pub fn get_components(&self) -> [f32; 3] {
+        self.components
+    }
 }
 
 /// A transformation matrix for the pdf graphics state.
@@ -90,6 +715,7 @@ impl Color {
 /// # }).unwrap();
 /// # document.finish().unwrap();
 /// ```
+#[derive(Clone, Copy, Debug)]
 pub struct Matrix {
     v: [f32; 6],
 }
@@ -131,6 +757,70 @@ impl Matrix {
     }
 }
 
+impl Matrix {
+    /// Construct the identity matrix, which does not transform
+    /// anything.
+    pub fn identity() -> Self {
+        Matrix {
+            v: [1., 0., 0., 1., 0., 0.],
+        }
+    }
+    /// Construct a matrix from its six coefficients `[a b c d e f]`, in
+    /// the same order as used by the PDF `cm` operator.
+    pub fn from_array(v: [f32; 6]) -> Self {
+        Matrix { v }
+    }
+    /// Get the six coefficients of the matrix, in the PDF order.
+    pub fn to_array(&self) -> [f32; 6] {
+        self.v
+    }
+    /// Get the inverse of this matrix, or None if the matrix is
+    /// singular (e.g. a scaling by zero).
+    ///
+    /// ```
+    /// # use pdf_canvas::graphicsstate::Matrix;
+    /// let m = Matrix::translate(10.0, 20.0) * Matrix::uniform_scale(2.0);
+    /// let p = m.transform_point((3.0, 4.0));
+    /// let (x, y) = m.invert().unwrap().transform_point(p);
+    /// assert!((x - 3.0).abs() < 1e-5 && (y - 4.0).abs() < 1e-5);
+    /// ```
+    pub fn invert(&self) -> Option<Self> {
+        let [a, b, c, d, e, f] = self.v;
+        let det = a * d - b * c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Matrix {
+            v: [
+                d / det,
+                -b / det,
+                -c / det,
+                a / det,
+                (c * f - d * e) / det,
+                (b * e - a * f) / det,
+            ],
+        })
+    }
+    /// Transform the point (x, y) by this matrix.
+    pub fn transform_point(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let v = self.v;
+        (v[0] * x + v[2] * y + v[4], v[1] * x + v[3] * y + v[5])
+    }
+    /// Transform the vector (dx, dy) by this matrix.
+    /// Unlike [transform_point](#method.transform_point), the
+    /// translation part of the matrix is ignored.
+    pub fn transform_vector(&self, (dx, dy): (f32, f32)) -> (f32, f32) {
+        let v = self.v;
+        (v[0] * dx + v[2] * dy, v[1] * dx + v[3] * dy)
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Matrix::identity()
+    }
+}
+
 impl Display for Matrix {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let v = self.v;
@@ -173,6 +863,26 @@ fn test_matrix_mul_d() {
     assert_unit(Matrix::rotate(PI) * Matrix::uniform_scale(-1.));
 }
 
+#[test]
+fn test_matrix_invert() {
+    let m = Matrix::rotate_deg(30.) * Matrix::translate(5., -3.);
+    assert_unit(m * m.invert().unwrap());
+    assert!(Matrix::scale(0., 1.).invert().is_none());
+}
+#[test]
+fn test_matrix_transform() {
+    let m = Matrix::translate(10., 20.) * Matrix::scale(2., 3.);
+    assert_eq!((22., 63.), m.transform_point((1., 1.)));
+    assert_eq!((2., 3.), m.transform_vector((1., 1.)));
+}
+
+#[test]
+fn test_named_colors_sorted() {
+    for pair in NAMED_COLORS.windows(2) {
+        assert!(pair[0].0 < pair[1].0, "{} < {}", pair[0].0, pair[1].0);
+    }
+}
+
 #[allow(dead_code)]
 fn assert_unit(m: Matrix) {
     assert_eq!(None, diff(&[1., 0., 0., 1., 0., 0.], &m.v));
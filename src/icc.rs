@@ -0,0 +1,144 @@
+//! A minimal ICC (version 2) profile for the sRGB color space, as
+//! needed for the output intent of PDF/A documents.
+
+/// The D50 white point of the profile connection space.
+const D50: [f32; 3] = [0.9642, 1.0, 0.8249];
+
+/// The sRGB primaries, adapted to D50.
+const PRIMARIES: [(&[u8; 4], [f32; 3]); 3] = [
+    (b"rXYZ", [0.4361, 0.2225, 0.0139]),
+    (b"gXYZ", [0.3851, 0.7169, 0.0971]),
+    (b"bXYZ", [0.1431, 0.0606, 0.7141]),
+];
+
+/// The description of the profile.
+pub const SRGB_NAME: &str = "sRGB IEC61966-2.1";
+
+/// Create the sRGB profile.
+pub fn srgb_profile() -> Vec<u8> {
+    let curve = srgb_curve();
+    let mut tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", text_description(SRGB_NAME)),
+        (b"cprt", text("No copyright, use freely")),
+        (b"wtpt", xyz(D50)),
+    ];
+    for &(signature, primary) in &PRIMARIES {
+        tags.push((signature, xyz(primary)));
+    }
+    tags.push((b"rTRC", curve.clone()));
+    tags.push((b"gTRC", curve.clone()));
+    tags.push((b"bTRC", curve));
+
+    let mut table = Vec::new();
+    let mut data = Vec::new();
+    let data_start = 128 + 4 + 12 * tags.len();
+    push_u32(&mut table, tags.len() as u32);
+    for (signature, tag) in tags {
+        table.extend_from_slice(signature);
+        push_u32(&mut table, (data_start + data.len()) as u32);
+        push_u32(&mut table, tag.len() as u32);
+        data.extend_from_slice(&tag);
+        // Each tag starts on a four byte boundary.
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+    }
+
+    let size = data_start + data.len();
+    let mut profile = Vec::with_capacity(size);
+    push_u32(&mut profile, size as u32);
+    push_u32(&mut profile, 0); // preferred cmm
+    push_u32(&mut profile, 0x0210_0000); // version 2.1
+    profile.extend_from_slice(b"mntrRGB XYZ ");
+    // The creation date, 2000-01-01 00:00:00.
+    for &field in &[2000, 1, 1, 0, 0, 0] {
+        push_u16(&mut profile, field);
+    }
+    profile.extend_from_slice(b"acsp");
+    // Platform, flags, manufacturer, model, attributes and rendering
+    // intent are all unspecified.
+    profile.resize(68, 0);
+    for &c in &D50 {
+        push_s15_fixed16(&mut profile, c);
+    }
+    // Creator, id and reserved bytes.
+    profile.resize(128, 0);
+    profile.extend_from_slice(&table);
+    profile.extend_from_slice(&data);
+    profile
+}
+
+/// The sRGB transfer function, as a sampled curve.
+fn srgb_curve() -> Vec<u8> {
+    let mut tag = tag_header(b"curv");
+    let samples = 1024;
+    push_u32(&mut tag, samples);
+    for i in 0..samples {
+        let v = i as f32 / (samples - 1) as f32;
+        let linear = if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        };
+        push_u16(&mut tag, (linear * 65535.0).round() as u16);
+    }
+    tag
+}
+
+fn text_description(description: &str) -> Vec<u8> {
+    let mut tag = tag_header(b"desc");
+    push_u32(&mut tag, description.len() as u32 + 1);
+    tag.extend_from_slice(description.as_bytes());
+    tag.push(0);
+    // No unicode or scriptcode descriptions: language and count for
+    // unicode, code and count for scriptcode, and the unused
+    // scriptcode buffer.
+    tag.extend_from_slice(&[0; 8 + 3 + 67]);
+    tag
+}
+
+fn text(text: &str) -> Vec<u8> {
+    let mut tag = tag_header(b"text");
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+    tag
+}
+
+fn xyz(xyz: [f32; 3]) -> Vec<u8> {
+    let mut tag = tag_header(b"XYZ ");
+    for &c in &xyz {
+        push_s15_fixed16(&mut tag, c);
+    }
+    tag
+}
+
+fn tag_header(signature: &[u8; 4]) -> Vec<u8> {
+    let mut tag = signature.to_vec();
+    push_u32(&mut tag, 0);
+    tag
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_s15_fixed16(data: &mut Vec<u8>, value: f32) {
+    let fixed = (value * 65536.0).round() as i32;
+    data.extend_from_slice(&fixed.to_be_bytes());
+}
+
+#[test]
+fn test_srgb_profile() {
+    let profile = srgb_profile();
+    let size =
+        u32::from_be_bytes([profile[0], profile[1], profile[2], profile[3]]);
+    assert_eq!(profile.len(), size as usize);
+    assert_eq!(b"acsp", &profile[36..40]);
+    assert_eq!(0, profile.len() % 4);
+    // The tag count.
+    assert_eq!(&[0, 0, 0, 9], &profile[128..132]);
+}
@@ -0,0 +1,1151 @@
+use ccitt;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use graphicsstate::Matrix;
+use jpeg;
+use jpx;
+#[cfg(feature = "png")]
+use png;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use Pdf;
+
+/// The color space of the pixels of an [Image](struct.Image.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageColorSpace {
+    /// One gray value per pixel.
+    DeviceGray,
+    /// Red, green and blue values for each pixel.
+    DeviceRGB,
+    /// Cyan, magenta, yellow and black values for each pixel.
+    DeviceCMYK,
+}
+
+impl ImageColorSpace {
+    /// The number of color components per pixel.
+    pub fn components(&self) -> usize {
+        match *self {
+            ImageColorSpace::DeviceGray => 1,
+            ImageColorSpace::DeviceRGB => 3,
+            ImageColorSpace::DeviceCMYK => 4,
+        }
+    }
+}
+
+/// Source of unique ids for images, so each image can be written only
+/// once, even if it is used on many pages.
+static NEXT_IMAGE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A raster image, that can be drawn on a canvas, as described in
+/// section 8.9 of the PDF specification.
+///
+/// The image data is stored only once in the pdf file, regardless of
+/// how many times (and on how many pages) it is drawn.
+/// This also holds for separately created images with identical
+/// content, such as the same file read for each page.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Image, ImageColorSpace, Pdf, Rect};
+/// // A 2 × 2 pixel image, red and green on top of blue and white.
+/// let pixels = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+/// let image = Image::raw(2, 2, ImageColorSpace::DeviceRGB, &pixels).unwrap();
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// canvas.draw_image(&image, Rect::new(10.0, 10.0, 160.0, 160.0))
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Image {
+    id: usize,
+    data: Arc<ImageData>,
+    /// The EXIF orientation (1 to 8) to display the image in.
+    orientation: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ImageData {
+    width: u32,
+    height: u32,
+    /// The color space, or None for a stencil mask.
+    color_space: Option<ImageColorSpace>,
+    /// For an indexed image, the colors of the palette, in
+    /// `color_space`.
+    palette: Option<Vec<u8>>,
+    /// True if the color space is specified by the image data itself,
+    /// so it is not written to the pdf.
+    embedded_color_space: bool,
+    bits_per_component: u8,
+    /// A /Decode array, mapping the samples to color values.
+    decode: Option<&'static str>,
+    /// Ask the viewer to smooth the image when it is scaled up.
+    interpolate: bool,
+    filter: &'static str,
+    decode_parms: Option<String>,
+    content: Vec<u8>,
+    smask: Option<Image>,
+    mask: Option<Mask>,
+}
+
+/// An explicit mask of an image.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Mask {
+    /// A stencil mask image.
+    Stencil(Image),
+    /// Ranges of colors to be masked out, as min and max for each color
+    /// component.
+    ColorKey(Vec<u8>),
+}
+
+impl Image {
+    /// Create an image from raw pixel data.
+    ///
+    /// The data has one byte per color component, with the pixels in
+    /// rows from top to bottom, and each row from left to right.
+    /// The data is compressed when it is written to the pdf file.
+    ///
+    /// Return an error if the data size doesn't match the given
+    /// dimensions and color space.
+    pub fn raw(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace,
+        data: &[u8],
+    ) -> io::Result<Self> {
+        Image::from_samples(width, height, color_space, 8, data)
+    }
+
+    /// Create an image from raw pixel data with 16 bits per color
+    /// component, for images where 8 bits would lose too much
+    /// precision, such as scientific or medical imagery.
+    ///
+    /// The layout is the same as for [raw](#method.raw), but with one
+    /// `u16` per color component.
+    ///
+    /// Return an error if the data size doesn't match the given
+    /// dimensions and color space.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, ImageColorSpace, Pdf, Rect};
+    /// // A smooth ramp from black to white.
+    /// let ramp: Vec<u16> = (0..1024).map(|x| (x * 64) as u16).collect();
+    /// let image =
+    ///     Image::raw16(1024, 1, ImageColorSpace::DeviceGray, &ramp).unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.draw_image(&image, Rect::new(10.0, 10.0, 160.0, 40.0))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn raw16(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace,
+        data: &[u16],
+    ) -> io::Result<Self> {
+        Image::from_samples(width, height, color_space, 16, &to_bytes(data))
+    }
+
+    /// Create an image from samples of 8 or 16 (big-endian) bits.
+    fn from_samples(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace,
+        bits_per_component: u8,
+        data: &[u8],
+    ) -> io::Result<Self> {
+        let expected = width as usize
+            * height as usize
+            * color_space.components()
+            * usize::from(bits_per_component / 8);
+        if data.len() != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes of image data, got {}",
+                    expected,
+                    data.len(),
+                ),
+            ));
+        }
+        Ok(Image::new(ImageData {
+            width,
+            height,
+            color_space: Some(color_space),
+            palette: None,
+            embedded_color_space: false,
+            bits_per_component,
+            decode: None,
+            interpolate: false,
+            filter: "/FlateDecode",
+            decode_parms: None,
+            content: deflate(data)?,
+            smask: None,
+            mask: None,
+        }))
+    }
+
+    /// Create an image from the contents of a JPEG file.
+    ///
+    /// Only the header of the file is parsed, for the image size and
+    /// color space; the compressed data is embedded as is, without
+    /// loss of quality.
+    /// The image is displayed in the orientation given by its EXIF
+    /// data, as photos from phones and cameras often are stored
+    /// sideways; use
+    /// [without_orientation](#method.without_orientation) to opt out.
+    /// CMYK and YCCK files written by Adobe software, which store
+    /// inverted values, are detected and get a matching /Decode
+    /// array.
+    ///
+    /// Return an error if the data is not a JPEG file that can be used
+    /// in a pdf.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pdf_canvas::{Image, Pdf, Rect};
+    /// let photo = Image::jpeg(std::fs::read("photo.jpg").unwrap()).unwrap();
+    /// let (w, h) = (photo.width() as f32, photo.height() as f32);
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.draw_image(&photo, Rect::new(10.0, 10.0, 160.0, 160.0 * h / w))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn jpeg(data: Vec<u8>) -> io::Result<Self> {
+        let info = jpeg::parse_header(&data)?;
+        let color_space = match info.components {
+            1 => ImageColorSpace::DeviceGray,
+            3 => ImageColorSpace::DeviceRGB,
+            4 => ImageColorSpace::DeviceCMYK,
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsupported JPEG with {} components", n),
+                ))
+            }
+        };
+        // Adobe software writes CMYK (and YCCK) JPEG files with
+        // inverted values, which would otherwise come out as a
+        // negative.
+        let decode = match (color_space, info.adobe_transform) {
+            (ImageColorSpace::DeviceCMYK, Some(_)) => {
+                Some("[ 1 0 1 0 1 0 1 0 ]")
+            }
+            _ => None,
+        };
+        let mut image = Image::new(ImageData {
+            width: info.width,
+            height: info.height,
+            color_space: Some(color_space),
+            palette: None,
+            embedded_color_space: false,
+            bits_per_component: info.bits_per_component,
+            decode,
+            interpolate: false,
+            filter: "/DCTDecode",
+            decode_parms: None,
+            content: data,
+            smask: None,
+            mask: None,
+        });
+        image.orientation = info.orientation;
+        Ok(image)
+    }
+
+    /// Create an image from the contents of a JPEG 2000 file, either a
+    /// JP2 file or a bare codestream (J2K).
+    ///
+    /// As for [jpeg](#method.jpeg), only the header is parsed and the
+    /// compressed data is embedded as is.
+    /// A JP2 file specifies its own color space, a codestream is
+    /// taken to be gray, RGB or CMYK, by its number of components.
+    ///
+    /// Return an error if the data is not a JPEG 2000 file that can be
+    /// used in a pdf.
+    pub fn jpx(data: Vec<u8>) -> io::Result<Self> {
+        let info = jpx::parse_header(&data)?;
+        let color_space = match info.components {
+            1 | 2 => ImageColorSpace::DeviceGray,
+            3 => ImageColorSpace::DeviceRGB,
+            4 => ImageColorSpace::DeviceCMYK,
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsupported JPEG 2000 with {} components", n),
+                ))
+            }
+        };
+        Ok(Image::new(ImageData {
+            width: info.width,
+            height: info.height,
+            color_space: Some(color_space),
+            palette: None,
+            embedded_color_space: info.is_jp2,
+            bits_per_component: info.bits_per_component,
+            decode: None,
+            interpolate: false,
+            filter: "/JPXDecode",
+            decode_parms: None,
+            content: data,
+            smask: None,
+            mask: None,
+        }))
+    }
+
+    /// Create an image from the contents of a PNG file.
+    ///
+    /// Any transparency in the image (an alpha channel, or a
+    /// transparent color) is converted to a soft mask, so the image is
+    /// composited correctly with whatever is under it on the page.
+    /// Palette images without transparency are kept as indexed images,
+    /// other palette images are expanded to full color.
+    /// Images with 16 bits per component keep their full precision.
+    ///
+    /// Only available with the `png` feature.
+    /// Return an error if the data is not a valid PNG file.
+    #[cfg(feature = "png")]
+    pub fn png(data: &[u8]) -> io::Result<Self> {
+        if let Some(image) = Image::png_indexed(data)? {
+            return Ok(image);
+        }
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let mut reader = decoder.read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels)?;
+        pixels.truncate(frame.buffer_size());
+        let (color_space, has_alpha) = match frame.color_type {
+            png::ColorType::Grayscale => (ImageColorSpace::DeviceGray, false),
+            png::ColorType::GrayscaleAlpha => {
+                (ImageColorSpace::DeviceGray, true)
+            }
+            png::ColorType::Rgba => (ImageColorSpace::DeviceRGB, true),
+            _ => (ImageColorSpace::DeviceRGB, false),
+        };
+        let bits = match frame.bit_depth {
+            png::BitDepth::Sixteen => 16,
+            _ => 8,
+        };
+        if has_alpha {
+            Image::from_samples_with_alpha(
+                frame.width,
+                frame.height,
+                color_space,
+                bits,
+                &pixels,
+            )
+        } else {
+            Image::from_samples(
+                frame.width,
+                frame.height,
+                color_space,
+                bits,
+                &pixels,
+            )
+        }
+    }
+
+    /// Read a PNG palette image without transparency as an indexed
+    /// image, keeping its size small.
+    /// Return None for any other kind of PNG image.
+    #[cfg(feature = "png")]
+    fn png_indexed(data: &[u8]) -> io::Result<Option<Self>> {
+        let mut reader = png::Decoder::new(data).read_info()?;
+        let (palette, bit_depth) = {
+            let info = reader.info();
+            match (info.color_type, &info.palette, &info.trns) {
+                (png::ColorType::Indexed, Some(palette), None) => {
+                    (palette.to_vec(), info.bit_depth as u8)
+                }
+                _ => return Ok(None),
+            }
+        };
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels)?;
+        pixels.truncate(frame.buffer_size());
+        Ok(Some(Image::new(ImageData {
+            width: frame.width,
+            height: frame.height,
+            color_space: Some(ImageColorSpace::DeviceRGB),
+            palette: Some(palette),
+            embedded_color_space: false,
+            bits_per_component: bit_depth,
+            decode: None,
+            interpolate: false,
+            filter: "/FlateDecode",
+            decode_parms: None,
+            content: deflate(&pixels)?,
+            smask: None,
+            mask: None,
+        })))
+    }
+
+    /// Create an indexed (palette) image, where each pixel is given as
+    /// one byte, an index in a palette of at most 256 colors.
+    ///
+    /// The palette is given as the color components of each color in
+    /// `base`, so e.g. three bytes per color for DeviceRGB.
+    /// This is much smaller than full color for images with few
+    /// colors, such as screenshots and charts.
+    ///
+    /// Return an error if the palette is empty or too large, or if the
+    /// data size doesn't match the image dimensions, or if any index
+    /// is outside of the palette.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, ImageColorSpace, Pdf, Rect};
+    /// let palette = [255, 255, 255, 200, 0, 0, 0, 0, 200];
+    /// let flag = [1, 1, 0, 0, 2, 2, 1, 1, 0, 0, 2, 2];
+    /// let image =
+    ///     Image::indexed(6, 2, ImageColorSpace::DeviceRGB, &palette, &flag)
+    ///         .unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.draw_image(&image, Rect::new(0.0, 180.0, 180.0, 60.0))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn indexed(
+        width: u32,
+        height: u32,
+        base: ImageColorSpace,
+        palette: &[u8],
+        data: &[u8],
+    ) -> io::Result<Self> {
+        let colors = palette.len() / base.components();
+        if colors == 0
+            || colors > 256
+            || palette.len() != colors * base.components()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A palette must have 1 to 256 complete colors",
+            ));
+        }
+        if data.len() != width as usize * height as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes of image data, got {}",
+                    width as usize * height as usize,
+                    data.len(),
+                ),
+            ));
+        }
+        if data.iter().any(|&i| usize::from(i) >= colors) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Image data refers to a color outside of the palette",
+            ));
+        }
+        Ok(Image::new(ImageData {
+            width,
+            height,
+            color_space: Some(base),
+            palette: Some(palette.to_vec()),
+            embedded_color_space: false,
+            bits_per_component: 8,
+            decode: None,
+            interpolate: false,
+            filter: "/FlateDecode",
+            decode_parms: None,
+            content: deflate(data)?,
+            smask: None,
+            mask: None,
+        }))
+    }
+
+    /// Create an image from raw pixel data, where each pixel has an
+    /// alpha component after the color components.
+    ///
+    /// The alpha is converted to a soft mask, unless all pixels are
+    /// opaque.
+    pub fn raw_with_alpha(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace,
+        data: &[u8],
+    ) -> io::Result<Self> {
+        Image::from_samples_with_alpha(width, height, color_space, 8, data)
+    }
+
+    /// Create an image from raw pixel data with 16 bits per component,
+    /// where each pixel has an alpha component after the color
+    /// components.
+    ///
+    /// The alpha is converted to a 16 bit soft mask, unless all pixels
+    /// are opaque.
+    pub fn raw16_with_alpha(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace,
+        data: &[u16],
+    ) -> io::Result<Self> {
+        Image::from_samples_with_alpha(
+            width,
+            height,
+            color_space,
+            16,
+            &to_bytes(data),
+        )
+    }
+
+    /// Create an image with a soft mask from samples of 8 or 16
+    /// (big-endian) bits, with alpha after the color components.
+    fn from_samples_with_alpha(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace,
+        bits_per_component: u8,
+        data: &[u8],
+    ) -> io::Result<Self> {
+        let size = usize::from(bits_per_component / 8);
+        let n = color_space.components() * size;
+        let expected = width as usize * height as usize * (n + size);
+        if data.len() != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes of image data, got {}",
+                    expected,
+                    data.len(),
+                ),
+            ));
+        }
+        let mut color = Vec::with_capacity(data.len() / (n + size) * n);
+        let mut alpha = Vec::with_capacity(data.len() / (n + size) * size);
+        for pixel in data.chunks(n + size) {
+            color.extend_from_slice(&pixel[..n]);
+            alpha.extend_from_slice(&pixel[n..]);
+        }
+        let mut image = Image::from_samples(
+            width,
+            height,
+            color_space,
+            bits_per_component,
+            &color,
+        )?;
+        if alpha.iter().any(|&a| a != 255) {
+            let mask = Image::from_samples(
+                width,
+                height,
+                ImageColorSpace::DeviceGray,
+                bits_per_component,
+                &alpha,
+            )?;
+            Arc::get_mut(&mut image.data)
+                .expect("A new image is not shared")
+                .smask = Some(mask);
+        }
+        Ok(image)
+    }
+
+    /// Read an image file.
+    ///
+    /// JPEG and JPEG 2000 files are supported directly, and PNG files
+    /// with the `png` feature enabled.
+    /// With the `image` feature enabled, any format supported by the
+    /// [image crate](https://docs.rs/image) can be read.
+    pub fn read<R: Read>(mut source: R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        source.read_to_end(&mut data)?;
+        if data.starts_with(&[0xFF, 0xD8]) {
+            return Image::jpeg(data);
+        }
+        if jpx::is_jpx(&data) {
+            return Image::jpx(data);
+        }
+        #[cfg(feature = "png")]
+        {
+            if data.starts_with(b"\x89PNG\r\n\x1A\n") {
+                return Image::png(&data);
+            }
+        }
+        Image::decode_other(&data)
+    }
+
+    #[cfg(feature = "image")]
+    fn decode_other(data: &[u8]) -> io::Result<Self> {
+        let image = image::load_from_memory(data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Image::from_dynamic(&image)
+    }
+
+    #[cfg(not(feature = "image"))]
+    fn decode_other(_data: &[u8]) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Unsupported image format",
+        ))
+    }
+
+    /// Create an image from an image of the
+    /// [image crate](https://docs.rs/image).
+    ///
+    /// Only available with the `image` feature.
+    /// Gray and color images, with or without alpha and with 8 or 16
+    /// bits per component, are kept as is, other images are converted
+    /// to 8 bit RGB with alpha.
+    #[cfg(feature = "image")]
+    pub fn from_dynamic(image: &image::DynamicImage) -> io::Result<Self> {
+        use image::DynamicImage::*;
+        let (w, h) = (image.width(), image.height());
+        match *image {
+            ImageLuma8(ref i) => {
+                Image::raw(w, h, ImageColorSpace::DeviceGray, i.as_raw())
+            }
+            ImageLumaA8(ref i) => Image::raw_with_alpha(
+                w,
+                h,
+                ImageColorSpace::DeviceGray,
+                i.as_raw(),
+            ),
+            ImageRgb8(ref i) => {
+                Image::raw(w, h, ImageColorSpace::DeviceRGB, i.as_raw())
+            }
+            ImageLuma16(ref i) => {
+                Image::raw16(w, h, ImageColorSpace::DeviceGray, i.as_raw())
+            }
+            ImageLumaA16(ref i) => Image::raw16_with_alpha(
+                w,
+                h,
+                ImageColorSpace::DeviceGray,
+                i.as_raw(),
+            ),
+            ImageRgb16(ref i) => {
+                Image::raw16(w, h, ImageColorSpace::DeviceRGB, i.as_raw())
+            }
+            ImageRgba16(ref i) => Image::raw16_with_alpha(
+                w,
+                h,
+                ImageColorSpace::DeviceRGB,
+                i.as_raw(),
+            ),
+            _ => Image::raw_with_alpha(
+                w,
+                h,
+                ImageColorSpace::DeviceRGB,
+                image.to_rgba8().as_raw(),
+            ),
+        }
+    }
+
+    /// Create a stencil mask, a 1-bit image that is painted with the
+    /// current fill color.
+    ///
+    /// The data has one bit per pixel, with each row starting on a new
+    /// byte, and the most significant bit of each byte first.
+    /// Pixels with a bit value of 1 are painted, pixels with 0 leave
+    /// whatever is under the image unchanged.
+    ///
+    /// A stencil can also be used as the mask of a color image, see
+    /// [with_mask](#method.with_mask).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, Pdf, Rect};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// // An 8 × 8 pixel checkerboard.
+    /// let board = [0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55];
+    /// let stencil = Image::stencil(8, 8, &board).unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_fill_color(Color::rgb(0, 128, 0))?;
+    /// canvas.draw_image(&stencil, Rect::new(10.0, 10.0, 160.0, 160.0))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn stencil(width: u32, height: u32, data: &[u8]) -> io::Result<Self> {
+        let expected = (width as usize + 7) / 8 * height as usize;
+        if data.len() != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes of stencil data, got {}",
+                    expected,
+                    data.len(),
+                ),
+            ));
+        }
+        // The PDF default is to paint zero bits; invert the data rather
+        // than requiring a /Decode array.
+        let inverted: Vec<u8> = data.iter().map(|b| !b).collect();
+        Ok(Image::new(ImageData {
+            width,
+            height,
+            color_space: None,
+            palette: None,
+            embedded_color_space: false,
+            bits_per_component: 1,
+            decode: None,
+            interpolate: false,
+            filter: "/FlateDecode",
+            decode_parms: None,
+            content: deflate(&inverted)?,
+            smask: None,
+            mask: None,
+        }))
+    }
+
+    /// Create a black and white image, compressed with CCITT Group 4
+    /// (fax) compression, which is usually much smaller than other
+    /// compression for scanned text pages.
+    ///
+    /// The data has one bit per pixel, with each row starting on a new
+    /// byte, and the most significant bit of each byte first.
+    /// Pixels with a bit value of 1 are black, 0 is white.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, Pdf, Rect};
+    /// // A 16 × 16 pixel black square with a white border.
+    /// let mut scan = vec![0u8; 2 * 16];
+    /// for row in 2..14 {
+    ///     scan[2 * row] = 0x3F;
+    ///     scan[2 * row + 1] = 0xFC;
+    /// }
+    /// let image = Image::bilevel(16, 16, &scan).unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.draw_image(&image, Rect::new(10.0, 10.0, 160.0, 160.0))
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn bilevel(width: u32, height: u32, data: &[u8]) -> io::Result<Self> {
+        let expected = (width as usize + 7) / 8 * height as usize;
+        if data.len() != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} bytes of bilevel data, got {}",
+                    expected,
+                    data.len(),
+                ),
+            ));
+        }
+        let encoded = ccitt::encode_g4(width, height, data);
+        Image::ccitt_g4(width, height, encoded)
+    }
+
+    /// Create a black and white image from data that is already
+    /// compressed with CCITT Group 4 compression, e.g. from a scanner
+    /// or a TIFF file.
+    ///
+    /// The data is embedded as is, and is not checked.
+    pub fn ccitt_g4(
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) -> io::Result<Self> {
+        Ok(Image::new(ImageData {
+            width,
+            height,
+            color_space: Some(ImageColorSpace::DeviceGray),
+            palette: None,
+            embedded_color_space: false,
+            bits_per_component: 1,
+            decode: None,
+            interpolate: false,
+            filter: "/CCITTFaxDecode",
+            decode_parms: Some(format!(
+                "<< /K -1 /Columns {} /Rows {} >>",
+                width, height,
+            )),
+            content: data,
+            smask: None,
+            mask: None,
+        }))
+    }
+
+    /// True if this image is a stencil mask.
+    pub fn is_stencil(&self) -> bool {
+        self.data.color_space.is_none()
+    }
+
+    /// Use a stencil mask for this image.
+    ///
+    /// Only the pixels where the stencil has a bit value of 1 are
+    /// painted.
+    /// The stencil may have another resolution than this image, it is
+    /// stretched to cover the same area.
+    ///
+    /// Return an error if `mask` is not a stencil, or if this image is
+    /// a stencil itself.
+    pub fn with_mask(self, mask: &Image) -> io::Result<Self> {
+        if !mask.is_stencil() || self.is_stencil() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only a color image can have a mask, and it must be a stencil",
+            ));
+        }
+        Ok(self.with_explicit_mask(Mask::Stencil(mask.clone())))
+    }
+
+    /// Mask out pixels with colors within the given ranges.
+    ///
+    /// The ranges are given as (min, max) for each color component.
+    /// A pixel is masked out (not painted) if all its components are
+    /// within their ranges.
+    /// This is the same as a transparent color in a GIF or PNG image.
+    ///
+    /// Return an error if the number of ranges doesn't match the color
+    /// space of the image.
+    pub fn with_color_key_mask(
+        self,
+        ranges: &[(u8, u8)],
+    ) -> io::Result<Self> {
+        let components = match self.data.palette {
+            Some(_) => Some(1),
+            None => self.data.color_space.map(|space| space.components()),
+        };
+        match components {
+            Some(n) if n == ranges.len() => {
+                let ranges =
+                    ranges.iter().flat_map(|&(a, b)| vec![a, b]).collect();
+                Ok(self.with_explicit_mask(Mask::ColorKey(ranges)))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Color key ranges must match the color space of the image",
+            )),
+        }
+    }
+
+    /// Ask the viewer to interpolate (smooth) the image when it is
+    /// shown at a higher resolution than its own, instead of showing
+    /// the pixels as blocks.
+    ///
+    /// Viewers may ignore this, and printers typically do.
+    pub fn with_interpolate(mut self, interpolate: bool) -> Self {
+        Arc::make_mut(&mut self.data).interpolate = interpolate;
+        self.id = NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed);
+        self
+    }
+
+    fn with_explicit_mask(mut self, mask: Mask) -> Self {
+        Arc::make_mut(&mut self.data).mask = Some(mask);
+        self.id = NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed);
+        self
+    }
+
+    fn new(data: ImageData) -> Self {
+        Image {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            data: Arc::new(data),
+            orientation: 1,
+        }
+    }
+
+    /// Draw the image as stored, ignoring the EXIF orientation of a
+    /// JPEG file.
+    pub fn without_orientation(mut self) -> Self {
+        self.orientation = 1;
+        self
+    }
+
+    /// Get the width of the image, in pixels, as displayed.
+    ///
+    /// For a JPEG file rotated by its EXIF orientation, this is the
+    /// stored height.
+    pub fn width(&self) -> u32 {
+        if self.orientation >= 5 {
+            self.data.height
+        } else {
+            self.data.width
+        }
+    }
+
+    /// Get the height of the image, in pixels, as displayed.
+    pub fn height(&self) -> u32 {
+        if self.orientation >= 5 {
+            self.data.width
+        } else {
+            self.data.height
+        }
+    }
+
+    /// The transformation of the unit square that displays the image
+    /// in its EXIF orientation.
+    pub(crate) fn orientation_matrix(&self) -> Matrix {
+        Matrix::from_array(match self.orientation {
+            2 => [-1., 0., 0., 1., 1., 0.],
+            3 => [-1., 0., 0., -1., 1., 1.],
+            4 => [1., 0., 0., -1., 0., 1.],
+            5 => [0., -1., -1., 0., 1., 1.],
+            6 => [0., -1., 1., 0., 0., 1.],
+            7 => [0., 1., 1., 0., 0., 0.],
+            8 => [0., 1., -1., 0., 1., 0.],
+            _ => [1., 0., 0., 1., 0., 0.],
+        })
+    }
+
+    /// A unique id for the image content.
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    /// True if the image has RGB colors.
+    pub(crate) fn is_rgb(&self) -> bool {
+        self.data.color_space == Some(ImageColorSpace::DeviceRGB)
+    }
+
+    /// True if the image has a soft mask, i.e. is partly transparent.
+    pub(crate) fn has_soft_mask(&self) -> bool {
+        self.data.smask.is_some()
+    }
+
+    /// Write the image to the pdf.  Return its object id.
+    pub(crate) fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        let data = &self.data;
+        let smask = match data.smask {
+            Some(ref smask) => Some(pdf.write_image(smask)?),
+            None => None,
+        };
+        let stencil_mask = match data.mask {
+            Some(Mask::Stencil(ref mask)) => Some(pdf.write_image(mask)?),
+            _ => None,
+        };
+        pdf.write_new_object(|object_id, pdf| {
+            write!(
+                pdf.output,
+                "<< /Type /XObject /Subtype /Image\n   \
+                 /Width {} /Height {}\n   ",
+                data.width, data.height,
+            )?;
+            match (data.color_space, &data.palette) {
+                (Some(space), Some(palette)) => {
+                    let hival = palette.len() / space.components() - 1;
+                    write!(
+                        pdf.output,
+                        "/ColorSpace [ /Indexed /{:?} {} <",
+                        space, hival,
+                    )?;
+                    for byte in palette {
+                        write!(pdf.output, "{:02X}", byte)?;
+                    }
+                    write!(pdf.output, "> ]")?;
+                }
+                (Some(_), None) if data.embedded_color_space => (),
+                (Some(space), None) => {
+                    write!(pdf.output, "/ColorSpace /{:?}", space)?
+                }
+                (None, _) => write!(pdf.output, "/ImageMask true")?,
+            }
+            write!(
+                pdf.output,
+                " /BitsPerComponent {}\n   ",
+                data.bits_per_component,
+            )?;
+            if data.interpolate {
+                write!(pdf.output, "/Interpolate true\n   ")?;
+            }
+            if let Some(decode) = data.decode {
+                write!(pdf.output, "/Decode {}\n   ", decode)?;
+            }
+            if let Some(smask) = smask {
+                write!(pdf.output, "/SMask {} 0 R\n   ", smask)?;
+            }
+            if let Some(mask) = stencil_mask {
+                write!(pdf.output, "/Mask {} 0 R\n   ", mask)?;
+            }
+            if let Some(Mask::ColorKey(ref ranges)) = data.mask {
+                write!(pdf.output, "/Mask [")?;
+                for r in ranges {
+                    write!(pdf.output, " {}", r)?;
+                }
+                write!(pdf.output, " ]\n   ")?;
+            }
+            write!(pdf.output, "/Filter {}", data.filter)?;
+            if let Some(ref parms) = data.decode_parms {
+                write!(pdf.output, " /DecodeParms {}", parms)?;
+            }
+            writeln!(
+                pdf.output,
+                " /Length {} >>\n\
+                 stream",
+                data.content.len(),
+            )?;
+            pdf.output.write_all(&data.content)?;
+            writeln!(pdf.output, "\nendstream")?;
+            Ok(object_id)
+        })
+    }
+}
+
+/// Images are equal if they have the same content, regardless of how
+/// they were created.
+impl PartialEq for Image {
+    fn eq(&self, other: &Image) -> bool {
+        Arc::ptr_eq(&self.data, &other.data) || self.data == other.data
+    }
+}
+
+impl Eq for Image {}
+
+impl Hash for Image {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state)
+    }
+}
+
+/// Anything that can be drawn as an image by
+/// [Canvas::draw_image](struct.Canvas.html#method.draw_image).
+///
+/// This is implemented for [Image](struct.Image.html) itself, for file
+/// paths (read by [Image::read](struct.Image.html#method.read)), and
+/// with the `image` feature enabled, for `image::DynamicImage`.
+pub trait ImageSource {
+    /// Get the image to draw.
+    fn to_image(&self) -> io::Result<Image>;
+}
+
+impl ImageSource for Image {
+    fn to_image(&self) -> io::Result<Image> {
+        Ok(self.clone())
+    }
+}
+
+impl ImageSource for Path {
+    fn to_image(&self) -> io::Result<Image> {
+        Image::read(File::open(self)?)
+    }
+}
+
+impl ImageSource for PathBuf {
+    fn to_image(&self) -> io::Result<Image> {
+        self.as_path().to_image()
+    }
+}
+
+impl ImageSource for str {
+    fn to_image(&self) -> io::Result<Image> {
+        Path::new(self).to_image()
+    }
+}
+
+#[cfg(feature = "image")]
+impl ImageSource for image::DynamicImage {
+    fn to_image(&self) -> io::Result<Image> {
+        Image::from_dynamic(self)
+    }
+}
+
+/// Convert 16 bit samples to big-endian bytes, as used in pdf images.
+fn to_bytes(samples: &[u16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_be_bytes()).collect()
+}
+
+/// Compress data for the /FlateDecode filter.
+fn deflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_alpha_to_smask() {
+    let mut file = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut file, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[255, 0, 0, 255, 0, 0, 255, 128])
+            .unwrap();
+    }
+    let image = Image::png(&file).unwrap();
+    assert_eq!((2, 1), (image.width(), image.height()));
+    assert_eq!(Some(ImageColorSpace::DeviceRGB), image.data.color_space);
+    let mask = image.data.smask.as_ref().unwrap();
+    assert_eq!(Some(ImageColorSpace::DeviceGray), mask.data.color_space);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn test_dynamic_image_alpha() {
+    let mut rgba = image::RgbaImage::new(3, 2);
+    rgba.put_pixel(1, 1, image::Rgba([10, 20, 30, 40]));
+    let image = image::DynamicImage::ImageRgba8(rgba).to_image().unwrap();
+    assert_eq!((3, 2), (image.width(), image.height()));
+    assert!(image.data.smask.is_some());
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_palette_stays_indexed() {
+    let mut file = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut file, 3, 1);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(vec![0, 0, 0, 255, 255, 255]);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[0, 1, 0]).unwrap();
+    }
+    let image = Image::png(&file).unwrap();
+    assert_eq!(Some(vec![0, 0, 0, 255, 255, 255]), image.data.palette);
+    assert!(
+        Image::indexed(1, 1, ImageColorSpace::DeviceGray, &[0], &[1])
+            .is_err()
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_16_bit_keeps_precision() {
+    let mut file = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut file, 2, 1);
+        encoder.set_color(png::ColorType::GrayscaleAlpha);
+        encoder.set_depth(png::BitDepth::Sixteen);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[0x12, 0x34, 0xFF, 0xFF, 0x56, 0x78, 0, 0])
+            .unwrap();
+    }
+    let image = Image::png(&file).unwrap();
+    assert_eq!(16, image.data.bits_per_component);
+    let smask = image.data.smask.as_ref().unwrap();
+    assert_eq!(16, smask.data.bits_per_component);
+    assert!(Image::raw16(2, 1, ImageColorSpace::DeviceGray, &[0]).is_err());
+}
+
+#[test]
+fn test_equal_images_written_once() {
+    let pixels = [0, 64, 128, 255];
+    let filename = "test_equal_images_written_once.pdf";
+    let mut document = Pdf::create(filename).unwrap();
+    for _ in 0..2 {
+        // A new, but identical, image for each page.
+        let image =
+            Image::raw(2, 2, ImageColorSpace::DeviceGray, &pixels).unwrap();
+        document
+            .render_page(100.0, 100.0, |c| {
+                c.draw_image(&image, ::Rect::new(0.0, 0.0, 100.0, 100.0))
+            })
+            .unwrap();
+    }
+    document.finish().unwrap();
+    let output = ::std::fs::read(filename).unwrap();
+    ::std::fs::remove_file(filename).unwrap();
+    let count = output
+        .windows(b"/Subtype /Image".len())
+        .filter(|w| *w == b"/Subtype /Image")
+        .count();
+    assert_eq!(1, count);
+}
@@ -0,0 +1,162 @@
+//! Just enough parsing of JPEG files to embed them in a pdf.
+
+use std::io;
+
+/// What is needed from a JPEG header to describe the image in a pdf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JpegInfo {
+    pub width: u32,
+    pub height: u32,
+    pub components: u8,
+    pub bits_per_component: u8,
+    /// The color transform of an Adobe APP14 marker, if there is one:
+    /// 0 for none (RGB or CMYK), 1 for YCbCr and 2 for YCCK.
+    pub adobe_transform: Option<u8>,
+    /// The EXIF orientation, 1 to 8, or 1 if there is none.
+    pub orientation: u8,
+}
+
+/// Parse the markers of a JPEG file until the start of frame.
+pub fn parse_header(data: &[u8]) -> io::Result<JpegInfo> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(invalid("Not a JPEG file"));
+    }
+    let mut pos = 2;
+    let mut adobe_transform = None;
+    let mut orientation = 1;
+    loop {
+        // Markers may be preceded by any number of fill bytes.
+        let start = pos;
+        while data.get(pos) == Some(&0xFF) {
+            pos += 1;
+        }
+        let marker = match data.get(pos) {
+            Some(&marker) if pos > start => marker,
+            _ => return Err(invalid("Bad JPEG marker")),
+        };
+        pos += 1;
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            // Standalone markers without a segment.
+            continue;
+        }
+        if data.len() < pos + 2 {
+            return Err(invalid("Truncated JPEG file"));
+        }
+        let length = u16_at(data, pos) as usize;
+        let segment = match data.get(pos + 2..pos + length) {
+            Some(segment) if length >= 2 => segment,
+            _ => return Err(invalid("Truncated JPEG file")),
+        };
+        match marker {
+            // Start of frame markers, except DHT, JPG and DAC.
+            0xC0..=0xCF
+                if marker != 0xC4 && marker != 0xC8 && marker != 0xCC =>
+            {
+                if segment.len() < 6 {
+                    return Err(invalid("Truncated JPEG frame header"));
+                }
+                return Ok(JpegInfo {
+                    bits_per_component: segment[0],
+                    height: u32::from(u16_at(segment, 1)),
+                    width: u32::from(u16_at(segment, 3)),
+                    components: segment[5],
+                    adobe_transform,
+                    orientation,
+                });
+            }
+            0xEE if segment.len() >= 12 && segment.starts_with(b"Adobe") => {
+                adobe_transform = Some(segment[11]);
+            }
+            0xE1 if segment.starts_with(b"Exif\0\0") => {
+                if let Some(value) = exif_orientation(&segment[6..]) {
+                    orientation = value;
+                }
+            }
+            0xDA | 0xD9 => {
+                return Err(invalid("No frame header in JPEG file"));
+            }
+            _ => (),
+        }
+        pos += length;
+    }
+}
+
+/// Find the orientation tag in the first IFD of EXIF (TIFF) data.
+/// Return None if there is no valid orientation.
+fn exif_orientation(tiff: &[u8]) -> Option<u8> {
+    let big_endian = match tiff.get(..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let u16_at = |pos: usize| {
+        let bytes = [*tiff.get(pos)?, *tiff.get(pos + 1)?];
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |pos: usize| {
+        let (a, b) = (u32::from(u16_at(pos)?), u32::from(u16_at(pos + 2)?));
+        Some(if big_endian { a << 16 | b } else { b << 16 | a })
+    };
+    let ifd = u32_at(4)? as usize;
+    for i in 0..usize::from(u16_at(ifd)?) {
+        let entry = ifd + 2 + 12 * i;
+        if u16_at(entry)? == 0x0112 {
+            return match u16_at(entry + 8)? {
+                value @ 1..=8 => Some(value as u8),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+fn u16_at(data: &[u8], pos: usize) -> u16 {
+    u16::from(data[pos]) << 8 | u16::from(data[pos + 1])
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[test]
+fn test_parse_header() {
+    let data = [
+        0xFF, 0xD8, // SOI
+        0xFF, 0xE0, 0, 4, 0, 0, // An empty APP0 segment
+        0xFF, 0xFF, // Fill
+        0xFF, 0xC0, 0, 11, 8, 0, 3, 0, 5, 1, 1, 0x11, 0, // SOF0
+    ];
+    assert_eq!(
+        JpegInfo {
+            width: 5,
+            height: 3,
+            components: 1,
+            bits_per_component: 8,
+            adobe_transform: None,
+            orientation: 1,
+        },
+        parse_header(&data).unwrap(),
+    );
+    let adobe = [
+        0xFF, 0xD8, // SOI
+        0xFF, 0xEE, 0, 14, b'A', b'd', b'o', b'b', b'e', 0, 100, 0, 0, 0, 0,
+        2, // APP14 with the YCCK transform
+        0xFF, 0xC0, 0, 11, 8, 0, 3, 0, 5, 4, 1, 0x11, 0, // SOF0
+    ];
+    assert_eq!(Some(2), parse_header(&adobe).unwrap().adobe_transform);
+    let exif = [
+        0xFF, 0xD8, // SOI
+        0xFF, 0xE1, 0, 34, b'E', b'x', b'i', b'f', 0, 0, // APP1
+        b'I', b'I', 42, 0, 8, 0, 0, 0, // TIFF header
+        1, 0, 0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0, // Orientation 6
+        0, 0, 0, 0, // No next IFD
+        0xFF, 0xC0, 0, 11, 8, 0, 3, 0, 5, 1, 1, 0x11, 0, // SOF0
+    ];
+    assert_eq!(6, parse_header(&exif).unwrap().orientation);
+    assert!(parse_header(&data[..12]).is_err());
+    assert!(parse_header(b"GIF89a").is_err());
+}
@@ -0,0 +1,120 @@
+//! Just enough parsing of JPEG 2000 files to embed them in a pdf.
+
+use std::io;
+
+/// What is needed from a JPEG 2000 header to describe the image in a
+/// pdf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JpxInfo {
+    pub width: u32,
+    pub height: u32,
+    pub components: u16,
+    pub bits_per_component: u8,
+    /// True for a JP2 file, which specifies its own color space, false
+    /// for a bare codestream.
+    pub is_jp2: bool,
+}
+
+const JP2_SIGNATURE: &[u8] = b"\0\0\0\x0CjP  \r\n\x87\n";
+const CODESTREAM_START: &[u8] = &[0xFF, 0x4F, 0xFF, 0x51];
+
+/// Check if data looks like a JP2 file or JPEG 2000 codestream.
+pub fn is_jpx(data: &[u8]) -> bool {
+    data.starts_with(JP2_SIGNATURE) || data.starts_with(CODESTREAM_START)
+}
+
+/// Parse the image header of a JP2 file or a JPEG 2000 codestream.
+pub fn parse_header(data: &[u8]) -> io::Result<JpxInfo> {
+    if data.starts_with(CODESTREAM_START) {
+        parse_siz(&data[4..])
+    } else if data.starts_with(JP2_SIGNATURE) {
+        let header = find_box(&data[JP2_SIGNATURE.len()..], b"jp2h")
+            .ok_or_else(|| invalid("No header box in JP2 file"))?;
+        let ihdr = find_box(header, b"ihdr")
+            .filter(|ihdr| ihdr.len() >= 11)
+            .ok_or_else(|| invalid("No image header in JP2 file"))?;
+        Ok(JpxInfo {
+            height: u32_at(ihdr, 0),
+            width: u32_at(ihdr, 4),
+            components: u16_at(ihdr, 8),
+            bits_per_component: (ihdr[10] & 0x7F) + 1,
+            is_jp2: true,
+        })
+    } else {
+        Err(invalid("Not a JPEG 2000 file"))
+    }
+}
+
+/// Parse the SIZ marker segment (after its marker) of a codestream.
+fn parse_siz(siz: &[u8]) -> io::Result<JpxInfo> {
+    if siz.len() < 39 {
+        return Err(invalid("Truncated JPEG 2000 codestream"));
+    }
+    Ok(JpxInfo {
+        width: u32_at(siz, 4).saturating_sub(u32_at(siz, 12)),
+        height: u32_at(siz, 8).saturating_sub(u32_at(siz, 16)),
+        components: u16_at(siz, 36),
+        bits_per_component: (siz[38] & 0x7F) + 1,
+        is_jp2: false,
+    })
+}
+
+/// Find the contents of the first box of type `kind` in `data`.
+fn find_box<'a>(mut data: &'a [u8], kind: &[u8]) -> Option<&'a [u8]> {
+    while data.len() >= 8 {
+        let (length, header) = match u32_at(data, 0) {
+            // The box extends to the end of the data.
+            0 => (data.len(), 8),
+            // A 64 bit length follows the type.
+            1 if data.len() >= 16 => {
+                let high = u64::from(u32_at(data, 8));
+                let low = u64::from(u32_at(data, 12));
+                ((high << 32 | low) as usize, 16)
+            }
+            length => (length as usize, 8),
+        };
+        if length < header || length > data.len() {
+            return None;
+        }
+        if &data[4..8] == kind {
+            return Some(&data[header..length]);
+        }
+        data = &data[length..];
+    }
+    None
+}
+
+fn u16_at(data: &[u8], pos: usize) -> u16 {
+    u16::from(data[pos]) << 8 | u16::from(data[pos + 1])
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from(u16_at(data, pos)) << 16 | u32::from(u16_at(data, pos + 2))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[test]
+fn test_parse_header() {
+    let mut jp2 = JP2_SIGNATURE.to_vec();
+    jp2.extend_from_slice(&[0, 0, 0, 20, b'f', b't', b'y', b'p']);
+    jp2.extend_from_slice(b"jp2 \0\0\0\0jp2 ");
+    jp2.extend_from_slice(&[0, 0, 0, 30, b'j', b'p', b'2', b'h']);
+    jp2.extend_from_slice(&[0, 0, 0, 22, b'i', b'h', b'd', b'r']);
+    jp2.extend_from_slice(&[0, 0, 0, 3, 0, 0, 0, 5, 0, 3, 7, 7, 0, 0]);
+    assert!(is_jpx(&jp2));
+    assert_eq!(
+        JpxInfo {
+            width: 5,
+            height: 3,
+            components: 3,
+            bits_per_component: 8,
+            is_jp2: true,
+        },
+        parse_header(&jp2).unwrap(),
+    );
+    assert!(parse_header(&jp2[..40]).is_err());
+    assert!(parse_header(b"GIF89a").is_err());
+}
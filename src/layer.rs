@@ -0,0 +1,116 @@
+use pdfstring::text_string;
+
+/// A layer (optional content group) of a document, that the viewer
+/// can show or hide, as described in section 8.11 of the PDF
+/// specification.
+///
+/// Layers are created by
+/// [Pdf::add_layer](struct.Pdf.html#method.add_layer), and content is
+/// drawn on them by
+/// [Canvas::layer](struct.Canvas.html#method.layer).
+/// A layer can only be used in the document that created it.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{BuiltinFont, LayerOrder, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// let english = document.add_layer("English");
+/// let german = document.add_layer("Deutsch");
+/// let grid = document.add_layer("Grid");
+/// // Only one language is shown at a time, German is hidden at first.
+/// document.set_layer_visible(german, false);
+/// document.add_layer_radio_group(&[english, german]);
+/// document.set_layer_order(vec![
+///     LayerOrder::Group(
+///         "Language".to_string(),
+///         vec![english.into(), german.into()],
+///     ),
+///     grid.into(),
+/// ]);
+/// document.render_page(180.0, 240.0, |canvas| {
+///     let font = BuiltinFont::Helvetica;
+///     canvas.layer(english, |c| c.left_text(10.0, 200.0, font, 12.0, "Hello"))?;
+///     canvas.layer(german, |c| c.left_text(10.0, 200.0, font, 12.0, "Hallo"))?;
+///     canvas.layer(grid, |c| {
+///         c.line(0.0, 120.0, 180.0, 120.0)?;
+///         c.stroke()
+///     })
+/// }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Layer {
+    object_id: usize,
+}
+
+impl Layer {
+    pub(crate) fn new(object_id: usize) -> Self {
+        Layer { object_id }
+    }
+
+    pub(crate) fn object_id(self) -> usize {
+        self.object_id
+    }
+}
+
+/// An entry in the list of layers shown in the layers panel of a
+/// viewer, as set by
+/// [Pdf::set_layer_order](struct.Pdf.html#method.set_layer_order).
+///
+/// See [Layer](struct.Layer.html) for an example.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayerOrder {
+    /// A layer, that can be turned on and off.
+    Layer(Layer),
+    /// A label, with entries below it.
+    Group(String, Vec<LayerOrder>),
+}
+
+impl From<Layer> for LayerOrder {
+    fn from(layer: Layer) -> Self {
+        LayerOrder::Layer(layer)
+    }
+}
+
+impl LayerOrder {
+    /// The entry as an element of an /Order array.
+    pub(crate) fn to_pdf(&self) -> String {
+        match *self {
+            LayerOrder::Layer(layer) => format!("{} 0 R", layer.object_id),
+            LayerOrder::Group(ref label, ref entries) => {
+                format!("[ {}{} ]", text_string(label), order_pdf(entries))
+            }
+        }
+    }
+}
+
+/// References to `layers`, each with a leading space.
+pub(crate) fn refs_pdf<I>(layers: I) -> String
+where
+    I: Iterator<Item = Layer>,
+{
+    layers
+        .map(|layer| format!(" {} 0 R", layer.object_id))
+        .collect()
+}
+
+/// The entries of an /Order array, each with a leading space.
+pub(crate) fn order_pdf(entries: &[LayerOrder]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!(" {}", entry.to_pdf()))
+        .collect()
+}
+
+#[test]
+fn test_layer_order() {
+    let order = vec![
+        LayerOrder::Group(
+            "Maps".to_string(),
+            vec![Layer::new(7).into(), Layer::new(8).into()],
+        ),
+        Layer::new(9).into(),
+    ];
+    assert_eq!(" [ (Maps) 7 0 R 8 0 R ] 9 0 R", order_pdf(&order));
+}
@@ -42,19 +42,30 @@
 //! pdf-canvas = "*"
 //! ```
 //!
+//! Enable the `png` feature to read PNG files, and the `image` feature
+//! to draw images from the [image crate](https://docs.rs/image), and
+//! to read any image file format it supports.
+//!
 //! Some more working usage examples exists in [the examples directory]
 //! (https://github.com/kaj/rust-pdf/tree/master/examples).
 #![deny(missing_docs)]
+// Enums implement Default by hand, without #[default] variants.
+#![allow(clippy::derivable_impls)]
 
+extern crate flate2;
+#[cfg(feature = "image")]
+extern crate image;
 #[macro_use]
 extern crate lazy_static;
-
+#[cfg(feature = "png")]
+extern crate png;
 extern crate time;
 
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fs::File;
 use std::io::{self, Seek, SeekFrom, Write};
+use std::sync::Arc;
 
 mod fontsource;
 pub use fontsource::{BuiltinFont, FontSource};
@@ -71,10 +82,110 @@ pub use encoding::Encoding;
 pub mod graphicsstate;
 
 mod outline;
-use outline::OutlineItem;
+pub use outline::OutlineItem;
+use outline::OutlineTree;
+
+mod md5;
+use md5::{Md5, Md5Writer};
+
+mod pdfstring;
+use pdfstring::{ascii_string, date};
+
+mod action;
+pub use action::{Action, Destination, SubmitFormat, Zoom};
+
+mod annotation;
+use annotation::AnnotationRefs;
+pub use annotation::{
+    Annotation, AnnotationFlags, AnnotationStyle, AttachmentIcon, MarkupKind,
+    NoteIcon, StampName,
+};
+
+mod formfield;
+pub use formfield::{FormField, TabOrder};
+
+mod viewer;
+pub use viewer::{Duplex, PageMode, ViewerPreferences};
+
+mod transition;
+pub use transition::{Transition, TransitionDirection, TransitionStyle};
+
+mod units;
+pub use units::{Cm, Inch, Mm, Pt};
+
+mod papersize;
+pub use papersize::PaperSize;
+
+mod pagebox;
+pub use pagebox::PageBoxes;
+
+mod pagelabel;
+pub use pagelabel::{PageLabels, PageNumbering};
+
+mod measure;
+pub use measure::MeasureScale;
+
+mod embeddedfile;
+pub use embeddedfile::{EmbeddedFile, FileRelationship};
+
+mod icc;
+
+mod pdfa;
+pub use pdfa::PdfA;
+
+mod pdfx;
+pub use pdfx::{OutputIntent, PdfX};
+
+mod bates;
+pub use bates::BatesNumbering;
+
+mod toc;
+pub use toc::TableOfContents;
+
+mod layer;
+pub use layer::{Layer, LayerOrder};
+
+mod structure;
+use structure::StructTree;
+pub use structure::{StructureType, TableScope};
+
+mod colorspace;
+use colorspace::ColorSpace;
+
+mod extgstate;
+use extgstate::ExtGState;
+
+mod formxobject;
+use formxobject::FormXObject;
+
+mod resources;
+use resources::Resources;
+
+mod ccitt;
+mod jpeg;
+mod jpx;
+
+mod imagexobject;
+pub use imagexobject::{Image, ImageColorSpace, ImageSource};
+
+mod rect;
+pub use rect::{Align, Fit, Margins, Rect};
+
+mod path;
+pub use path::{Path, PathSegment};
+
+mod shading;
+pub use shading::{GradientSpec, Shading};
+
+mod recording;
+pub use recording::Recording;
+
+mod pattern;
+use pattern::PatternResource;
+pub use pattern::{Paint, TilingPattern};
 
 mod canvas;
-pub use canvas::Canvas;
+pub use canvas::{Canvas, Origin, Rotation};
 
 mod textobject;
 pub use textobject::TextObject;
@@ -87,16 +198,102 @@ pub use textobject::TextObject;
 /// Don't forget to call `finish` when done, to write the document
 /// trailer, without it the written file won't be a proper PDF.
 pub struct Pdf {
-    output: File,
+    output: Md5Writer<File>,
+    /// The header is written with the first object, when the version
+    /// is known.
+    header_written: bool,
     object_offsets: Vec<i64>,
-    page_objects_ids: Vec<usize>,
+    pages: Vec<PageInfo>,
     all_font_object_ids: HashMap<BuiltinFont, usize>,
+    all_ext_gstate_object_ids: HashMap<ExtGState, usize>,
+    all_pattern_object_ids: HashMap<usize, usize>,
+    all_form_object_ids: HashMap<usize, usize>,
+    all_image_object_ids: HashMap<usize, usize>,
+    all_image_contents: HashMap<Image, usize>,
+    all_color_space_object_ids: HashMap<ColorSpace, usize>,
     outline_items: Vec<OutlineItem>,
+    annotations: Vec<PendingAnnotation>,
+    /// The rendered pages, by object id, to be written by `finish`.
+    page_dicts: HashMap<usize, PendingPage>,
+    named_destinations: BTreeMap<String, Destination>,
+    embedded_files: BTreeMap<String, EmbeddedFile>,
+    javascripts: BTreeMap<String, String>,
+    form_fonts: Vec<BuiltinFont>,
     document_info: BTreeMap<String, String>,
+    margins: Margins,
+    origin: Origin,
+    rotation: Rotation,
+    page_boxes: PageBoxes,
+    user_unit: f32,
+    tab_order: TabOrder,
+    transition: Option<Transition>,
+    page_duration: Option<f32>,
+    viewer_preferences: ViewerPreferences,
+    open_action: Option<Destination>,
+    page_mode: PageMode,
+    /// The page labels, with the index of the first page they apply to.
+    page_labels: Vec<(usize, PageLabels)>,
+    document_id: Option<Vec<u8>>,
+    deterministic: bool,
+    created: time::Tm,
+    pdfa: Option<PdfA>,
+    pdfx: Option<(PdfX, OutputIntent)>,
+    struct_tree: StructTree,
+    language: Option<String>,
+    /// The layers, with their names and whether they are visible.
+    layers: Vec<(Layer, String, bool)>,
+    layer_radio_groups: Vec<Vec<Layer>>,
+    layer_order: Option<Vec<LayerOrder>>,
+    /// The object ids of thumbnail images, by page object id.
+    thumbnails: HashMap<usize, usize>,
+    bates_numbering: Option<BatesNumbering>,
+    /// The watermark, and whether it is drawn over the content.
+    watermark: Option<(Box<Watermark>, bool)>,
+    xmp_extra: String,
+    xobjects: HashMap<String, Recording>,
+}
+
+/// Draws a watermark on a page.
+type Watermark = dyn Fn(&mut Canvas) -> io::Result<()>;
+
+/// What is needed to refer to a written page.
+#[derive(Clone, Copy, Debug)]
+struct PageInfo {
+    object_id: usize,
+    /// The page height, for pages with a top left origin, to convert
+    /// coordinates to the default coordinate system.
+    flip_height: Option<f32>,
+}
+
+/// An annotation with a reserved object id, to be written by `finish`,
+/// when all pages it may refer to are known.
+struct PendingAnnotation {
+    object_id: usize,
+    popup_id: Option<usize>,
+    page_id: usize,
+    annotation: Annotation,
+}
+
+/// A rendered page, to be written by `finish`, when its place in the
+/// page tree is known and any stamps are drawn on it.
+struct PendingPage {
+    width: f32,
+    height: f32,
+    resources: ResourceRefs,
+    /// The object id of the content stream.
+    contents: usize,
+    /// The object ids of content streams drawing stamps below and
+    /// above the content.
+    under: Vec<usize>,
+    over: Vec<usize>,
+    /// The other entries of the page dictionary.
+    dict: Vec<u8>,
 }
 
 const ROOT_OBJECT_ID: usize = 1;
 const PAGES_OBJECT_ID: usize = 2;
+/// The largest number of kids of a node in the page tree.
+const PAGE_TREE_FANOUT: usize = 32;
 
 impl Pdf {
     /// Create a new PDF document as a new file with given filename.
@@ -107,19 +304,59 @@ impl Pdf {
 
     /// Create a new PDF document, writing to `output`.
     pub fn new(output: File) -> io::Result<Pdf> {
-        let mut output = output; // Strange but needed
-
-        // TODO Maybe use a lower version?  Possibly decide by features used?
-        output.write_all(b"%PDF-1.7\n%\xB5\xED\xAE\xFB\n")?;
+        let output = Md5Writer {
+            inner: output,
+            md5: Md5::new(),
+        };
         Ok(Pdf {
             output,
+            header_written: false,
             // Object ID 0 is special in PDF.
             // We reserve IDs 1 and 2 for the catalog and page tree.
             object_offsets: vec![-1, -1, -1],
-            page_objects_ids: vec![],
+            pages: vec![],
             all_font_object_ids: HashMap::new(),
+            all_ext_gstate_object_ids: HashMap::new(),
+            all_pattern_object_ids: HashMap::new(),
+            all_form_object_ids: HashMap::new(),
+            all_image_object_ids: HashMap::new(),
+            all_image_contents: HashMap::new(),
+            all_color_space_object_ids: HashMap::new(),
             outline_items: Vec::new(),
+            annotations: Vec::new(),
+            page_dicts: HashMap::new(),
+            named_destinations: BTreeMap::new(),
+            embedded_files: BTreeMap::new(),
+            javascripts: BTreeMap::new(),
+            form_fonts: Vec::new(),
             document_info: BTreeMap::new(),
+            margins: Margins::default(),
+            origin: Origin::default(),
+            rotation: Rotation::default(),
+            page_boxes: PageBoxes::default(),
+            user_unit: 1.0,
+            tab_order: TabOrder::default(),
+            transition: None,
+            page_duration: None,
+            viewer_preferences: ViewerPreferences::default(),
+            open_action: None,
+            page_mode: PageMode::default(),
+            page_labels: Vec::new(),
+            document_id: None,
+            deterministic: false,
+            created: time::now(),
+            pdfa: None,
+            pdfx: None,
+            struct_tree: StructTree::default(),
+            language: None,
+            layers: Vec::new(),
+            layer_radio_groups: Vec::new(),
+            layer_order: None,
+            thumbnails: HashMap::new(),
+            bates_numbering: None,
+            watermark: None,
+            xmp_extra: String::new(),
+            xobjects: HashMap::new(),
         })
     }
     /// Set metadata: the document's title.
@@ -155,10 +392,503 @@ impl Pdf {
         self.document_info
             .insert("Producer".to_string(), producer.to_string());
     }
+    /// Set the natural language of the text in the document, as a
+    /// language tag such as `en-US`, for screen readers and other
+    /// text to speech tools.
+    ///
+    /// Tagged content in another language can override it, see
+    /// [Canvas::tagged_with_language](struct.Canvas.html#method.tagged_with_language).
+    pub fn set_language(&mut self, language: &str) {
+        self.language = Some(language.to_string());
+    }
+
+    /// Set the permanent identifier of the document, written as the
+    /// first part of the /ID of the file.
+    ///
+    /// The /ID also has an identifier of this version of the document,
+    /// the MD5 digest of the contents of the file, which is also the
+    /// default permanent identifier.
+    /// Set the permanent identifier to the one of an earlier version
+    /// of the same document, so that tools can tell that the file
+    /// replaces it.
+    pub fn set_document_id(&mut self, id: &[u8]) {
+        self.document_id = Some(id.to_vec());
+    }
+
+    /// Make the output depend only on the content of the document, so
+    /// that rendering the same document again gives the same file,
+    /// byte for byte, e.g. for reproducible builds or for comparing
+    /// with expected files in tests.
+    ///
+    /// This leaves out the creation and modification dates, which are
+    /// otherwise set to the current time, except the modification
+    /// times given for embedded files, see
+    /// [EmbeddedFile::with_modification_time](struct.EmbeddedFile.html#method.with_modification_time).
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    /// The creation time of the document, if it is written.
+    fn creation_time(&self) -> Option<time::Tm> {
+        if self.deterministic {
+            None
+        } else {
+            Some(self.created)
+        }
+    }
+
+    /// True if the document is to conform to PDF/X-1a.
+    fn is_pdfx1a(&self) -> bool {
+        matches!(self.pdfx, Some((PdfX::X1a, _)))
+    }
+
+    /// The start of every content stream, that makes RGB the initial
+    /// color space, except in PDF/X-1a documents, where RGB is not
+    /// allowed.
+    pub(crate) fn content_prologue(&self) -> &'static [u8] {
+        if self.is_pdfx1a() {
+            b""
+        } else {
+            b"/DeviceRGB cs /DeviceRGB CS\n"
+        }
+    }
+
+    /// Make the document conform to a PDF/A level, for long term
+    /// archiving.
+    ///
+    /// See [PdfA](enum.PdfA.html) for the details and an example.
+    pub fn set_pdfa(&mut self, level: PdfA) {
+        self.pdfa = Some(level);
+    }
+
+    /// Make the document conform to a PDF/X level, for print
+    /// production, with the printing condition it is prepared for.
+    ///
+    /// This should be called before any pages are rendered, since
+    /// they are checked for what the level allows, and the level
+    /// decides the pdf version in the header of the file.
+    /// See [PdfX](enum.PdfX.html) for the details and an example.
+    pub fn set_pdfx(&mut self, level: PdfX, intent: OutputIntent) {
+        self.pdfx = Some((level, intent));
+    }
+
+    /// Add XMP metadata for a PDF/A document, as `rdf:Description`
+    /// elements, e.g. the properties and extension schemas required
+    /// by an electronic invoice standard.
+    pub fn add_xmp_metadata(&mut self, rdf_descriptions: &str) {
+        self.xmp_extra.push_str(rdf_descriptions);
+    }
+
+    /// Set the margins for pages rendered after this.
+    ///
+    /// The margins don't limit what can be drawn on the page, but
+    /// define the
+    /// [Canvas::content_box](struct.Canvas.html#method.content_box)
+    /// for layout code.
+    /// The default is no margins.
+    pub fn set_margins(&mut self, margins: Margins) {
+        self.margins = margins;
+    }
+
+    /// Set the origin of the coordinate system for pages rendered after
+    /// this.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Origin, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_origin(Origin::TopLeft);
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     // A heading near the top of the page.
+    ///     let font = BuiltinFont::Helvetica_Bold;
+    ///     canvas.left_text(10.0, 30.0, font, 18.0, "Heading")?;
+    ///     canvas.rectangle(10.0, 40.0, 160.0, 2.0)?;
+    ///     canvas.fill()
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+
+    /// Set how viewers should present the document, see
+    /// [ViewerPreferences](struct.ViewerPreferences.html).
+    pub fn set_viewer_preferences(&mut self, preferences: ViewerPreferences) {
+        self.viewer_preferences = preferences;
+    }
+
+    /// Set which panel, such as the outline, viewers show with the
+    /// document when it is opened.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{PageMode, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_page_mode(PageMode::UseOutlines);
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     canvas.add_outline("Introduction");
+    ///     Ok(())
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_page_mode(&mut self, page_mode: PageMode) {
+        self.page_mode = page_mode;
+    }
+
+    /// Set the page (by its index, starting at 0) and zoom to show when
+    /// the document is opened, instead of the defaults of the viewer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Pdf, Zoom};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_open_action(1, Zoom::FitPage);
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_open_action(&mut self, page: usize, zoom: Zoom) {
+        self.open_action = Some(Destination::Page(page, zoom));
+    }
+
+    /// Set how viewers label (number) the pages rendered after this
+    /// call.
+    ///
+    /// See [PageLabels](struct.PageLabels.html) for an example.
+    pub fn set_page_labels(&mut self, labels: PageLabels) {
+        let first = self.pages.len();
+        if self.page_labels.last().map_or(false, |&(f, _)| f == first) {
+            self.page_labels.pop();
+        }
+        self.page_labels.push((first, labels));
+    }
+
+    /// Set how pages rendered after this call are rotated when they
+    /// are shown or printed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf, Rotation};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// // A wide table, drawn on a landscape page that is shown in
+    /// // portrait, like the other pages.
+    /// document.set_rotation(Rotation::Counterclockwise90);
+    /// document.render_page(240.0, 180.0, |canvas| {
+    ///     canvas.left_text(10.0, 160.0, BuiltinFont::Helvetica, 12.0, "Wide table")
+    /// }).unwrap();
+    /// document.set_rotation(Rotation::None);
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    /// Set the crop, bleed, trim and art boxes of pages rendered after
+    /// this call.
+    ///
+    /// See [PageBoxes](struct.PageBoxes.html) for an example.
+    pub fn set_page_boxes(&mut self, boxes: PageBoxes) {
+        self.page_boxes = boxes;
+    }
+
+    /// Set the size of a unit, in points (1/72 inch), for pages
+    /// rendered after this call.
+    ///
+    /// All sizes and coordinates of the page, including its width and
+    /// height, are given in units of this size.
+    /// This makes pages larger than the limit of 14400 units (200
+    /// inches) possible, e.g. for large drawings or banners.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Mm, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// // A banner of 10 x 1 meters, drawn in millimeters.
+    /// document.set_user_unit(Mm(1.0).into());
+    /// document.render_page(10000.0, 1000.0, |canvas| {
+    ///     canvas.rectangle(100.0, 100.0, 9800.0, 800.0)?;
+    ///     canvas.stroke()
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_user_unit(&mut self, points: f32) {
+        self.user_unit = points;
+    }
+
+    /// Set the order in which the user tabs through the form fields
+    /// (and other annotations) of pages rendered after this call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Pdf, TabOrder};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_tab_order(TabOrder::Row);
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_tab_order(&mut self, tab_order: TabOrder) {
+        self.tab_order = tab_order;
+    }
+
+    /// Set the transition effect for pages rendered after this call,
+    /// shown when moving to the page in presentation mode.
+    ///
+    /// See [Transition](struct.Transition.html) for an example.
+    pub fn set_transition(&mut self, transition: Option<Transition>) {
+        self.transition = transition;
+    }
+
+    /// Set the number of seconds that pages rendered after this call
+    /// are shown, before a viewer in presentation mode advances to the
+    /// next page automatically.
+    pub fn set_page_duration(&mut self, seconds: Option<f32>) {
+        self.page_duration = seconds;
+    }
+
+    /// Make the document open as a full screen presentation, which
+    /// advances automatically after `seconds` on each page rendered
+    /// after this call, e.g. for a kiosk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_presentation_mode(5.0);
+    /// for slide in &["Welcome", "Opening hours", "Prices"] {
+    ///     document.render_page(320.0, 240.0, |canvas| {
+    ///         let font = BuiltinFont::Helvetica_Bold;
+    ///         canvas.center_text(160.0, 120.0, font, 24.0, slide)
+    ///     }).unwrap();
+    /// }
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_presentation_mode(&mut self, seconds: f32) {
+        self.set_page_mode(PageMode::FullScreen);
+        self.set_page_duration(Some(seconds));
+    }
+
+    /// Define a named form XObject, a piece of content that can be
+    /// drawn on any page by
+    /// [Canvas::draw_xobject](struct.Canvas.html#method.draw_xobject),
+    /// but is only stored once in the file.
+    ///
+    /// The content is drawn by `render`, in a box from (0, 0) to
+    /// (width, height).
+    /// Defining a form with the same name as an earlier form replaces
+    /// it for pages rendered after this.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf};
+    /// # use pdf_canvas::graphicsstate::{Color, Matrix};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.define_xobject("stamp", 60.0, 20.0, |c| {
+    ///     c.set_stroke_color(Color::rgb(200, 0, 0))?;
+    ///     c.rectangle(1.0, 1.0, 58.0, 18.0)?;
+    ///     c.stroke()?;
+    ///     c.set_fill_color(Color::rgb(200, 0, 0))?;
+    ///     c.center_text(30.0, 6.0, BuiltinFont::Helvetica_Bold, 10.0, "DRAFT")
+    /// }).unwrap();
+    /// for _ in 0..2 {
+    ///     document.render_page(180.0, 240.0, |canvas| {
+    ///         let m = Matrix::translate(100.0, 200.0) * Matrix::rotate_deg(15.0);
+    ///         canvas.draw_xobject("stamp", m)
+    ///     }).unwrap();
+    /// }
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn define_xobject<F>(
+        &mut self,
+        name: &str,
+        width: f32,
+        height: f32,
+        render: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let recording = Recording::new(width, height, render)?;
+        self.xobjects.insert(name.to_string(), recording);
+        Ok(())
+    }
+
+    /// Add a JPEG image, to be drawn with
+    /// [Canvas::draw_image](struct.Canvas.html#method.draw_image).
+    ///
+    /// The JPEG data is embedded as is, see
+    /// [Image::jpeg](struct.Image.html#method.jpeg).
+    /// The image is written to the file on the first page that uses it.
+    pub fn add_jpeg(&mut self, data: Vec<u8>) -> io::Result<Image> {
+        Image::jpeg(data)
+    }
+
+    /// Add a PNG image, to be drawn with
+    /// [Canvas::draw_image](struct.Canvas.html#method.draw_image).
+    ///
+    /// See [Image::png](struct.Image.html#method.png).
+    /// Only available with the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn add_png(&mut self, data: &[u8]) -> io::Result<Image> {
+        Image::png(data)
+    }
+
+    /// Add a named destination, a stable name for a place in the
+    /// document.
+    ///
+    /// Links and outlines can go to the name with
+    /// `Destination::Named`, and other documents (or a url with
+    /// `#name`) can link into this document by it.
+    /// The page is given by its index, and doesn't have to be rendered
+    /// yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Destination, Pdf, Rect, Zoom};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.add_named_destination("summary", 1, Zoom::FitPage);
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     let summary = Destination::Named("summary".to_string());
+    ///     canvas.link(Rect::new(10.0, 10.0, 160.0, 20.0), summary);
+    ///     Ok(())
+    /// }).unwrap();
+    /// document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn add_named_destination(
+        &mut self,
+        name: &str,
+        page: usize,
+        zoom: Zoom,
+    ) {
+        self.named_destinations
+            .insert(name.to_string(), Destination::Page(page, zoom));
+    }
+
+    /// Attach a file to the document, such as the source data of a
+    /// chart or the XML of an invoice.
+    ///
+    /// Viewers list the file in their attachments panel, where it can
+    /// be opened or saved.
+    /// The `name` is the file name shown to the user, attaching
+    /// another file with the same name replaces the first one.
+    /// To attach a file to a place on a page instead, see
+    /// [Annotation::file_attachment](struct.Annotation.html#method.file_attachment).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.attach_file(
+    ///     "invoice.xml",
+    ///     b"<Invoice><Total>17.00</Total></Invoice>".to_vec(),
+    ///     "text/xml",
+    ///     "The invoice in machine readable form",
+    /// );
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn attach_file(
+        &mut self,
+        name: &str,
+        data: Vec<u8>,
+        mime_type: &str,
+        description: &str,
+    ) {
+        self.add_embedded_file(
+            EmbeddedFile::new(name, data)
+                .with_mime_type(mime_type)
+                .with_description(description),
+        );
+    }
+
+    /// Attach a file to the document, as
+    /// [attach_file](#method.attach_file), or associate it with the
+    /// document if it has a
+    /// [relationship](struct.EmbeddedFile.html#method.with_relationship).
+    pub fn add_embedded_file(&mut self, file: EmbeddedFile) {
+        self.embedded_files.insert(file.name().to_string(), file);
+    }
+
+    /// Add a document-level JavaScript script, which viewers that
+    /// support JavaScript run when the document is opened.
+    ///
+    /// This is typically used to define functions for calculating and
+    /// validating form fields.
+    /// Adding another script with the same `name` replaces the first
+    /// one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::Pdf;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.add_javascript(
+    ///     "totals",
+    ///     "function total(a, b) { return a * b; }",
+    /// );
+    /// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn add_javascript(&mut self, name: &str, script: &str) {
+        self.javascripts
+            .insert(name.to_string(), script.to_string());
+    }
+
+    /// Add a layer (optional content group) to the document, to draw
+    /// content on that the viewer can show or hide.
+    ///
+    /// The layer is visible when the document is opened, unless
+    /// changed by [set_layer_visible](#method.set_layer_visible).
+    /// See [Layer](struct.Layer.html) for an example.
+    pub fn add_layer(&mut self, name: &str) -> Layer {
+        let layer = Layer::new(self.reserve_object_id());
+        self.layers.push((layer, name.to_string(), true));
+        layer
+    }
+
+    /// Set whether `layer` is visible when the document is opened.
+    pub fn set_layer_visible(&mut self, layer: Layer, visible: bool) {
+        for entry in &mut self.layers {
+            if entry.0 == layer {
+                entry.2 = visible;
+            }
+        }
+    }
+
+    /// Make `layers` a group of radio buttons in the layers panel, so
+    /// that turning one of them on turns the others off.
+    ///
+    /// At most one of them should be visible when the document is
+    /// opened.
+    pub fn add_layer_radio_group(&mut self, layers: &[Layer]) {
+        self.layer_radio_groups.push(layers.to_vec());
+    }
+
+    /// Set how the layers are listed in the layers panel of the
+    /// viewer.
+    ///
+    /// By default, all layers are listed in the order they were added.
+    /// Layers that are not in `order` are not listed, but can still
+    /// be visible.
+    pub fn set_layer_order(&mut self, order: Vec<LayerOrder>) {
+        self.layer_order = Some(order);
+    }
 
     /// Return the current read/write position in the output file.
     fn tell(&mut self) -> io::Result<u64> {
-        self.output.seek(SeekFrom::Current(0))
+        self.output.inner.seek(SeekFrom::Current(0))
     }
 
     /// Create a new page in the PDF document.
@@ -175,87 +905,866 @@ impl Pdf {
     where
         F: FnOnce(&mut Canvas) -> io::Result<()>,
     {
-        let (contents_object_id, content_length, fonts, outline_items) = self
-            .write_new_object(move |contents_object_id, pdf| {
-                use canvas::create_canvas;
-                // Guess the ID of the next object. (We’ll assert it below.)
+        if self.pdfx.is_some()
+            && self.page_boxes.trim.is_none()
+            && self.page_boxes.art.is_none()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Pages of a PDF/X document must have a trim box",
+            ));
+        }
+        let page = Rect::new(0.0, 0.0, width, height);
+        let y_down = self.origin == Origin::TopLeft;
+        let margins = if y_down {
+            let m = self.margins;
+            Margins::new(m.bottom, m.right, m.top, m.left)
+        } else {
+            self.margins
+        };
+        let content_box = page.inside(margins);
+        let (
+            contents_object_id,
+            content_length,
+            resources,
+            outline_items,
+            mut annotations,
+            structure,
+        ) = self.write_new_object(move |contents_object_id, pdf| {
+            use canvas::create_canvas;
+            // Guess the ID of the next object. (We’ll assert it below.)
+            writeln!(
+                pdf.output,
+                "<< /Length {} 0 R >>\n\
+                     stream",
+                contents_object_id + 1,
+            )?;
+
+            let start = pdf.tell()?;
+            pdf.output.write_all(pdf.content_prologue())?;
+            if y_down {
+                writeln!(pdf.output, "1 0 0 -1 0 {} cm", height)?;
+            }
+            let mut resources = Resources::new();
+            let mut outline_items: Vec<OutlineItem> = Vec::new();
+            let mut annotations = Vec::new();
+            let mut structure = Vec::new();
+            render_contents(&mut create_canvas(
+                &mut pdf.output,
+                page,
+                content_box,
+                y_down,
+                &mut resources,
+                &mut outline_items,
+                &mut annotations,
+                &pdf.xobjects,
+                Some(&mut structure),
+            ))?;
+            let end = pdf.tell()?;
+
+            writeln!(pdf.output, "endstream")?;
+            Ok((
+                contents_object_id,
+                end - start,
+                resources,
+                outline_items,
+                annotations,
+                structure,
+            ))
+        })?;
+        self.write_new_object(|length_object_id, pdf| {
+            assert!(length_object_id == contents_object_id + 1);
+            writeln!(pdf.output, "{}", content_length)
+        })?;
+
+        let resources = self.write_resources(&resources)?;
+        // Fields with a tab index go first, in order (the sort is
+        // stable, so other annotations keep their order).
+        annotations.sort_by_key(|a| (a.tab_index().is_none(), a.tab_index()));
+        // The annotations may refer to pages that are not written yet,
+        // so only reserve their ids here.
+        let mut annotation_ids = Vec::new();
+        let mut pending = Vec::new();
+        for annotation in annotations {
+            let object_id = self.reserve_object_id();
+            annotation_ids.push(object_id);
+            let popup_id = if annotation.popup().is_some() {
+                let popup_id = self.reserve_object_id();
+                annotation_ids.push(popup_id);
+                Some(popup_id)
+            } else {
+                None
+            };
+            pending.push((object_id, popup_id, annotation));
+        }
+        // The page dictionary is written by `finish`, when its place in
+        // the page tree is known.
+        let page_oid = self.reserve_object_id();
+        let struct_parents = if structure.is_empty() {
+            None
+        } else {
+            Some(self.struct_tree.add_page(page_oid, structure))
+        };
+        let dict =
+            self.page_dict(width, height, &annotation_ids, struct_parents)?;
+        self.page_dicts.insert(
+            page_oid,
+            PendingPage {
+                width,
+                height,
+                resources,
+                contents: contents_object_id,
+                under: Vec::new(),
+                over: Vec::new(),
+                dict,
+            },
+        );
+        for (object_id, popup_id, annotation) in pending {
+            self.annotations.push(PendingAnnotation {
+                object_id,
+                popup_id,
+                page_id: page_oid,
+                annotation,
+            });
+        }
+        // Take the outline_items from this page, mark them with the page,
+        // and save them for the document outline.
+        for mut item in outline_items {
+            item.set_page(self.pages.len());
+            self.outline_items.push(item);
+        }
+        self.pages.push(PageInfo {
+            object_id: page_oid,
+            flip_height: if y_down { Some(height) } else { None },
+        });
+        Ok(())
+    }
+
+    /// Create a new page of a standard paper size, and render its
+    /// contents with `render_contents`.
+    ///
+    /// See [PaperSize](enum.PaperSize.html) for an example, and
+    /// [render_page](#method.render_page) for the details.
+    pub fn render_page_sized<F>(
+        &mut self,
+        paper: PaperSize,
+        render_contents: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let (width, height) = paper.size();
+        self.render_page(width, height, render_contents)
+    }
+
+    /// Create a new page and render its contents with
+    /// `render_contents`, like [render_page](#method.render_page), but
+    /// insert it at `index` (starting at 0) instead of after the last
+    /// page.
+    ///
+    /// Note that destinations given by page index, such as links and
+    /// named destinations, refer to the order of the pages when the
+    /// document is finished.  Outline items follow the pages they were
+    /// added on.  Page labels apply to positions: the inserted page
+    /// gets the labels of the page it is inserted before, and the
+    /// ranges of labels after it move one page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// let font = BuiltinFont::Times_Roman;
+    /// let mut chapters = Vec::new();
+    /// for title in &["Beginning", "Middle", "End"] {
+    ///     chapters.push(title.to_string());
+    ///     document.render_page(180.0, 240.0, |canvas| {
+    ///         canvas.add_outline(title);
+    ///         canvas.left_text(10.0, 220.0, font, 14.0, title)
+    ///     }).unwrap();
+    /// }
+    /// // Now that the chapters are known, put a table of contents first.
+    /// document.insert_page(0, 180.0, 240.0, |canvas| {
+    ///     canvas.add_outline("Contents");
+    ///     for (i, title) in chapters.iter().enumerate() {
+    ///         let y = 200.0 - 20.0 * i as f32;
+    ///         canvas.left_text(10.0, y, font, 12.0, title)?;
+    ///     }
+    ///     Ok(())
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn insert_page<F>(
+        &mut self,
+        index: usize,
+        width: f32,
+        height: f32,
+        render_contents: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        if index > self.pages.len() {
+            return Err(page_index_error(index, self.pages.len() + 1));
+        }
+        self.render_page(width, height, render_contents)?;
+        let last = self.pages.len() - 1;
+        self.move_page(last, index)
+    }
+
+    /// Insert pages with a table of contents at `index`, with the
+    /// outline items of the pages rendered so far, in the style given
+    /// by `contents`.
+    /// Return the number of pages inserted.
+    ///
+    /// The pages are `width` x `height` points large, with the current
+    /// margins, and are inserted like by
+    /// [insert_page](#method.insert_page).  The page numbers in the
+    /// table are the positions of the pages in the finished document,
+    /// starting at 1.
+    /// See [TableOfContents](struct.TableOfContents.html) for an
+    /// example.
+    pub fn insert_table_of_contents(
+        &mut self,
+        index: usize,
+        width: f32,
+        height: f32,
+        contents: &TableOfContents,
+    ) -> io::Result<usize> {
+        if index > self.pages.len() {
+            return Err(page_index_error(index, self.pages.len() + 1));
+        }
+        let area = Rect::new(0.0, 0.0, width, height).inside(self.margins);
+        let first = contents.entries_per_page(area, true);
+        let rest = contents.entries_per_page(area, false);
+        if rest == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The table of contents doesn't fit on the page",
+            ));
+        }
+        let mut entries = self
+            .outline_items
+            .iter()
+            .filter(|item| contents.lists(item.level()))
+            .filter_map(|item| {
+                item.page().map(|page| {
+                    (item.title().to_string(), item.level(), page)
+                })
+            })
+            .collect::<Vec<_>>();
+        let count =
+            1 + (entries.len().saturating_sub(first) + rest - 1) / rest;
+        // The pages from `index` on move back by the inserted pages.
+        for entry in &mut entries {
+            if entry.2 >= index {
+                entry.2 += count;
+            }
+        }
+        // The table is drawn in the default coordinate system.
+        let origin =
+            ::std::mem::replace(&mut self.origin, Origin::BottomLeft);
+        let mut result = Ok(());
+        let mut start = 0;
+        for i in 0..count {
+            let end = (start + if i == 0 { first } else { rest })
+                .min(entries.len());
+            let chunk = &entries[start..end];
+            start = end;
+            result = self.insert_page(index + i, width, height, |c| {
+                contents.draw_page(c, chunk, i == 0)
+            });
+            if result.is_err() {
+                break;
+            }
+        }
+        self.origin = origin;
+        result.map(|()| count)
+    }
+
+    /// Move the page at index `from` to index `to`, so that it is
+    /// shown before the page that is now at `to` (or after it, when
+    /// moving a page towards the end).
+    ///
+    /// See [insert_page](#method.insert_page) for how destinations and
+    /// outline items are affected.
+    pub fn move_page(&mut self, from: usize, to: usize) -> io::Result<()> {
+        let count = self.pages.len();
+        if from >= count || to >= count {
+            return Err(page_index_error(from.max(to), count));
+        }
+        let mut order = (0..count).collect::<Vec<_>>();
+        let page = order.remove(from);
+        order.insert(to, page);
+        self.reorder_pages(&order);
+        self.remove_page_label_position(from);
+        self.insert_page_label_position(to);
+        Ok(())
+    }
+
+    /// Remove the page at `index` from the document, with its
+    /// annotations and outline items.
+    ///
+    /// See [insert_page](#method.insert_page) for how destinations are
+    /// affected.
+    pub fn delete_page(&mut self, index: usize) -> io::Result<()> {
+        let count = self.pages.len();
+        if index >= count {
+            return Err(page_index_error(index, count));
+        }
+        let page_id = self.pages[index].object_id;
+        let (deleted, kept) = self
+            .annotations
+            .drain(..)
+            .partition::<Vec<_>, _>(|a| a.page_id == page_id);
+        self.annotations = kept;
+        // The object ids of the annotations are already reserved, so
+        // they are left as null objects, like the page.
+        for annotation in deleted {
+            let ids = Some(annotation.object_id).into_iter();
+            for id in ids.chain(annotation.popup_id) {
+                self.write_object_with_id(id, |pdf| {
+                    writeln!(pdf.output, "null")
+                })?;
+            }
+        }
+        self.struct_tree.remove_page(page_id);
+        let order = (0..count).filter(|&i| i != index).collect::<Vec<_>>();
+        self.reorder_pages(&order);
+        self.remove_page_label_position(index);
+        Ok(())
+    }
+
+    /// Set a thumbnail image for the page at `index`, which some
+    /// viewers show in their page panel instead of rendering a small
+    /// version of the page, e.g. to speed up navigation in large
+    /// documents.
+    ///
+    /// The image should be a small raster of the page, rendered by
+    /// the caller, typically about 100 pixels wide.
+    /// It must not be transparent, and the colors of a CMYK image may
+    /// not be shown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Image, ImageColorSpace, PageMode, Pdf};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+    /// // A blank page gives a white thumbnail.
+    /// let white = vec![255; 45 * 60];
+    /// let thumbnail =
+    ///     Image::raw(45, 60, ImageColorSpace::DeviceGray, &white).unwrap();
+    /// document.set_page_thumbnail(0, &thumbnail).unwrap();
+    /// document.set_page_mode(PageMode::UseThumbs);
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_page_thumbnail(
+        &mut self,
+        index: usize,
+        image: &Image,
+    ) -> io::Result<()> {
+        let page_id = match self.pages.get(index) {
+            Some(page) => page.object_id,
+            None => return Err(page_index_error(index, self.pages.len())),
+        };
+        let image_id = self.write_image(image)?;
+        self.thumbnails.insert(page_id, image_id);
+        Ok(())
+    }
+
+    /// Stamp Bates numbers on all pages of the document, when it is
+    /// finished.
+    ///
+    /// See [BatesNumbering](struct.BatesNumbering.html) for an
+    /// example.
+    pub fn set_bates_numbering(&mut self, numbering: BatesNumbering) {
+        self.bates_numbering = Some(numbering);
+    }
+
+    /// Draw a watermark over the content of all pages of the document,
+    /// when it is finished, e.g. to mark it as a draft.
+    ///
+    /// The watermark is drawn by `render` in the default coordinate
+    /// system of the page, whatever the origin of the page is, and
+    /// `Canvas::bounds` is the whole page.
+    /// It is drawn once for each size of page, and shared by the pages
+    /// of that size.
+    /// A watermark replaces any earlier one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{BuiltinFont, Pdf};
+    /// # use pdf_canvas::graphicsstate::{Color, Matrix};
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// document.set_watermark(|canvas| {
+    ///     let page = canvas.bounds();
+    ///     canvas.set_fill_color(Color::rgb(200, 0, 0))?;
+    ///     canvas.set_fill_alpha(0.3)?;
+    ///     canvas.concat(
+    ///         Matrix::translate(page.width / 2.0, page.height / 2.0)
+    ///             * Matrix::rotate_deg(45.0),
+    ///     )?;
+    ///     canvas.center_text(0.0, -18.0, BuiltinFont::Helvetica_Bold, 48.0, "DRAFT")
+    /// });
+    /// document.render_page(180.0, 240.0, |canvas| {
+    ///     canvas.left_text(10.0, 200.0, BuiltinFont::Helvetica, 12.0, "Hello")
+    /// }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn set_watermark<F>(&mut self, render: F)
+    where
+        F: Fn(&mut Canvas) -> io::Result<()> + 'static,
+    {
+        self.watermark = Some((Box::new(render), true));
+    }
+
+    /// Draw a watermark below the content of all pages of the
+    /// document, when it is finished.
+    ///
+    /// This is like [set_watermark](#method.set_watermark), but the
+    /// watermark is covered by anything opaque on the pages, such as
+    /// images, which suits backgrounds and letterheads.
+    pub fn set_watermark_below<F>(&mut self, render: F)
+    where
+        F: Fn(&mut Canvas) -> io::Result<()> + 'static,
+    {
+        self.watermark = Some((Box::new(render), false));
+    }
+
+    /// Put the pages in a new order, where `order` has the old index of
+    /// each page, and update everything that refers to pages by index.
+    /// Pages not in `order` are removed.
+    fn reorder_pages(&mut self, order: &[usize]) {
+        let mut new_index = vec![None; self.pages.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = Some(new);
+        }
+        self.pages = order.iter().map(|&old| self.pages[old]).collect();
+
+        let items = ::std::mem::take(&mut self.outline_items);
+        for mut item in items {
+            if let Some(page) = item.page().and_then(|page| new_index[page]) {
+                item.set_page(page);
+                self.outline_items.push(item);
+            }
+        }
+        // Keep the outline in page order (the sort is stable, so items
+        // of the same page keep their order).
+        self.outline_items.sort_by_key(|item| item.page());
+    }
+
+    /// Update the page labels for removing the page at `index`.
+    ///
+    /// Page labels apply to positions rather than to pages, so the
+    /// ranges after the page move back one position.
+    fn remove_page_label_position(&mut self, index: usize) {
+        for label in &mut self.page_labels {
+            if label.0 > index {
+                label.0 -= 1;
+            }
+        }
+        // If a range became empty, the range after it takes over.
+        let mut labels = ::std::mem::take(&mut self.page_labels);
+        labels.reverse();
+        labels.dedup_by_key(|&mut (first, _)| first);
+        labels.reverse();
+        self.page_labels = labels;
+    }
+
+    /// Update the page labels for inserting a page at `index`, which
+    /// joins the range of the page that was at `index`.
+    fn insert_page_label_position(&mut self, index: usize) {
+        for label in &mut self.page_labels {
+            if label.0 > index {
+                label.0 += 1;
+            }
+        }
+    }
+
+    /// Write an image (unless it, or an image with the same content,
+    /// is already written) and return its object id.
+    fn write_image(&mut self, image: &Image) -> io::Result<usize> {
+        if let Some(&object_id) = self.all_image_object_ids.get(&image.id()) {
+            return Ok(object_id);
+        }
+        let object_id = match self.all_image_contents.get(image) {
+            Some(&object_id) => object_id,
+            None => {
+                let object_id = image.write_object(self)?;
+                self.all_image_contents.insert(image.clone(), object_id);
+                object_id
+            }
+        };
+        self.all_image_object_ids.insert(image.id(), object_id);
+        Ok(object_id)
+    }
+
+    /// Write the objects needed by `resources` (unless they are already
+    /// written) and return the resource dictionary referencing them.
+    fn write_resources(
+        &mut self,
+        resources: &Resources,
+    ) -> io::Result<ResourceRefs> {
+        if self.is_pdfx1a() {
+            check_pdfx1a(resources)?;
+        }
+        let mut font_oids = NamedRefs::new();
+        // Write the resources in order of their names, so the output
+        // doesn't depend on the order of the hash maps.
+        let mut fonts = resources.fonts.iter().collect::<Vec<_>>();
+        fonts.sort_by_key(|&(_, r)| r.to_string());
+        for (src, r) in fonts {
+            if let Some(&object_id) = self.all_font_object_ids.get(&src) {
+                font_oids.insert(r.to_string(), object_id);
+            } else {
+                let object_id = src.write_object(self)?;
+                font_oids.insert(r.to_string(), object_id);
+                self.all_font_object_ids.insert(*src, object_id);
+            }
+        }
+        let mut form_oids = Vec::with_capacity(resources.forms.len());
+        for form in &resources.forms {
+            let shared = form
+                .shared_id()
+                .and_then(|id| self.all_form_object_ids.get(&id));
+            if let Some(&object_id) = shared {
+                form_oids.push(object_id);
+            } else {
+                let object_id = form.write_object(self)?;
+                if let Some(id) = form.shared_id() {
+                    self.all_form_object_ids.insert(id, object_id);
+                }
+                form_oids.push(object_id);
+            }
+        }
+        let mut gs_oids = NamedRefs::new();
+        let mut ext_gstates =
+            resources.ext_gstates.iter().collect::<Vec<_>>();
+        ext_gstates.sort_by_key(|&(_, name)| name);
+        for (state, name) in ext_gstates {
+            if !state.is_shareable() {
+                let object_id = state.write_object(self, &form_oids)?;
+                gs_oids.insert(name.clone(), object_id);
+            } else if let Some(&object_id) =
+                self.all_ext_gstate_object_ids.get(state)
+            {
+                gs_oids.insert(name.clone(), object_id);
+            } else {
+                let object_id = state.write_object(self, &form_oids)?;
+                gs_oids.insert(name.clone(), object_id);
+                self.all_ext_gstate_object_ids
+                    .insert(state.clone(), object_id);
+            }
+        }
+        let mut xobject_oids = NamedRefs::new();
+        for (name, form) in &resources.xobjects {
+            xobject_oids.insert(name.clone(), form_oids[*form]);
+        }
+        for (name, image) in &resources.images {
+            xobject_oids.insert(name.clone(), self.write_image(image)?);
+        }
+        let mut shading_oids = NamedRefs::new();
+        for (name, shading) in &resources.shadings {
+            shading_oids.insert(name.clone(), shading.write_object(self)?);
+        }
+        let mut pattern_oids = NamedRefs::new();
+        for (name, pattern) in &resources.patterns {
+            let tiling_id = pattern.tiling_id();
+            let written = tiling_id
+                .and_then(|id| self.all_pattern_object_ids.get(&id))
+                .cloned();
+            let object_id = match written {
+                Some(object_id) => object_id,
+                None => pattern.write_object(self)?,
+            };
+            if let Some(id) = tiling_id {
+                self.all_pattern_object_ids.insert(id, object_id);
+            }
+            pattern_oids.insert(name.clone(), object_id);
+        }
+        let mut property_oids = NamedRefs::new();
+        for &(ref name, layer) in &resources.layers {
+            property_oids.insert(name.clone(), layer.object_id());
+        }
+        let mut color_space_oids = NamedRefs::new();
+        let mut color_spaces =
+            resources.color_spaces.iter().collect::<Vec<_>>();
+        color_spaces.sort_by_key(|&(_, name)| name);
+        for (space, name) in color_spaces {
+            if let Some(&object_id) =
+                self.all_color_space_object_ids.get(space)
+            {
+                color_space_oids.insert(name.clone(), object_id);
+            } else {
+                let object_id = space.write_object(self)?;
+                color_space_oids.insert(name.clone(), object_id);
+                self.all_color_space_object_ids
+                    .insert(space.clone(), object_id);
+            }
+        }
+        Ok(ResourceRefs {
+            entries: vec![
+                ("Font", font_oids),
+                ("ExtGState", gs_oids),
+                ("XObject", xobject_oids),
+                ("Shading", shading_oids),
+                ("Pattern", pattern_oids),
+                ("ColorSpace", color_space_oids),
+                ("Properties", property_oids),
+            ],
+        })
+    }
+
+    /// The entries of a page dictionary, except its parent, resources,
+    /// media box and contents.
+    fn page_dict(
+        &self,
+        width: f32,
+        height: f32,
+        annotation_ids: &[usize],
+        struct_parents: Option<usize>,
+    ) -> io::Result<Vec<u8>> {
+        let mut dict = Vec::new();
+        write!(dict, "{}", self.page_boxes.to_pdf(width, height))?;
+        if !annotation_ids.is_empty() {
+            write!(dict, "   /Annots [ ")?;
+            for id in annotation_ids {
+                write!(dict, "{} 0 R ", id)?;
+            }
+            writeln!(dict, "]")?;
+        }
+        if self.user_unit != 1.0 {
+            writeln!(dict, "   /UserUnit {}", self.user_unit)?;
+        }
+        if self.rotation != Rotation::None {
+            writeln!(dict, "   /Rotate {}", self.rotation.degrees())?;
+        }
+        if let Some(tabs) = self.tab_order.to_pdf() {
+            writeln!(dict, "   /Tabs {}", tabs)?;
+        }
+        if let Some(transition) = self.transition {
+            writeln!(dict, "   /Trans {}", transition.to_pdf())?;
+        }
+        if let Some(seconds) = self.page_duration {
+            writeln!(dict, "   /Dur {}", seconds)?;
+        }
+        if let Some(key) = struct_parents {
+            writeln!(dict, "   /StructParents {}", key)?;
+        }
+        writeln!(dict, ">>")?;
+        Ok(dict)
+    }
+
+    /// Draw the stamps, such as Bates numbers, that go on all pages.
+    fn write_stamps(&mut self) -> io::Result<()> {
+        if let Some((render, over)) = self.watermark.take() {
+            // The forms of the watermark, by page size.
+            let mut forms = HashMap::new();
+            for page in self.pages.clone() {
+                let size = {
+                    let dict = &self.page_dicts[&page.object_id];
+                    (dict.width.to_bits(), dict.height.to_bits())
+                };
+                let form = match forms.get(&size) {
+                    Some(&form) => form,
+                    None => {
+                        let form =
+                            self.stamp_form(page.object_id, |c| render(c))?;
+                        forms.insert(size, form);
+                        form
+                    }
+                };
+                self.add_stamp(page.object_id, form, over)?;
+            }
+        }
+        if let Some(numbering) = self.bates_numbering.clone() {
+            for (index, page) in self.pages.clone().iter().enumerate() {
+                let form = self.stamp_form(page.object_id, |c| {
+                    numbering.draw(c, index)
+                })?;
+                self.add_stamp(page.object_id, form, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render a stamp for the page with object id `page_id` as a form,
+    /// in the default coordinate system of the page.
+    fn stamp_form<F>(
+        &mut self,
+        page_id: usize,
+        render: F,
+    ) -> io::Result<usize>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        use canvas::render_content;
+        let (width, height) = {
+            let page = &self.page_dicts[&page_id];
+            (page.width, page.height)
+        };
+        let (content, resources) = render_content(
+            Rect::new(0.0, 0.0, width, height),
+            false,
+            &mut Vec::new(),
+            &self.xobjects,
+            render,
+        )?;
+        FormXObject::new([0.0, 0.0, width, height], content, resources)
+            .write_object(self)
+    }
+
+    /// Draw the form with object id `form_id` as a stamp on the page
+    /// with object id `page_id`, below or `over` its content.
+    fn add_stamp(
+        &mut self,
+        page_id: usize,
+        form_id: usize,
+        over: bool,
+    ) -> io::Result<()> {
+        let mut page = self.page_dicts.remove(&page_id).unwrap();
+        let name = format!("/Stamp{}", page.under.len() + page.over.len());
+        page.resources.insert("XObject", name.clone(), form_id);
+        let mut content = Vec::new();
+        if over && page.over.is_empty() {
+            // Save the graphics state before the content of the page,
+            // and restore it before the stamps above it, so they are
+            // drawn in the default coordinate system.
+            page.under.push(self.write_content_stream(b"q\n")?);
+            writeln!(content, "Q")?;
+        }
+        writeln!(content, "/Artifact BMC\nq {} Do Q\nEMC", name)?;
+        let stream_id = self.write_content_stream(&content)?;
+        if over {
+            page.over.push(stream_id);
+        } else {
+            page.under.push(stream_id);
+        }
+        self.page_dicts.insert(page_id, page);
+        Ok(())
+    }
+
+    /// Write a content stream.  Return its object id.
+    fn write_content_stream(&mut self, content: &[u8]) -> io::Result<usize> {
+        self.write_new_object(|object_id, pdf| {
+            writeln!(
+                pdf.output,
+                "<< /Length {} >>\n\
+                 stream",
+                content.len(),
+            )?;
+            pdf.output.write_all(content)?;
+            writeln!(pdf.output, "endstream")?;
+            Ok(object_id)
+        })
+    }
+
+    /// Write the page tree, and the dictionaries of the pages in it.
+    ///
+    /// Viewers are slow with a very long list of pages, so if there are
+    /// many pages, they are split into a tree of intermediate page
+    /// tree nodes with at most `PAGE_TREE_FANOUT` kids each.
+    fn write_page_tree(&mut self) -> io::Result<()> {
+        let mut parents = HashMap::new();
+        // The nodes (object id, kids and page count), root last.
+        let mut nodes = Vec::new();
+        let mut level = self
+            .pages
+            .iter()
+            .map(|page| (page.object_id, 1))
+            .collect::<Vec<_>>();
+        while level.len() > PAGE_TREE_FANOUT {
+            let mut next_level = Vec::new();
+            for kids in level.chunks(PAGE_TREE_FANOUT) {
+                let id = self.reserve_object_id();
+                let count = kids.iter().map(|&(_, count)| count).sum();
+                for &(kid, _) in kids {
+                    parents.insert(kid, id);
+                }
+                nodes.push((id, kids.to_vec(), count));
+                next_level.push((id, count));
+            }
+            level = next_level;
+        }
+        for &(kid, _) in &level {
+            parents.insert(kid, PAGES_OBJECT_ID);
+        }
+        nodes.push((PAGES_OBJECT_ID, level, self.pages.len()));
+
+        for (id, kids, count) in nodes {
+            self.write_object_with_id(id, |pdf| {
+                write!(pdf.output, "<< /Type /Pages\n   ")?;
+                if let Some(parent) = parents.get(&id) {
+                    write!(pdf.output, "/Parent {} 0 R\n   ", parent)?;
+                }
                 writeln!(
                     pdf.output,
-                    "<< /Length {} 0 R >>\n\
-                     stream",
-                    contents_object_id + 1,
+                    "/Count {c}\n   \
+                     /Kids [ {kids}]\n\
+                     >>",
+                    c = count,
+                    kids = kids
+                        .iter()
+                        .map(|&(kid, _)| format!("{} 0 R ", kid))
+                        .collect::<String>(),
+                )
+            })?;
+        }
+        for page in self.pages.clone() {
+            let p = self.page_dicts.remove(&page.object_id).unwrap();
+            self.write_object_with_id(page.object_id, |pdf| {
+                writeln!(
+                    pdf.output,
+                    "<< /Type /Page\n   \
+                     /Parent {} 0 R",
+                    parents[&page.object_id],
                 )?;
-
-                let start = pdf.tell()?;
-                writeln!(pdf.output, "/DeviceRGB cs /DeviceRGB CS")?;
-                let mut fonts = HashMap::new();
-                let mut outline_items: Vec<OutlineItem> = Vec::new();
-                render_contents(&mut create_canvas(
-                    &mut pdf.output,
-                    &mut fonts,
-                    &mut outline_items,
-                ))?;
-                let end = pdf.tell()?;
-
-                writeln!(pdf.output, "endstream")?;
-                Ok((contents_object_id, end - start, fonts, outline_items))
+                if let Some(thumbnail) = pdf.thumbnails.get(&page.object_id) {
+                    writeln!(pdf.output, "   /Thumb {} 0 R", thumbnail)?;
+                }
+                write!(
+                    pdf.output,
+                    "   /Resources {}\n   \
+                     /MediaBox [ 0 0 {} {} ]\n   \
+                     /Contents ",
+                    p.resources, p.width, p.height,
+                )?;
+                if p.under.is_empty() && p.over.is_empty() {
+                    writeln!(pdf.output, "{} 0 R", p.contents)?;
+                } else {
+                    write!(pdf.output, "[")?;
+                    for id in
+                        p.under.iter().chain(&[p.contents]).chain(&p.over)
+                    {
+                        write!(pdf.output, " {} 0 R", id)?;
+                    }
+                    writeln!(pdf.output, " ]")?;
+                }
+                pdf.output.write_all(&p.dict)
             })?;
-        self.write_new_object(|length_object_id, pdf| {
-            assert!(length_object_id == contents_object_id + 1);
-            writeln!(pdf.output, "{}", content_length)
-        })?;
-
-        let mut font_oids = NamedRefs::new();
-        for (src, r) in &fonts {
-            if let Some(&object_id) = self.all_font_object_ids.get(&src) {
-                font_oids.insert(r.clone(), object_id);
-            } else {
-                let object_id = src.write_object(self)?;
-                font_oids.insert(r.clone(), object_id);
-                self.all_font_object_ids.insert(*src, object_id);
-            }
         }
-        let page_oid = self.write_page_dict(
-            contents_object_id,
-            width,
-            height,
-            font_oids,
-        )?;
-        // Take the outline_items from this page, mark them with the page ref,
-        // and save them for the document outline.
-        for i in &outline_items {
-            let mut item = i.clone();
-            item.set_page(page_oid);
-            self.outline_items.push(item);
+        // Deleted pages are left as null objects.
+        let mut deleted = self
+            .page_dicts
+            .drain()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        deleted.sort();
+        for id in deleted {
+            self.write_object_with_id(id, |pdf| {
+                writeln!(pdf.output, "null")
+            })?;
         }
-        self.page_objects_ids.push(page_oid);
         Ok(())
     }
 
-    fn write_page_dict(
-        &mut self,
-        content_oid: usize,
-        width: f32,
-        height: f32,
-        font_oids: NamedRefs,
-    ) -> io::Result<usize> {
-        self.write_new_object(|page_oid, pdf| {
-            writeln!(
-                pdf.output,
-                "<< /Type /Page\n   \
-                 /Parent {parent} 0 R\n   \
-                 /Resources << /Font << {fonts}>> >>\n   \
-                 /MediaBox [ 0 0 {width} {height} ]\n   \
-                 /Contents {c_oid} 0 R\n\
-                 >>",
-                parent = PAGES_OBJECT_ID,
-                fonts = font_oids,
-                width = width,
-                height = height,
-                c_oid = content_oid,
-            )
-            .map(|_| page_oid)
-        })
+    /// Reserve an object id, for an object to be written later with
+    /// `write_object_with_id`.
+    fn reserve_object_id(&mut self) -> usize {
+        self.object_offsets.push(-1);
+        self.object_offsets.len() - 1
     }
 
     fn write_new_object<F, T>(&mut self, write_content: F) -> io::Result<T>
@@ -291,6 +1800,9 @@ impl Pdf {
     where
         F: FnOnce(&mut Pdf) -> io::Result<T>,
     {
+        if !self.header_written {
+            self.write_header()?;
+        }
         // `as i64` here would overflow for PDF files bigger than 2**63 bytes
         let offset = self.tell()? as i64;
         writeln!(self.output, "{} 0 obj", id)?;
@@ -299,25 +1811,28 @@ impl Pdf {
         Ok((result, offset))
     }
 
+    /// Write the header, with the version of a PDF/X level, if any.
+    fn write_header(&mut self) -> io::Result<()> {
+        // TODO Maybe use a lower version?  Possibly decide by features used?
+        let version = match self.pdfx {
+            Some((level, _)) => level.pdf_version(),
+            None => "1.7",
+        };
+        writeln!(self.output, "%PDF-{}", version)?;
+        self.output.write_all(b"%\xB5\xED\xAE\xFB\n")?;
+        self.header_written = true;
+        Ok(())
+    }
+
     /// Write out the document trailer.
     /// The trailer consists of the pages object, the root object,
     /// the xref list, the trailer object and the startxref position.
     pub fn finish(mut self) -> io::Result<()> {
-        self.write_object_with_id(PAGES_OBJECT_ID, |pdf| {
-            writeln!(
-                pdf.output,
-                "<< /Type /Pages\n   \
-                 /Count {c}\n   \
-                 /Kids [ {pages}]\n\
-                 >>",
-                c = pdf.page_objects_ids.len(),
-                pages = pdf
-                    .page_objects_ids
-                    .iter()
-                    .map(|id| format!("{} 0 R ", id))
-                    .collect::<String>(),
-            )
-        })?;
+        self.write_stamps()?;
+        self.write_page_tree()?;
+        if let Some((level, ref intent)) = self.pdfx {
+            self.check_pdfx(level, intent)?;
+        }
         let document_info_id = if !self.document_info.is_empty() {
             let info = self.document_info.clone();
             self.write_new_object(|page_object_id, pdf| {
@@ -325,14 +1840,19 @@ impl Pdf {
                 for (key, value) in info {
                     writeln!(pdf.output, " /{} ({})", key, value)?;
                 }
-                if let Ok(now) =
-                    time::strftime("%Y%m%d%H%M%S%z", &time::now())
-                {
+                if let Some(time) = pdf.creation_time() {
+                    write!(
+                        pdf.output,
+                        " /CreationDate {now}\n \
+                         /ModDate {now}",
+                        now = date(&time),
+                    )?;
+                }
+                if let Some((level, _)) = pdf.pdfx {
                     write!(
                         pdf.output,
-                        " /CreationDate (D:{now})\n \
-                         /ModDate (D:{now})",
-                        now = now,
+                        "\n /GTS_PDFXVersion ({})\n /Trapped /False",
+                        level.version(),
                     )?;
                 }
                 writeln!(pdf.output, ">>")?;
@@ -342,7 +1862,18 @@ impl Pdf {
             None
         };
 
+        let fields = self.write_annotations()?;
         let outlines_id = self.write_outlines()?;
+        let struct_tree_id = self.write_struct_tree()?;
+        let layers = self.write_layers()?;
+        let (names, associated_files) = self.write_names()?;
+        let metadata_id = self.write_metadata()?;
+        let output_intents = self.write_output_intents()?;
+        let acroform_id = self.write_acroform(&fields)?;
+        let open_action = match self.open_action {
+            Some(ref destination) => Some(destination.to_pdf(&self.pages)?),
+            None => None,
+        };
 
         self.write_object_with_id(ROOT_OBJECT_ID, |pdf| {
             writeln!(
@@ -354,6 +1885,64 @@ impl Pdf {
             if let Some(outlines_id) = outlines_id {
                 writeln!(pdf.output, "/Outlines {} 0 R", outlines_id)?;
             }
+            if let Some(ref layers) = layers {
+                writeln!(pdf.output, "/OCProperties {}", layers)?;
+            }
+            if let Some(ref language) = pdf.language {
+                writeln!(pdf.output, "/Lang {}", ascii_string(language))?;
+            }
+            if let Some(id) = struct_tree_id {
+                writeln!(
+                    pdf.output,
+                    "/StructTreeRoot {} 0 R\n\
+                     /MarkInfo << /Marked true >>",
+                    id,
+                )?;
+            }
+            if !names.is_empty() {
+                writeln!(pdf.output, "/Names << {}>>", names)?;
+            }
+            if let Some(id) = acroform_id {
+                writeln!(pdf.output, "/AcroForm {} 0 R", id)?;
+            }
+            if pdf.page_mode != PageMode::UseNone {
+                writeln!(pdf.output, "/PageMode /{:?}", pdf.page_mode)?;
+            }
+            if let Some(ref destination) = open_action {
+                writeln!(pdf.output, "/OpenAction {}", destination)?;
+            }
+            if !pdf.page_labels.is_empty() {
+                write!(pdf.output, "/PageLabels << /Nums [")?;
+                // The labels must start at the first page.
+                if pdf.page_labels[0].0 != 0 {
+                    let decimal = PageLabels::new(PageNumbering::Decimal);
+                    write!(pdf.output, " 0 {}", decimal.to_pdf())?;
+                }
+                for &(first, ref labels) in &pdf.page_labels {
+                    write!(pdf.output, " {} {}", first, labels.to_pdf())?;
+                }
+                writeln!(pdf.output, " ] >>")?;
+            }
+            if let Some(id) = metadata_id {
+                writeln!(pdf.output, "/Metadata {} 0 R", id)?;
+            }
+            if !output_intents.is_empty() {
+                writeln!(
+                    pdf.output,
+                    "/OutputIntents [ {} ]",
+                    output_intents.join("\n   "),
+                )?;
+            }
+            if !associated_files.is_empty() {
+                write!(pdf.output, "/AF [")?;
+                for id in &associated_files {
+                    write!(pdf.output, " {} 0 R", id)?;
+                }
+                writeln!(pdf.output, " ]")?;
+            }
+            if let Some(preferences) = pdf.viewer_preferences.to_pdf() {
+                writeln!(pdf.output, "/ViewerPreferences {}", preferences)?;
+            }
             writeln!(pdf.output, ">>")?;
             Ok(())
         })?;
@@ -382,6 +1971,14 @@ impl Pdf {
         if let Some(id) = document_info_id {
             writeln!(self.output, "   /Info {} 0 R", id)?;
         }
+        // The first part of the /ID is permanent, the second part
+        // identifies this version of the document.
+        let version = hex_string(&self.output.md5.clone().finish());
+        let permanent = match self.document_id {
+            Some(ref id) => hex_string(id),
+            None => version.clone(),
+        };
+        writeln!(self.output, "   /ID [ {} {} ]", permanent, version)?;
         writeln!(
             self.output,
             ">>\n\
@@ -393,35 +1990,466 @@ impl Pdf {
         Ok(())
     }
 
+    /// Write the annotations of all pages, now that all pages that
+    /// they may refer to are known, and the form fields of any
+    /// widgets among them.
+    /// Return the fields, with their object ids.
+    fn write_annotations(
+        &mut self,
+    ) -> io::Result<Vec<(Arc<FormField>, usize)>> {
+        let pages = self.pages.clone();
+        let annotations = ::std::mem::take(&mut self.annotations);
+        // Each field is written after its widgets, with a reserved id
+        // for the widgets to refer to.
+        let mut fields: Vec<(Arc<FormField>, usize, Vec<usize>)> = Vec::new();
+        for pending in &annotations {
+            if let Some(field) = pending.annotation.field() {
+                match fields.iter_mut().find(|f| Arc::ptr_eq(&f.0, field)) {
+                    Some(f) => f.2.push(pending.object_id),
+                    None => {
+                        let field_id = self.reserve_object_id();
+                        fields.push((
+                            field.clone(),
+                            field_id,
+                            vec![pending.object_id],
+                        ));
+                    }
+                }
+            }
+        }
+        for pending in annotations {
+            if let Some(font) = pending.annotation.default_font() {
+                if !self.form_fonts.contains(&font) {
+                    self.form_fonts.push(font);
+                }
+            }
+            let mut refs = AnnotationRefs::default();
+            if let Some(form) = pending.annotation.appearance() {
+                refs.appearance = Some(form.write_object(self)?);
+            }
+            for (state, form) in pending.annotation.appearance_states() {
+                let id = form.write_object(self)?;
+                refs.appearance_states.push((state.clone(), id));
+            }
+            if let Some(file) = pending.annotation.file() {
+                refs.file = Some(file.write_object(self)?);
+            }
+            refs.popup = pending.popup_id;
+            if let Some(field) = pending.annotation.field() {
+                refs.parent = fields
+                    .iter()
+                    .find(|f| Arc::ptr_eq(&f.0, field))
+                    .map(|f| f.1);
+            }
+            self.write_object_with_id(pending.object_id, |pdf| {
+                pending.annotation.write_dictionary(
+                    &mut pdf.output,
+                    pending.page_id,
+                    &refs,
+                    &pages,
+                )
+            })?;
+            if let Some(popup_id) = pending.popup_id {
+                self.write_object_with_id(popup_id, |pdf| {
+                    pending.annotation.write_popup(
+                        &mut pdf.output,
+                        pending.page_id,
+                        pending.object_id,
+                    )
+                })?;
+            }
+        }
+        for (field, field_id, kids) in &fields {
+            self.write_object_with_id(*field_id, |pdf| {
+                field.write_dictionary(&mut pdf.output, kids)
+            })?;
+        }
+        Ok(fields.into_iter().map(|f| (f.0, f.1)).collect())
+    }
+
+    /// Write the interactive form dictionary, with the form fields
+    /// `fields` and the fonts used in default appearances of
+    /// annotations, if there are any.
+    fn write_acroform(
+        &mut self,
+        fields: &[(Arc<FormField>, usize)],
+    ) -> io::Result<Option<usize>> {
+        if self.form_fonts.is_empty() && fields.is_empty() {
+            return Ok(None);
+        }
+        let mut font_oids = NamedRefs::new();
+        for font in self.form_fonts.clone() {
+            let object_id = match self.all_font_object_ids.get(&font) {
+                Some(&object_id) => object_id,
+                None => {
+                    let object_id = font.write_object(self)?;
+                    self.all_font_object_ids.insert(font, object_id);
+                    object_id
+                }
+            };
+            font_oids.insert(format!("/{}", font.pdf_name()), object_id);
+        }
+        let fields = fields
+            .iter()
+            .map(|f| format!("{} 0 R ", f.1))
+            .collect::<String>();
+        self.write_new_object(|object_id, pdf| {
+            write!(pdf.output, "<< /Fields [ {}]", fields)?;
+            if !font_oids.is_empty() {
+                write!(
+                    pdf.output,
+                    "\n   /DR << /Font << {}>> >>",
+                    font_oids
+                )?;
+            }
+            writeln!(pdf.output, "\n>>")?;
+            Ok(Some(object_id))
+        })
+    }
+
+    /// Write the name trees of the document, and return the entries
+    /// for the /Names dictionary of the catalog, and the ids of the
+    /// files associated with the document.
+    fn write_names(&mut self) -> io::Result<(NamedRefs, Vec<usize>)> {
+        let mut names = NamedRefs::new();
+        let mut associated_files = Vec::new();
+        if !self.named_destinations.is_empty() {
+            let mut entries = Vec::new();
+            for (name, destination) in &self.named_destinations {
+                entries
+                    .push((name.clone(), destination.to_pdf(&self.pages)?));
+            }
+            names
+                .insert("/Dests".to_string(), self.write_name_tree(entries)?);
+        }
+        if !self.javascripts.is_empty() {
+            let mut entries = Vec::new();
+            for (name, script) in &self.javascripts {
+                let action = Action::JavaScript(script.clone());
+                entries.push((name.clone(), action.to_pdf(&self.pages)?));
+            }
+            names.insert(
+                "/JavaScript".to_string(),
+                self.write_name_tree(entries)?,
+            );
+        }
+        if !self.embedded_files.is_empty() {
+            let mut entries = Vec::new();
+            for (name, file) in ::std::mem::take(&mut self.embedded_files) {
+                let id = file.write_object(self)?;
+                if file.relationship().is_some() {
+                    associated_files.push(id);
+                }
+                entries.push((name, format!("{} 0 R", id)));
+            }
+            names.insert(
+                "/EmbeddedFiles".to_string(),
+                self.write_name_tree(entries)?,
+            );
+        }
+        Ok((names, associated_files))
+    }
+
+    /// Check what a PDF/X document needs, that isn't checked for each
+    /// page.
+    fn check_pdfx(
+        &self,
+        level: PdfX,
+        intent: &OutputIntent,
+    ) -> io::Result<()> {
+        let problem = if !self.document_info.contains_key("Title") {
+            Some("must have a title")
+        } else if self.deterministic {
+            Some("must have creation and modification dates")
+        } else if level == PdfX::X4 && intent.profile().is_none() {
+            Some("must have an output intent with a profile")
+        } else {
+            None
+        };
+        match problem {
+            Some(problem) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} documents {}", level.version(), problem),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the layers of the document, if any.
+    /// Return the optional content properties dictionary.
+    fn write_layers(&mut self) -> io::Result<Option<String>> {
+        if self.layers.is_empty() {
+            return Ok(None);
+        }
+        let layers = self.layers.clone();
+        for &(layer, ref name, _) in &layers {
+            self.write_object_with_id(layer.object_id(), |pdf| {
+                writeln!(
+                    pdf.output,
+                    "<< /Type /OCG /Name {} >>",
+                    pdfstring::text_string(name),
+                )
+            })?;
+        }
+        let all = layer::refs_pdf(layers.iter().map(|entry| entry.0));
+        let mut config = String::new();
+        let order = match self.layer_order {
+            Some(ref order) => layer::order_pdf(order),
+            None => all.clone(),
+        };
+        config.push_str(&format!("/Order [{} ]", order));
+        let hidden = layers.iter().filter(|entry| !entry.2);
+        let hidden = layer::refs_pdf(hidden.map(|entry| entry.0));
+        if !hidden.is_empty() {
+            config.push_str(&format!(" /OFF [{} ]", hidden));
+        }
+        if !self.layer_radio_groups.is_empty() {
+            config.push_str(" /RBGroups [");
+            for group in &self.layer_radio_groups {
+                let group = layer::refs_pdf(group.iter().cloned());
+                config.push_str(&format!(" [{} ]", group));
+            }
+            config.push_str(" ]");
+        }
+        Ok(Some(format!(
+            "<< /OCGs [{} ]\n   /D << {} >> >>",
+            all, config
+        )))
+    }
+
+    /// Write the logical structure of the document, if any content is
+    /// tagged.  Return the object id of the structure tree root.
+    fn write_struct_tree(&mut self) -> io::Result<Option<usize>> {
+        if self.struct_tree.is_empty() {
+            return Ok(None);
+        }
+        let root_id = self.reserve_object_id();
+        let document_id = self.reserve_object_id();
+        let elements = self.struct_tree.elements.clone();
+        let ids = elements
+            .iter()
+            .map(|_| self.reserve_object_id())
+            .collect::<Vec<_>>();
+        let mut kids = vec![Vec::new(); elements.len()];
+        let mut top = Vec::new();
+        for (i, (_, element)) in elements.iter().enumerate() {
+            match element.parent {
+                Some(parent) => kids[parent].push(ids[i]),
+                None => top.push(i),
+            }
+        }
+        // The elements of each page follow each other in the order of
+        // the pages (the sort is stable, so elements of the same page
+        // keep their order).
+        let positions = self
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| (page.object_id, i))
+            .collect::<HashMap<_, _>>();
+        top.sort_by_key(|&i| positions[&elements[i].0]);
+        for (i, &(page_id, ref element)) in elements.iter().enumerate() {
+            let parent = element.parent.map_or(document_id, |p| ids[p]);
+            let dict = element.to_pdf(parent, page_id, &kids[i]);
+            self.write_object_with_id(ids[i], |pdf| {
+                writeln!(pdf.output, "{}", dict)
+            })?;
+        }
+        self.write_object_with_id(document_id, |pdf| {
+            write!(
+                pdf.output,
+                "<< /Type /StructElem /S /Document /P {} 0 R /K [",
+                root_id,
+            )?;
+            for &i in &top {
+                write!(pdf.output, " {} 0 R", ids[i])?;
+            }
+            writeln!(pdf.output, " ] >>")
+        })?;
+        // The parent tree maps the marked-content ids of each page to
+        // the elements they belong to.
+        let pages = self.struct_tree.pages.clone();
+        self.write_object_with_id(root_id, |pdf| {
+            write!(
+                pdf.output,
+                "<< /Type /StructTreeRoot /K {} 0 R\n   \
+                 /ParentTree << /Nums [",
+                document_id,
+            )?;
+            for (key, &page_id) in pages.iter().enumerate() {
+                if !positions.contains_key(&page_id) {
+                    continue;
+                }
+                let mut content = elements
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &(page, _))| page == page_id)
+                    .filter_map(|(i, (_, e))| e.mcid.map(|m| (m, i)))
+                    .collect::<Vec<_>>();
+                content.sort();
+                write!(pdf.output, "\n     {} [", key)?;
+                for (_, i) in content {
+                    write!(pdf.output, " {} 0 R", ids[i])?;
+                }
+                write!(pdf.output, " ]")?;
+            }
+            writeln!(
+                pdf.output,
+                " ] >>\n   \
+                 /ParentTreeNextKey {} >>",
+                pages.len(),
+            )
+        })?;
+        Ok(Some(root_id))
+    }
+
+    /// Write the XMP metadata of a PDF/A or PDF/X document.
+    /// Return its object id, or None for other documents.
+    fn write_metadata(&mut self) -> io::Result<Option<usize>> {
+        let mut identification = String::new();
+        if let Some(level) = self.pdfa {
+            if !self.javascripts.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "JavaScript is not allowed in PDF/A documents",
+                ));
+            }
+            identification.push_str(&level.identification());
+        }
+        if let Some((level, _)) = self.pdfx {
+            identification.push_str(&level.identification());
+        }
+        if identification.is_empty() {
+            return Ok(None);
+        }
+        let xmp = pdfa::xmp_metadata(
+            &identification,
+            &self.document_info,
+            self.creation_time().as_ref(),
+            &self.xmp_extra,
+        );
+        self.write_new_object(|object_id, pdf| {
+            writeln!(
+                pdf.output,
+                "<< /Type /Metadata /Subtype /XML /Length {} >>\n\
+                 stream",
+                xmp.len(),
+            )?;
+            writeln!(pdf.output, "{}\nendstream", xmp)?;
+            Ok(Some(object_id))
+        })
+    }
+
+    /// Write the profiles of the output intents of a PDF/X or PDF/A
+    /// document.
+    /// Return the output intent dictionaries.
+    ///
+    /// A PDF/A document that is also a PDF/X document with a profile
+    /// uses the same output intent for both.
+    fn write_output_intents(&mut self) -> io::Result<Vec<String>> {
+        let mut intents = Vec::new();
+        let mut shared = None;
+        if let Some((_, intent)) = self.pdfx.clone() {
+            let profile_id = self.write_icc_profile(intent.profile())?;
+            intents.push(intent.to_pdf("GTS_PDFX", profile_id));
+            if profile_id.is_some() {
+                shared = Some((intent, profile_id));
+            }
+        }
+        if self.pdfa.is_some() {
+            let (intent, profile_id) = match shared {
+                Some(shared) => shared,
+                None => {
+                    let intent = OutputIntent::srgb();
+                    let profile_id =
+                        self.write_icc_profile(intent.profile())?;
+                    (intent, profile_id)
+                }
+            };
+            intents.push(intent.to_pdf("GTS_PDFA1", profile_id));
+        }
+        Ok(intents)
+    }
+
+    /// Write an ICC profile, if any, and return its object id.
+    fn write_icc_profile(
+        &mut self,
+        profile: Option<&[u8]>,
+    ) -> io::Result<Option<usize>> {
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return Ok(None),
+        };
+        self.write_new_object(|object_id, pdf| {
+            writeln!(
+                pdf.output,
+                "<< /N {} /Length {} >>\n\
+                 stream",
+                pdfx::profile_components(profile),
+                profile.len(),
+            )?;
+            pdf.output.write_all(profile)?;
+            writeln!(pdf.output, "\nendstream")?;
+            Ok(Some(object_id))
+        })
+    }
+
+    /// Write a name tree, as described in section 7.9.6 of the PDF
+    /// specification, as a single node with all `entries`, which must
+    /// be sorted by name.
+    fn write_name_tree(
+        &mut self,
+        entries: Vec<(String, String)>,
+    ) -> io::Result<usize> {
+        self.write_new_object(|object_id, pdf| {
+            writeln!(pdf.output, "<< /Names [")?;
+            for (name, value) in entries {
+                writeln!(pdf.output, "  {} {}", ascii_string(&name), value)?;
+            }
+            writeln!(pdf.output, "] >>")?;
+            Ok(object_id)
+        })
+    }
+
     fn write_outlines(&mut self) -> io::Result<Option<usize>> {
         if self.outline_items.is_empty() {
             return Ok(None);
         }
 
-        let parent_id = self.object_offsets.len();
-        self.object_offsets.push(-1);
-        let count = self.outline_items.len();
-        let mut first_id = 0;
-        let mut last_id = 0;
+        let parent_id = self.reserve_object_id();
         let items = self.outline_items.clone();
+        let ids = items
+            .iter()
+            .map(|_| self.reserve_object_id())
+            .collect::<Vec<_>>();
+        let tree = OutlineTree::new(&items);
         for (i, item) in items.iter().enumerate() {
-            let (is_first, is_last) = (i == 0, i == count - 1);
-            let id = self.write_new_object(|object_id, pdf| {
+            let siblings = tree.siblings(i);
+            let position = siblings.iter().position(|&s| s == i).unwrap();
+            let prev = position.checked_sub(1).map(|p| ids[siblings[p]]);
+            let next = siblings.get(position + 1).map(|&n| ids[n]);
+            let children = &tree.children[i];
+            let children = match (children.first(), children.last()) {
+                (Some(&first), Some(&last)) => {
+                    Some((ids[first], ids[last], tree.counts[i]))
+                }
+                _ => None,
+            };
+            let parent = tree.parents[i].map_or(parent_id, |p| ids[p]);
+            self.write_object_with_id(ids[i], |pdf| {
                 item.write_dictionary(
                     &mut pdf.output,
-                    parent_id,
-                    if is_first { None } else { Some(object_id - 1) },
-                    if is_last { None } else { Some(object_id + 1) },
+                    &pdf.pages,
+                    parent,
+                    prev,
+                    next,
+                    children,
                 )
-                .and(Ok(object_id))
             })?;
-            if is_first {
-                first_id = id;
-            }
-            if is_last {
-                last_id = id;
-            }
         }
+        let first_id = ids[tree.top[0]];
+        let last_id = ids[*tree.top.last().unwrap()];
+        let count = items.len();
         self.write_object_with_id(parent_id, |pdf| {
             writeln!(
                 pdf.output,
@@ -439,19 +2467,100 @@ impl Pdf {
     }
 }
 
+/// A pdf string of `data` in hexadecimal form.
+fn hex_string(data: &[u8]) -> String {
+    let hex = data
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<String>();
+    format!("<{}>", hex)
+}
+
+fn page_index_error(index: usize, count: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("No page {}, the document has {} pages", index, count),
+    )
+}
+
+/// Check that `resources` only use what PDF/X-1a allows: no RGB
+/// colors and no transparency.
+fn check_pdfx1a(resources: &Resources) -> io::Result<()> {
+    let problem = if resources.uses_rgb
+        || resources.images.iter().any(|(_, image)| image.is_rgb())
+        || resources.shadings.iter().any(|(_, s)| !s.is_cmyk())
+        || resources.patterns.iter().any(|(_, p)| match *p {
+            PatternResource::Shading(ref s) => !s.is_cmyk(),
+            PatternResource::Tiling(_) => false,
+        }) {
+        Some("RGB colors are")
+    } else if resources.ext_gstates.keys().any(ExtGState::is_transparent)
+        || resources.images.iter().any(|(_, i)| i.has_soft_mask())
+        || resources.forms.iter().any(FormXObject::is_group)
+    {
+        Some("Transparency is")
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} not allowed in PDF/X-1a documents", problem),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The resource dictionary of a content stream, with the object ids
+/// of the resources.
+struct ResourceRefs {
+    entries: Vec<(&'static str, NamedRefs)>,
+}
+
+impl ResourceRefs {
+    /// Add a resource of type `key`, such as XObject.
+    fn insert(&mut self, key: &'static str, name: String, oid: usize) {
+        match self.entries.iter_mut().find(|entry| entry.0 == key) {
+            Some(entry) => {
+                entry.1.insert(name, oid);
+            }
+            None => {
+                let mut refs = NamedRefs::new();
+                refs.insert(name, oid);
+                self.entries.push((key, refs));
+            }
+        }
+    }
+}
+
+impl fmt::Display for ResourceRefs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<< ")?;
+        for (key, refs) in &self.entries {
+            if !refs.is_empty() {
+                write!(f, "/{} << {}>> ", key, refs)?;
+            }
+        }
+        write!(f, ">>")
+    }
+}
+
 struct NamedRefs {
-    oids: HashMap<FontRef, usize>,
+    oids: BTreeMap<String, usize>,
 }
 
 impl NamedRefs {
     fn new() -> Self {
         NamedRefs {
-            oids: HashMap::new(),
+            oids: BTreeMap::new(),
         }
     }
-    fn insert(&mut self, name: FontRef, oid: usize) -> Option<usize> {
+    fn insert(&mut self, name: String, oid: usize) -> Option<usize> {
         self.oids.insert(name, oid)
     }
+    fn is_empty(&self) -> bool {
+        self.oids.is_empty()
+    }
 }
 
 impl fmt::Display for NamedRefs {
@@ -462,3 +2571,304 @@ impl fmt::Display for NamedRefs {
         Ok(())
     }
 }
+
+#[test]
+fn test_reorder_pages() {
+    let filename = "test_reorder_pages.pdf";
+    let mut document = Pdf::create(filename).unwrap();
+    document.set_page_labels(PageLabels::new(PageNumbering::LowerRoman));
+    document.render_page(100.0, 100.0, |_| Ok(())).unwrap();
+    document.set_page_labels(PageLabels::new(PageNumbering::Decimal));
+    for title in &["one", "two", "three"] {
+        document
+            .render_page(100.0, 100.0, |c| {
+                c.add_outline(title);
+                Ok(())
+            })
+            .unwrap();
+    }
+    let ids = document
+        .pages
+        .iter()
+        .map(|p| p.object_id)
+        .collect::<Vec<_>>();
+    document.move_page(3, 1).unwrap();
+    document.delete_page(2).unwrap();
+    let reordered = document.pages.iter().map(|p| p.object_id);
+    assert_eq!(vec![ids[0], ids[3], ids[2]], reordered.collect::<Vec<_>>());
+    let pages = document.outline_items.iter().map(|i| i.page().unwrap());
+    assert_eq!(vec![1, 2], pages.collect::<Vec<_>>());
+    let firsts = document.page_labels.iter().map(|l| l.0);
+    assert_eq!(vec![0, 1], firsts.collect::<Vec<_>>());
+    assert!(document.move_page(3, 0).is_err());
+    document.finish().unwrap();
+    ::std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn test_page_tree() {
+    let filename = "test_page_tree.pdf";
+    let pages = PAGE_TREE_FANOUT * PAGE_TREE_FANOUT + 1;
+    let mut document = Pdf::create(filename).unwrap();
+    for _ in 0..pages {
+        document.render_page(100.0, 100.0, |_| Ok(())).unwrap();
+    }
+    document.finish().unwrap();
+    let output = ::std::fs::read(filename).unwrap();
+    ::std::fs::remove_file(filename).unwrap();
+    let output = String::from_utf8_lossy(&output);
+    // The dictionaries of the objects, by object id.
+    let mut objects = HashMap::new();
+    for (start, _) in output.match_indices(" 0 obj\n") {
+        let id = output[..start].rsplit('\n').next().unwrap();
+        let body = &output[start..];
+        let body = &body[..body.find("endobj").unwrap()];
+        objects.insert(id.parse::<usize>().unwrap(), body);
+    }
+    let reference = |body: &str, key: &str| -> Option<usize> {
+        let value = &body[body.find(key)? + key.len()..];
+        value.split_whitespace().next().unwrap().parse().ok()
+    };
+    // Check the node `id` with parent `parent`, return its page count.
+    fn check(
+        objects: &HashMap<usize, &str>,
+        reference: &dyn Fn(&str, &str) -> Option<usize>,
+        id: usize,
+        parent: Option<usize>,
+    ) -> usize {
+        let body = objects[&id];
+        assert_eq!(parent, reference(body, "/Parent "));
+        if body.contains("/Type /Page\n") {
+            return 1;
+        }
+        let kids = &body[body.find("/Kids [").unwrap() + 7..];
+        let kids = kids[..kids.find(']').unwrap()]
+            .split(" 0 R")
+            .map(str::trim)
+            .filter(|kid| !kid.is_empty())
+            .map(|kid| kid.parse().unwrap())
+            .collect::<Vec<usize>>();
+        assert!(kids.len() <= PAGE_TREE_FANOUT);
+        let count = kids
+            .iter()
+            .map(|&kid| check(objects, reference, kid, Some(id)))
+            .sum();
+        assert_eq!(Some(count), reference(body, "/Count "));
+        count
+    }
+    assert_eq!(pages, check(&objects, &reference, PAGES_OBJECT_ID, None));
+    // The root has two nodes of 32 * 32 and 1 pages, the first with
+    // 32 nodes below it.
+    let root = objects[&PAGES_OBJECT_ID];
+    assert_eq!(2, root.matches(" 0 R").count());
+}
+
+#[test]
+fn test_delete_annotated_page() {
+    let filename = "test_delete_annotated_page.pdf";
+    let mut document = Pdf::create(filename).unwrap();
+    document
+        .render_page(100.0, 100.0, |c| {
+            c.link(Rect::new(10.0, 10.0, 20.0, 20.0), Destination::page(1));
+            Ok(())
+        })
+        .unwrap();
+    document.render_page(100.0, 100.0, |_| Ok(())).unwrap();
+    document.delete_page(0).unwrap();
+    assert!(document.annotations.is_empty());
+    document.finish().unwrap();
+    let output = ::std::fs::read(filename).unwrap();
+    ::std::fs::remove_file(filename).unwrap();
+    let output = String::from_utf8_lossy(&output);
+    assert!(!output.contains("/Annot"));
+}
+
+#[test]
+fn test_deterministic() {
+    use graphicsstate::Color;
+    let render = |filename: &str| {
+        let mut document = Pdf::create(filename).unwrap();
+        document.set_deterministic(true);
+        document.set_title("Same");
+        document
+            .render_page(100.0, 100.0, |c| {
+                for &font in &[
+                    BuiltinFont::Times_Roman,
+                    BuiltinFont::Helvetica,
+                    BuiltinFont::Courier,
+                    BuiltinFont::Symbol,
+                ] {
+                    c.left_text(10.0, 10.0, font, 12.0, "text")?;
+                }
+                c.set_fill_alpha(0.5)?;
+                c.set_stroke_alpha(0.2)?;
+                c.set_fill_color(Color::rgb(1, 2, 3))?;
+                c.rectangle(10.0, 10.0, 10.0, 10.0)?;
+                c.fill()
+            })
+            .unwrap();
+        document.finish().unwrap();
+        let output = ::std::fs::read(filename).unwrap();
+        ::std::fs::remove_file(filename).unwrap();
+        output
+    };
+    let first = render("test_deterministic_1.pdf");
+    assert!(first == render("test_deterministic_2.pdf"));
+}
+
+#[test]
+fn test_pdfx1a() {
+    use graphicsstate::{Color, Matrix};
+    let write = |filename: &str, color: Color| -> io::Result<Vec<u8>> {
+        let mut document = Pdf::create(filename)?;
+        document.set_pdfx(
+            PdfX::X1a,
+            OutputIntent::registered("FOGRA39", "Coated FOGRA39"),
+        );
+        document.set_title("Flyer");
+        document.set_page_boxes(PageBoxes {
+            trim: Some(Margins::uniform(9.0)),
+            ..PageBoxes::default()
+        });
+        document.set_bates_numbering(BatesNumbering::new("X"));
+        document.define_xobject("box", 10.0, 10.0, |c| {
+            c.set_fill_color(Color::gray(0))?;
+            c.rectangle(0.0, 0.0, 10.0, 10.0)?;
+            c.fill()
+        })?;
+        document.render_page(100.0, 100.0, |c| {
+            c.set_fill_color(color)?;
+            c.rectangle(10.0, 10.0, 10.0, 10.0)?;
+            c.fill()?;
+            c.draw_xobject("box", Matrix::identity())
+        })?;
+        document.finish()?;
+        ::std::fs::read(filename)
+    };
+    let render = |filename: &str, color: Color| {
+        let output = write(filename, color);
+        ::std::fs::remove_file(filename).unwrap();
+        output
+    };
+    let cmyk = render("test_pdfx1a_cmyk.pdf", Color::cmyk(0, 0, 255, 0));
+    let cmyk = cmyk.unwrap();
+    // The document id is the hash of what precedes it in the file.
+    let id_start = cmyk.windows(8).position(|w| w == b"   /ID [").unwrap();
+    let mut md5 = Md5::new();
+    md5.update(&cmyk[..id_start]);
+    let id = hex_string(&md5.finish());
+    let cmyk = String::from_utf8_lossy(&cmyk).into_owned();
+    assert!(cmyk.contains(&format!("/ID [ {} {} ]", id, id)));
+    assert!(cmyk.starts_with("%PDF-1.4\n"));
+    assert!(cmyk.contains("/GTS_PDFXVersion (PDF/X-1a:2003)"));
+    assert!(cmyk.contains("/S /GTS_PDFX"));
+    assert!(cmyk.contains("/Stamp0 Do"));
+    assert!(cmyk.contains("/Subtype /Form"));
+    assert!(!cmyk.contains("DeviceRGB"));
+    let rgb = render("test_pdfx1a_rgb.pdf", Color::rgb(255, 255, 0));
+    assert!(rgb.is_err());
+}
+
+#[test]
+fn test_table_of_contents() {
+    let filename = "test_table_of_contents.pdf";
+    let mut document = Pdf::create(filename).unwrap();
+    for i in 0..10 {
+        document
+            .render_page(100.0, 100.0, |c| {
+                c.add_outline(&format!("Chapter {}", i));
+                Ok(())
+            })
+            .unwrap();
+    }
+    // Three entries fit below the heading, six on the other pages.
+    let contents = TableOfContents::new("Contents");
+    let count = document
+        .insert_table_of_contents(1, 100.0, 100.0, &contents)
+        .unwrap();
+    assert_eq!(3, count);
+    assert_eq!(13, document.pages.len());
+    let pages = document
+        .outline_items
+        .iter()
+        .map(|item| item.page().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![0, 4, 5, 6, 7, 8, 9, 10, 11, 12], pages);
+    assert!(document
+        .insert_table_of_contents(14, 100.0, 100.0, &contents)
+        .is_err());
+    document.finish().unwrap();
+    ::std::fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn test_watermark() {
+    let render = |filename: &str, over: bool| {
+        let mut document = Pdf::create(filename).unwrap();
+        let watermark = |c: &mut Canvas| {
+            c.set_fill_alpha(0.5)?;
+            c.rectangle(10.0, 10.0, 20.0, 20.0)?;
+            c.fill()
+        };
+        if over {
+            document.set_watermark(watermark);
+        } else {
+            document.set_watermark_below(watermark);
+        }
+        for &width in &[100.0, 200.0, 100.0] {
+            document
+                .render_page(width, 100.0, |c| {
+                    c.rectangle(0.0, 0.0, 5.0, 5.0)?;
+                    c.fill()
+                })
+                .unwrap();
+        }
+        document.finish().unwrap();
+        let output = ::std::fs::read(filename).unwrap();
+        ::std::fs::remove_file(filename).unwrap();
+        String::from_utf8_lossy(&output).into_owned()
+    };
+    // The content streams of each page, in order.
+    let contents = |output: &str| {
+        let stream = |id: &str| {
+            let start = output.find(&format!("\n{} 0 obj\n", id)).unwrap();
+            let body = &output[start..];
+            let body = &body[body.find("stream\n").unwrap() + 7..];
+            body[..body.find("endstream").unwrap()].to_string()
+        };
+        output
+            .match_indices("/Contents [")
+            .map(|(start, _)| {
+                let refs = &output[start + 11..];
+                refs[..refs.find(']').unwrap()]
+                    .split(" 0 R")
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty())
+                    .map(stream)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let page = "0 0 5 5 re\nf\n";
+    let stamp = "/Artifact BMC\nq /Stamp0 Do Q\nEMC\n";
+
+    let output = render("test_watermark_over.pdf", true);
+    // One form for each page size.
+    assert_eq!(2, output.matches("/Subtype /Form").count());
+    let pages = contents(&output);
+    assert_eq!(3, pages.len());
+    for streams in pages {
+        assert_eq!(3, streams.len());
+        assert_eq!("q\n", streams[0]);
+        assert!(streams[1].ends_with(page));
+        assert_eq!(format!("Q\n{}", stamp), streams[2]);
+    }
+
+    let output = render("test_watermark_below.pdf", false);
+    assert_eq!(2, output.matches("/Subtype /Form").count());
+    for streams in contents(&output) {
+        assert_eq!(2, streams.len());
+        assert_eq!(stamp, streams[0]);
+        assert!(streams[1].ends_with(page));
+    }
+}
@@ -0,0 +1,65 @@
+use pdfstring::text_string;
+
+/// The scale of a drawing, for measuring distances and areas on it in
+/// viewers that support it, as described in section 12.9 of the PDF
+/// specification.
+///
+/// Used by
+/// [Annotation::distance](struct.Annotation.html#method.distance)
+/// and [Annotation::area](struct.Annotation.html#method.area).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeasureScale {
+    label: String,
+    unit: String,
+    per_point: f32,
+}
+
+impl MeasureScale {
+    /// Create a scale where one point on the page is `per_point`
+    /// `unit`s in reality, shown as `label`.
+    pub fn new(label: &str, unit: &str, per_point: f32) -> Self {
+        MeasureScale {
+            label: label.to_string(),
+            unit: unit.to_string(),
+            per_point,
+        }
+    }
+
+    /// Create the scale 1:`ratio`, measuring in meters, e.g. 50 for a
+    /// drawing where 1 cm on paper is 50 cm in reality.
+    pub fn metric(ratio: f32) -> Self {
+        // A point is 1/72 inch, or 0.3528 mm.
+        let meters_per_point = 0.0254 / 72.0;
+        MeasureScale::new(
+            &format!("1:{}", ratio),
+            "m",
+            meters_per_point * ratio,
+        )
+    }
+
+    /// The scale as a pdf measure dictionary.
+    pub(crate) fn to_pdf(&self) -> String {
+        let format = |unit: &str, factor: f32| {
+            format!(
+                "[ << /Type /NumberFormat /U {} /C {} /D 100 >> ]",
+                text_string(unit),
+                factor,
+            )
+        };
+        format!(
+            "<< /Type /Measure /Subtype /RL /R {}\n   \
+             /X {}\n   /D {}\n   /A {} >>",
+            text_string(&self.label),
+            format(&self.unit, self.per_point),
+            format(&self.unit, 1.0),
+            format(&format!("sq {}", self.unit), 1.0),
+        )
+    }
+}
+
+#[test]
+fn test_metric_scale() {
+    let scale = MeasureScale::metric(72.0);
+    assert_eq!("1:72", scale.label);
+    assert!((scale.per_point - 0.0254).abs() < 1e-6);
+}
@@ -1,39 +1,128 @@
+use action::{Destination, Zoom};
 pub use encoding::WIN_ANSI_ENCODING;
+use graphicsstate::Color;
 use std::io::{self, Write};
+use PageInfo;
 
 /// An item in the document outline.
 ///
 /// An OutlineItem associates a name (contained in an ordered tree)
-/// with a location in the document.  The PDF standard supports
-/// several ways to specify an exact location on a page, but this
-/// implementation currently only supports linking to a specific page.
+/// with a location in the document, on the page it is added to with
+/// [Canvas::add_outline_item](struct.Canvas.html#method.add_outline_item).
+/// By default, the item goes to the top of the page, as added by
+/// [Canvas::add_outline](struct.Canvas.html#method.add_outline).
 ///
-/// To actually create an OutlineItem in a meaningful way, please
-/// use `Canvas::add_outline`.
-#[derive(Clone)]
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{OutlineItem, Pdf, Zoom};
+/// # use pdf_canvas::graphicsstate::Color;
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// canvas.add_outline("Chapter 1");
+/// // Go to the heading of a section, halfway down the page, and fit
+/// // the width of the page in the window.
+/// let section = OutlineItem::new("Section 1.1")
+///     .with_level(1)
+///     .with_zoom(Zoom::FitWidth(Some(130.0)));
+/// canvas.add_outline_item(section);
+/// // An important item, in bold red.
+/// let errata = OutlineItem::new("Errata")
+///     .with_bold()
+///     .with_color(Color::rgb(192, 0, 0));
+/// canvas.add_outline_item(errata);
+/// # Ok(())
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug)]
 pub struct OutlineItem {
     title: String,
-    page_id: Option<usize>,
+    level: usize,
+    zoom: Zoom,
+    page: Option<usize>,
+    bold: bool,
+    italic: bool,
+    color: Option<Color>,
 }
 
 impl OutlineItem {
+    /// Create a top level outline item.
     pub fn new(title: &str) -> OutlineItem {
         OutlineItem {
             title: title.to_string(),
-            page_id: None,
+            level: 0,
+            zoom: Zoom::XYZ(None, None, None),
+            page: None,
+            bold: false,
+            italic: false,
+            color: None,
         }
     }
 
-    pub fn set_page(&mut self, page_id: usize) {
-        self.page_id = Some(page_id)
+    /// Set the nesting level of the item, see
+    /// [Canvas::add_outline_level](struct.Canvas.html#method.add_outline_level).
+    pub fn with_level(mut self, level: usize) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set how to show the page when the item is clicked, e.g. to go
+    /// to the position of a heading on the page.
+    pub fn with_zoom(mut self, zoom: Zoom) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Show the title of the item in bold, in viewers that support it.
+    pub fn with_bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Show the title of the item in italic, in viewers that support
+    /// it.
+    pub fn with_italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Set the color of the title of the item, in viewers that support
+    /// it.
+    ///
+    /// Outline colors are always RGB, so gray and CMYK colors are
+    /// converted to RGB.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(crate) fn level(&self) -> usize {
+        self.level
+    }
+
+    /// The index of the page the item goes to.
+    pub(crate) fn page(&self) -> Option<usize> {
+        self.page
     }
 
-    pub fn write_dictionary(
+    /// Set the index of the page the item goes to.
+    pub(crate) fn set_page(&mut self, page: usize) {
+        self.page = Some(page)
+    }
+
+    pub(crate) fn write_dictionary(
         &self,
         output: &mut Write,
+        pages: &[PageInfo],
         parent_id: usize,
         prev: Option<usize>,
         next: Option<usize>,
+        children: Option<(usize, usize, usize)>,
     ) -> io::Result<()> {
         output.write_all(b"<< /Title (")?;
         output.write_all(&WIN_ANSI_ENCODING.encode_string(&self.title))?;
@@ -45,9 +134,140 @@ impl OutlineItem {
         if let Some(id) = next {
             writeln!(output, "/Next {} 0 R", id)?;
         }
-        if let Some(id) = self.page_id {
-            writeln!(output, "/Dest [{} 0 R /XYZ null null null]", id)?;
+        if let Some((first, last, count)) = children {
+            writeln!(output, "/First {} 0 R /Last {} 0 R", first, last)?;
+            writeln!(output, "/Count {}", count)?;
+        }
+        let flags =
+            if self.italic { 1 } else { 0 } | if self.bold { 2 } else { 0 };
+        if flags != 0 {
+            writeln!(output, "/F {}", flags)?;
+        }
+        if let Some(color) = self.color {
+            let (r, g, b) = rgb_components(color);
+            writeln!(output, "/C [{} {} {}]", r, g, b)?;
+        }
+        if let Some(page) = self.page {
+            let destination = Destination::Page(page, self.zoom);
+            writeln!(output, "/Dest {}", destination.to_pdf(pages)?)?;
         }
         writeln!(output, ">>")
     }
 }
+
+/// The RGB components of `color`, from 0 to 1.
+fn rgb_components(color: Color) -> (f32, f32, f32) {
+    let (red, green, blue) = match color {
+        Color::RGB { red, green, blue } => {
+            (f32::from(red), f32::from(green), f32::from(blue))
+        }
+        Color::Gray { gray } => {
+            let gray = f32::from(gray);
+            (gray, gray, gray)
+        }
+        Color::CMYK {
+            cyan,
+            magenta,
+            yellow,
+            black,
+        } => {
+            let white = 1.0 - f32::from(black) / 255.0;
+            (
+                (255.0 - f32::from(cyan)) * white,
+                (255.0 - f32::from(magenta)) * white,
+                (255.0 - f32::from(yellow)) * white,
+            )
+        }
+    };
+    (red / 255.0, green / 255.0, blue / 255.0)
+}
+
+/// The tree structure of a list of outline items, where each item is
+/// a child of the last item before it with a lower level.
+pub(crate) struct OutlineTree {
+    /// The parent of each item, or None for top level items.
+    pub parents: Vec<Option<usize>>,
+    /// The children of each item.
+    pub children: Vec<Vec<usize>>,
+    /// The top level items.
+    pub top: Vec<usize>,
+    /// The number of descendants of each item.
+    pub counts: Vec<usize>,
+}
+
+impl OutlineTree {
+    pub fn new(items: &[OutlineItem]) -> Self {
+        let mut parents = Vec::new();
+        let mut children = vec![Vec::new(); items.len()];
+        let mut top = Vec::new();
+        let mut ancestors: Vec<usize> = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            while ancestors
+                .last()
+                .map_or(false, |&a| items[a].level >= item.level)
+            {
+                ancestors.pop();
+            }
+            let parent = ancestors.last().cloned();
+            match parent {
+                Some(p) => children[p].push(i),
+                None => top.push(i),
+            }
+            parents.push(parent);
+            ancestors.push(i);
+        }
+        // Children always come after their parent.
+        let mut counts = vec![0; items.len()];
+        for i in (0..items.len()).rev() {
+            if let Some(p) = parents[i] {
+                counts[p] += 1 + counts[i];
+            }
+        }
+        OutlineTree {
+            parents,
+            children,
+            top,
+            counts,
+        }
+    }
+
+    /// The siblings of an item, including the item itself.
+    pub fn siblings(&self, item: usize) -> &[usize] {
+        match self.parents[item] {
+            Some(p) => &self.children[p],
+            None => &self.top,
+        }
+    }
+}
+
+#[test]
+fn test_outline_tree() {
+    let items = [
+        OutlineItem::new("Chapter 1"),
+        OutlineItem::new("Section 1.1").with_level(1),
+        OutlineItem::new("Detail").with_level(3),
+        OutlineItem::new("Section 1.2").with_level(1),
+        OutlineItem::new("Chapter 2"),
+    ];
+    let tree = OutlineTree::new(&items);
+    assert_eq!(vec![None, Some(0), Some(1), Some(0), None], tree.parents);
+    assert_eq!(vec![0, 4], tree.top);
+    assert_eq!(vec![1, 3], tree.children[0]);
+    assert_eq!(vec![3, 1, 0, 0, 0], tree.counts);
+    assert_eq!(&[1, 3], tree.siblings(3));
+}
+
+#[test]
+fn test_outline_style() {
+    let item = OutlineItem::new("Errata")
+        .with_bold()
+        .with_italic()
+        .with_color(Color::gray(255));
+    let mut output = Vec::new();
+    item.write_dictionary(&mut output, &[], 1, None, None, None)
+        .unwrap();
+    assert_eq!(
+        "<< /Title (Errata)\n/Parent 1 0 R\n/F 3\n/C [1 1 1]\n>>\n",
+        String::from_utf8(output).unwrap(),
+    );
+}
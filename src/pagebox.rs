@@ -0,0 +1,81 @@
+use rect::{Margins, Rect};
+
+/// The boundaries of a page for printing and prepress, as described
+/// in section 14.11.2 of the PDF specification, given as distances
+/// from the edges of the page (the media box).
+///
+/// Set with
+/// [Pdf::set_page_boxes](struct.Pdf.html#method.set_page_boxes).
+/// A `None` value leaves the box at its default: the crop box is the
+/// whole page, and the other boxes are the crop box.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Margins, PageBoxes, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// // A 180 x 240 pt flyer, with 9 pt bleed beyond the edges where it
+/// // is cut, and 9 pt outside of that for crop marks.
+/// document.set_page_boxes(PageBoxes {
+///     bleed: Some(Margins::uniform(9.0)),
+///     trim: Some(Margins::uniform(18.0)),
+///     ..PageBoxes::default()
+/// });
+/// document.render_page(216.0, 276.0, |canvas| {
+///     // The background extends into the bleed.
+///     canvas.rectangle(9.0, 9.0, 198.0, 258.0)?;
+///     canvas.fill()
+/// }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PageBoxes {
+    /// The visible part of the page, when it is shown or printed.
+    pub crop: Option<Margins>,
+    /// The part of the page to print in production, including any
+    /// bleed.
+    pub bleed: Option<Margins>,
+    /// The size of the finished page, after trimming.
+    pub trim: Option<Margins>,
+    /// The meaningful content of the page.
+    pub art: Option<Margins>,
+}
+
+impl PageBoxes {
+    /// The boxes of a page of the given size, as pdf page dictionary
+    /// entries.
+    pub(crate) fn to_pdf(self, width: f32, height: f32) -> String {
+        let page = Rect::new(0.0, 0.0, width, height);
+        let boxes = [
+            ("CropBox", self.crop),
+            ("BleedBox", self.bleed),
+            ("TrimBox", self.trim),
+            ("ArtBox", self.art),
+        ];
+        let mut result = String::new();
+        for &(key, margins) in &boxes {
+            if let Some(margins) = margins {
+                let r = page.inside(margins);
+                result.push_str(&format!(
+                    "   /{} [ {} {} {} {} ]\n",
+                    key,
+                    r.x,
+                    r.y,
+                    r.right(),
+                    r.top(),
+                ));
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn test_page_boxes() {
+    let boxes = PageBoxes {
+        trim: Some(Margins::new(10.0, 20.0, 30.0, 40.0)),
+        ..PageBoxes::default()
+    };
+    assert_eq!("   /TrimBox [ 40 30 80 90 ]\n", boxes.to_pdf(100.0, 100.0));
+    assert_eq!("", PageBoxes::default().to_pdf(100.0, 100.0));
+}
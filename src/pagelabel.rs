@@ -0,0 +1,123 @@
+use pdfstring::text_string;
+
+/// How pages are labelled (numbered) in viewers, as described in
+/// section 12.4.2 of the PDF specification.
+///
+/// Set with
+/// [Pdf::set_page_labels](struct.Pdf.html#method.set_page_labels),
+/// for a range of pages.
+/// Without page labels, viewers number the pages from 1.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{PageLabels, PageNumbering, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// // The front matter is numbered i, ii, iii ...
+/// document.set_page_labels(PageLabels::new(PageNumbering::LowerRoman));
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// // ... the main text 1, 2, 3 ...
+/// document.set_page_labels(PageLabels::new(PageNumbering::Decimal));
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// // ... and the appendix A-1, A-2, A-3 ...
+/// document.set_page_labels(
+///     PageLabels::new(PageNumbering::Decimal).with_prefix("A-"),
+/// );
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageLabels {
+    numbering: Option<PageNumbering>,
+    prefix: String,
+    start: u32,
+}
+
+/// The style of the numbers in [PageLabels](struct.PageLabels.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageNumbering {
+    /// Decimal numbers: 1, 2, 3 ...
+    Decimal,
+    /// Uppercase roman numerals: I, II, III ...
+    UpperRoman,
+    /// Lowercase roman numerals: i, ii, iii ...
+    LowerRoman,
+    /// Uppercase letters: A, B, C ... Z, AA, BB ...
+    UpperLetters,
+    /// Lowercase letters: a, b, c ... z, aa, bb ...
+    LowerLetters,
+}
+
+impl PageLabels {
+    /// Number the pages in the given style, starting at 1.
+    pub fn new(numbering: PageNumbering) -> Self {
+        PageLabels {
+            numbering: Some(numbering),
+            prefix: String::new(),
+            start: 1,
+        }
+    }
+
+    /// Label every page with the same text, and no number, e.g. for a
+    /// cover.
+    pub fn text(label: &str) -> Self {
+        PageLabels {
+            numbering: None,
+            prefix: label.to_string(),
+            start: 1,
+        }
+    }
+
+    /// Put `prefix` before the number of each page.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Set the number of the first page, instead of 1.
+    pub fn with_start(mut self, start: u32) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// The labels as a pdf page label dictionary.
+    pub(crate) fn to_pdf(&self) -> String {
+        let mut result = String::from("<<");
+        if let Some(numbering) = self.numbering {
+            let style = match numbering {
+                PageNumbering::Decimal => "D",
+                PageNumbering::UpperRoman => "R",
+                PageNumbering::LowerRoman => "r",
+                PageNumbering::UpperLetters => "A",
+                PageNumbering::LowerLetters => "a",
+            };
+            result.push_str(&format!(" /S /{}", style));
+        }
+        if !self.prefix.is_empty() {
+            result.push_str(&format!(" /P {}", text_string(&self.prefix)));
+        }
+        if self.start != 1 {
+            result.push_str(&format!(" /St {}", self.start));
+        }
+        result.push_str(" >>");
+        result
+    }
+}
+
+#[test]
+fn test_page_labels() {
+    assert_eq!(
+        "<< /S /r >>",
+        PageLabels::new(PageNumbering::LowerRoman).to_pdf()
+    );
+    assert_eq!(
+        "<< /S /D /P (A-) /St 3 >>",
+        PageLabels::new(PageNumbering::Decimal)
+            .with_prefix("A-")
+            .with_start(3)
+            .to_pdf()
+    );
+    assert_eq!("<< /P (Cover) >>", PageLabels::text("Cover").to_pdf());
+}
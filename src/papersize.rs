@@ -0,0 +1,91 @@
+use units::{Inch, Mm};
+
+/// Standard paper sizes, for
+/// [Pdf::render_page_sized](struct.Pdf.html#method.render_page_sized).
+///
+/// The sizes are in portrait orientation, use
+/// [landscape](#method.landscape) to turn them sideways.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{PaperSize, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.render_page_sized(PaperSize::A4, |canvas| {
+///     canvas.rectangle(50.0, 50.0, 495.0, 742.0)?;
+///     canvas.stroke()
+/// }).unwrap();
+/// document.render_page_sized(PaperSize::A4.landscape(), |canvas| {
+///     canvas.rectangle(50.0, 50.0, 742.0, 495.0)?;
+///     canvas.stroke()
+/// }).unwrap();
+/// # document.finish().unwrap();
+/// assert_eq!((612.0, 792.0), PaperSize::Letter.size());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaperSize {
+    /// ISO A0, 841 x 1189 mm.
+    A0,
+    /// ISO A1, 594 x 841 mm.
+    A1,
+    /// ISO A2, 420 x 594 mm.
+    A2,
+    /// ISO A3, 297 x 420 mm.
+    A3,
+    /// ISO A4, 210 x 297 mm.
+    A4,
+    /// ISO A5, 148 x 210 mm.
+    A5,
+    /// ISO A6, 105 x 148 mm.
+    A6,
+    /// ISO B4, 250 x 353 mm.
+    B4,
+    /// ISO B5, 176 x 250 mm.
+    B5,
+    /// US Letter, 8.5 x 11 inches.
+    Letter,
+    /// US Legal, 8.5 x 14 inches.
+    Legal,
+    /// US Tabloid (or Ledger, in landscape), 11 x 17 inches.
+    Tabloid,
+    /// US Executive, 7.25 x 10.5 inches.
+    Executive,
+    /// Any other size, width and height in points.
+    Custom(f32, f32),
+}
+
+impl PaperSize {
+    /// The width and height in points.
+    pub fn size(self) -> (f32, f32) {
+        let mm = |width, height| (Mm(width).into(), Mm(height).into());
+        let inch = |width, height| (Inch(width).into(), Inch(height).into());
+        match self {
+            PaperSize::A0 => mm(841.0, 1189.0),
+            PaperSize::A1 => mm(594.0, 841.0),
+            PaperSize::A2 => mm(420.0, 594.0),
+            PaperSize::A3 => mm(297.0, 420.0),
+            PaperSize::A4 => mm(210.0, 297.0),
+            PaperSize::A5 => mm(148.0, 210.0),
+            PaperSize::A6 => mm(105.0, 148.0),
+            PaperSize::B4 => mm(250.0, 353.0),
+            PaperSize::B5 => mm(176.0, 250.0),
+            PaperSize::Letter => inch(8.5, 11.0),
+            PaperSize::Legal => inch(8.5, 14.0),
+            PaperSize::Tabloid => inch(11.0, 17.0),
+            PaperSize::Executive => inch(7.25, 10.5),
+            PaperSize::Custom(width, height) => (width, height),
+        }
+    }
+
+    /// The same size, turned so that the width is the longer side.
+    pub fn landscape(self) -> Self {
+        let (width, height) = self.size();
+        PaperSize::Custom(width.max(height), width.min(height))
+    }
+
+    /// The same size, turned so that the height is the longer side.
+    pub fn portrait(self) -> Self {
+        let (width, height) = self.size();
+        PaperSize::Custom(width.min(height), width.max(height))
+    }
+}
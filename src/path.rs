@@ -0,0 +1,306 @@
+use graphicsstate::Matrix;
+use rect::Rect;
+
+/// One segment of a [Path](struct.Path.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathSegment {
+    /// Begin a new subpath at a point.
+    MoveTo(f32, f32),
+    /// A straight line from the current point to a point.
+    LineTo(f32, f32),
+    /// A Bézier curve from the current point, with two control points
+    /// and an end point (x1, y1, x2, y2, x3, y3).
+    CurveTo(f32, f32, f32, f32, f32, f32),
+    /// Close the current subpath.
+    Close,
+}
+
+/// A path, built as a value before it is used on a canvas.
+///
+/// The `move_to`, `line_to`, etc methods on a
+/// [Canvas](struct.Canvas.html) writes to the page immediately.
+/// A Path instead keeps its segments, so it can be transformed,
+/// measured, and reused before (and after) it is painted with
+/// [Canvas::fill_path](struct.Canvas.html#method.fill_path) and
+/// similar methods.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Path, Pdf};
+/// # use pdf_canvas::graphicsstate::Matrix;
+/// let mut arrow = Path::new();
+/// arrow
+///     .move_to(0.0, 0.0)
+///     .line_to(40.0, 0.0)
+///     .line_to(40.0, -5.0)
+///     .line_to(50.0, 2.5)
+///     .line_to(40.0, 10.0)
+///     .line_to(40.0, 5.0)
+///     .line_to(0.0, 5.0)
+///     .close();
+/// assert_eq!(50.0, arrow.bounding_box().unwrap().width);
+///
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// for i in 0..8 {
+///     let mut a = arrow.clone();
+///     a.transform(
+///         Matrix::rotate_deg(i as f32 * 45.0) * Matrix::translate(90.0, 120.0),
+///     );
+///     canvas.fill_path(&a)?;
+/// }
+/// # Ok(())
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Create a new empty path.
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// Begin a new subpath at the point (x, y).
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.segments.push(PathSegment::MoveTo(x, y));
+        self
+    }
+
+    /// Add a straight line from the current point to (x, y).
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.segments.push(PathSegment::LineTo(x, y));
+        self
+    }
+
+    /// Add a Bézier curve from the current point to (x3, y3) with
+    /// (x1, y1) and (x2, y2) as Bézier control points.
+    pub fn curve_to(
+        &mut self,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        x3: f32,
+        y3: f32,
+    ) -> &mut Self {
+        self.segments
+            .push(PathSegment::CurveTo(x1, y1, x2, y2, x3, y3));
+        self
+    }
+
+    /// Close the current subpath, by a straight line to its start
+    /// point.
+    pub fn close(&mut self) -> &mut Self {
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    /// Add a closed rectangle with a corner at (x, y) and extending
+    /// width × height.
+    pub fn rectangle(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    ) -> &mut Self {
+        self.move_to(x, y)
+            .line_to(x + width, y)
+            .line_to(x + width, y + height)
+            .line_to(x, y + height)
+            .close()
+    }
+
+    /// Add a circle with center (x, y) and radius r, approximated by
+    /// four Bézier curves.
+    pub fn circle(&mut self, x: f32, y: f32, r: f32) -> &mut Self {
+        let d = r * 0.551_915;
+        self.move_to(x + r, y)
+            .curve_to(x + r, y + d, x + d, y + r, x, y + r)
+            .curve_to(x - d, y + r, x - r, y + d, x - r, y)
+            .curve_to(x - r, y - d, x - d, y - r, x, y - r)
+            .curve_to(x + d, y - r, x + r, y - d, x + r, y)
+            .close()
+    }
+
+    /// Get the segments of this path.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// True if the path has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Transform all points of the path by a matrix.
+    pub fn transform(&mut self, m: Matrix) -> &mut Self {
+        let t = |x: f32, y: f32| m.transform_point((x, y));
+        for segment in &mut self.segments {
+            *segment = match *segment {
+                PathSegment::MoveTo(x, y) => {
+                    let (x, y) = t(x, y);
+                    PathSegment::MoveTo(x, y)
+                }
+                PathSegment::LineTo(x, y) => {
+                    let (x, y) = t(x, y);
+                    PathSegment::LineTo(x, y)
+                }
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    let (x1, y1) = t(x1, y1);
+                    let (x2, y2) = t(x2, y2);
+                    let (x3, y3) = t(x3, y3);
+                    PathSegment::CurveTo(x1, y1, x2, y2, x3, y3)
+                }
+                PathSegment::Close => PathSegment::Close,
+            }
+        }
+        self
+    }
+
+    /// Get the smallest rectangle containing the path, or None if the
+    /// path is empty.
+    ///
+    /// The box is exact for curves as well, not just a box around the
+    /// control points.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let mut bounds: Option<(f32, f32, f32, f32)> = None;
+        let mut add = |x: f32, y: f32| {
+            bounds = Some(match bounds {
+                None => (x, y, x, y),
+                Some((x0, y0, x1, y1)) => {
+                    (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+                }
+            })
+        };
+        let mut current = (0.0, 0.0);
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::MoveTo(x, y) | PathSegment::LineTo(x, y) => {
+                    add(x, y);
+                    current = (x, y);
+                }
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    let (x0, y0) = current;
+                    add(x3, y3);
+                    for t in cubic_extremes(x0, x1, x2, x3)
+                        .into_iter()
+                        .chain(cubic_extremes(y0, y1, y2, y3))
+                    {
+                        add(
+                            cubic(x0, x1, x2, x3, t),
+                            cubic(y0, y1, y2, y3, t),
+                        );
+                    }
+                    current = (x3, y3);
+                }
+                PathSegment::Close => (),
+            }
+        }
+        bounds.map(|(x0, y0, x1, y1)| Rect::new(x0, y0, x1 - x0, y1 - y0))
+    }
+
+    /// Get the total length of the path.
+    ///
+    /// The length of curves is approximated by dividing them into
+    /// many short straight lines.
+    pub fn length(&self) -> f32 {
+        let dist = |(x0, y0): (f32, f32), (x1, y1): (f32, f32)| {
+            ((x1 - x0) * (x1 - x0) + (y1 - y0) * (y1 - y0)).sqrt()
+        };
+        let mut length = 0.0;
+        let mut start = (0.0, 0.0);
+        let mut current = (0.0, 0.0);
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::MoveTo(x, y) => {
+                    start = (x, y);
+                    current = start;
+                }
+                PathSegment::LineTo(x, y) => {
+                    length += dist(current, (x, y));
+                    current = (x, y);
+                }
+                PathSegment::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    let (x0, y0) = current;
+                    let steps = 64;
+                    let mut prev = current;
+                    for i in 1..=steps {
+                        let t = i as f32 / steps as f32;
+                        let p = (
+                            cubic(x0, x1, x2, x3, t),
+                            cubic(y0, y1, y2, y3, t),
+                        );
+                        length += dist(prev, p);
+                        prev = p;
+                    }
+                    current = (x3, y3);
+                }
+                PathSegment::Close => {
+                    length += dist(current, start);
+                    current = start;
+                }
+            }
+        }
+        length
+    }
+}
+
+/// Evaluate a one-dimensional cubic Bézier curve at t.
+fn cubic(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let u = 1.0 - t;
+    u * u * u * p0
+        + 3.0 * u * u * t * p1
+        + 3.0 * u * t * t * p2
+        + t * t * t * p3
+}
+
+/// Get the values of t in (0, 1) where a one-dimensional cubic
+/// Bézier curve has a local minimum or maximum.
+fn cubic_extremes(p0: f32, p1: f32, p2: f32, p3: f32) -> Vec<f32> {
+    // The derivative is a quadratic a t² + b t + c.
+    let a = 3.0 * (-p0 + 3.0 * p1 - 3.0 * p2 + p3);
+    let b = 6.0 * (p0 - 2.0 * p1 + p2);
+    let c = 3.0 * (p1 - p0);
+    let roots = if a.abs() < 1e-6 {
+        if b.abs() < 1e-6 {
+            vec![]
+        } else {
+            vec![-c / b]
+        }
+    } else {
+        let d = b * b - 4.0 * a * c;
+        if d < 0.0 {
+            vec![]
+        } else {
+            let d = d.sqrt();
+            vec![(-b + d) / (2.0 * a), (-b - d) / (2.0 * a)]
+        }
+    };
+    roots.into_iter().filter(|&t| t > 0.0 && t < 1.0).collect()
+}
+
+#[test]
+fn test_circle_bounds_and_length() {
+    use std::f32::consts::PI;
+    let mut p = Path::new();
+    p.circle(10.0, 20.0, 5.0);
+    let b = p.bounding_box().unwrap();
+    assert!((b.x - 5.0).abs() < 1e-3, "{:?}", b);
+    assert!((b.y - 15.0).abs() < 1e-3, "{:?}", b);
+    assert!((b.width - 10.0).abs() < 1e-3, "{:?}", b);
+    assert!((p.length() - 10.0 * PI).abs() < 1e-2, "{}", p.length());
+}
+
+#[test]
+fn test_rectangle_length() {
+    let mut p = Path::new();
+    p.rectangle(0.0, 0.0, 3.0, 4.0);
+    assert_eq!(14.0, p.length());
+    assert_eq!(Some(Rect::new(0.0, 0.0, 3.0, 4.0)), p.bounding_box());
+}
@@ -0,0 +1,304 @@
+use canvas::{render_content, Canvas};
+use graphicsstate::{Color, Matrix};
+use rect::Rect;
+use resources::Resources;
+use shading::Shading;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use Pdf;
+
+/// Anything that can be used to paint with, for filling or stroking.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Paint, Pdf, Shading};
+/// # use pdf_canvas::graphicsstate::Color;
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// let sunset = Shading::axial(
+///     (0.0, 0.0),
+///     (0.0, 240.0),
+///     &[(0.0, Color::rgb(255, 128, 0)), (1.0, Color::rgb(0, 0, 128))],
+/// );
+/// canvas.set_fill(Paint::Shading(sunset))?;
+/// canvas.set_stroke(Paint::Color(Color::gray(0)))?;
+/// canvas.rectangle(10.0, 10.0, 160.0, 220.0)?;
+/// canvas.fill()?;
+/// canvas.rectangle(10.0, 10.0, 160.0, 220.0)?;
+/// canvas.stroke()
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone)]
+pub enum Paint {
+    /// A single color.
+    Color(Color),
+    /// A smooth gradient, used as a shading pattern.
+    Shading(Shading),
+    /// A tiling pattern.
+    Pattern(TilingPattern),
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Color(color)
+    }
+}
+
+impl From<Shading> for Paint {
+    fn from(shading: Shading) -> Self {
+        Paint::Shading(shading)
+    }
+}
+
+impl From<TilingPattern> for Paint {
+    fn from(pattern: TilingPattern) -> Self {
+        Paint::Pattern(pattern)
+    }
+}
+
+/// Source of unique ids for tiling patterns, so each pattern can be
+/// written only once, even if it is used on many pages.
+static NEXT_PATTERN_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn next_pattern_id() -> usize {
+    NEXT_PATTERN_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A tiling pattern, a small piece of content (a tile) that is
+/// repeated at fixed horizontal and vertical intervals to fill an
+/// area, as described in section 8.7.3 of the PDF specification.
+///
+/// Use it with
+/// [Canvas::set_fill_pattern](struct.Canvas.html#method.set_fill_pattern)
+/// or
+/// [Canvas::set_stroke_pattern](struct.Canvas.html#method.set_stroke_pattern).
+/// Note that the pattern is placed in the default coordinate system
+/// of the page, regardless of any transformations on the canvas.
+/// Use [with_matrix](#method.with_matrix) to transform the pattern.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Pdf, TilingPattern};
+/// # use pdf_canvas::graphicsstate::Color;
+/// // A checkerboard with 10 pt squares.
+/// let checkers = TilingPattern::new(20.0, 20.0, |c| {
+///     c.set_fill_color(Color::gray(0))?;
+///     c.rectangle(0.0, 0.0, 10.0, 10.0)?;
+///     c.rectangle(10.0, 10.0, 10.0, 10.0)?;
+///     c.fill()
+/// }).unwrap();
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// canvas.set_fill_pattern(&checkers)?;
+/// canvas.rectangle(10.0, 10.0, 160.0, 160.0)?;
+/// canvas.fill()
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct TilingPattern {
+    id: usize,
+    tile: Arc<Tile>,
+    step: (f32, f32),
+    matrix: Option<Matrix>,
+}
+
+/// The content of a tile, shared by clones of a pattern.
+struct Tile {
+    bbox: [f32; 4],
+    content: Vec<u8>,
+    resources: Resources,
+}
+
+impl TilingPattern {
+    /// Create a pattern with a tile drawn by `render`.
+    ///
+    /// The tile extends from (0, 0) to (width, height), anything drawn
+    /// outside of that is clipped.
+    /// The tiles are placed next to each other, unless another
+    /// spacing is given by [with_step](#method.with_step).
+    pub fn new<F>(width: f32, height: f32, render: F) -> io::Result<Self>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let mut outline_items = Vec::new();
+        let (content, resources) = render_content(
+            Rect::new(0.0, 0.0, width, height),
+            false,
+            &mut outline_items,
+            &HashMap::new(),
+            render,
+        )?;
+        Ok(TilingPattern {
+            id: next_pattern_id(),
+            tile: Arc::new(Tile {
+                bbox: [0.0, 0.0, width, height],
+                content,
+                resources,
+            }),
+            step: (width, height),
+            matrix: None,
+        })
+    }
+
+    /// Set the horizontal and vertical distance between tiles.
+    ///
+    /// Steps larger than the tile size leave gaps between the tiles,
+    /// smaller steps make the tiles overlap.
+    pub fn with_step(mut self, x_step: f32, y_step: f32) -> Self {
+        self.step = (x_step, y_step);
+        self.id = next_pattern_id();
+        self
+    }
+
+    /// Set a matrix mapping the pattern space to the default
+    /// coordinate system of the page, e.g. to scale or rotate the
+    /// pattern.
+    pub fn with_matrix(mut self, matrix: Matrix) -> Self {
+        self.matrix = Some(matrix);
+        self.id = next_pattern_id();
+        self
+    }
+
+    /// Create a hatch pattern of parallel lines, `spacing` apart.
+    ///
+    /// An `angle` of 0 gives horizontal lines, the lines are rotated
+    /// counterclockwise by `angle` degrees.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Pdf, TilingPattern};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// let diagonal =
+    ///     TilingPattern::hatch(6.0, 45.0, 0.5, Color::gray(0)).unwrap();
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// canvas.set_fill_pattern(&diagonal)?;
+    /// canvas.rectangle(10.0, 10.0, 160.0, 160.0)?;
+    /// canvas.fill()
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn hatch(
+        spacing: f32,
+        angle: f32,
+        line_width: f32,
+        color: Color,
+    ) -> io::Result<Self> {
+        TilingPattern::new(spacing, spacing, |c| {
+            c.set_stroke_color(color)?;
+            c.set_line_width(line_width)?;
+            c.line(0.0, spacing / 2.0, spacing, spacing / 2.0)?;
+            c.stroke()
+        })
+        .map(|p| p.with_matrix(Matrix::rotate_deg(angle)))
+    }
+
+    /// Create a pattern of crossing lines, `spacing` apart in both
+    /// directions.
+    ///
+    /// An `angle` of 0 gives horizontal and vertical lines, 45 gives
+    /// two sets of diagonal lines.
+    pub fn cross_hatch(
+        spacing: f32,
+        angle: f32,
+        line_width: f32,
+        color: Color,
+    ) -> io::Result<Self> {
+        TilingPattern::new(spacing, spacing, |c| {
+            let mid = spacing / 2.0;
+            c.set_stroke_color(color)?;
+            c.set_line_width(line_width)?;
+            c.line(0.0, mid, spacing, mid)?;
+            c.line(mid, 0.0, mid, spacing)?;
+            c.stroke()
+        })
+        .map(|p| p.with_matrix(Matrix::rotate_deg(angle)))
+    }
+
+    /// Create a pattern of filled dots with the given `radius`,
+    /// `spacing` apart in both directions.
+    ///
+    /// The grid of dots is rotated counterclockwise by `angle`
+    /// degrees.
+    pub fn dots(
+        spacing: f32,
+        angle: f32,
+        radius: f32,
+        color: Color,
+    ) -> io::Result<Self> {
+        TilingPattern::new(spacing, spacing, |c| {
+            c.set_fill_color(color)?;
+            c.circle(spacing / 2.0, spacing / 2.0, radius)?;
+            c.fill()
+        })
+        .map(|p| p.with_matrix(Matrix::rotate_deg(angle)))
+    }
+
+    /// Write the pattern, and any resources it needs, to the pdf.
+    /// Return the object id of the pattern.
+    fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        let tile = &self.tile;
+        let resources = pdf.write_resources(&tile.resources)?;
+        pdf.write_new_object(|object_id, pdf| {
+            let b = tile.bbox;
+            write!(
+                pdf.output,
+                "<< /Type /Pattern /PatternType 1 /PaintType 1 \
+                 /TilingType 1\n   \
+                 /BBox [ {} {} {} {} ]\n   \
+                 /XStep {} /YStep {}\n   \
+                 /Resources {}\n   ",
+                b[0], b[1], b[2], b[3], self.step.0, self.step.1, resources,
+            )?;
+            if let Some(matrix) = self.matrix {
+                write!(pdf.output, "/Matrix [ {} ]\n   ", matrix)?;
+            }
+            let prologue = pdf.content_prologue();
+            writeln!(
+                pdf.output,
+                "/Length {} >>\n\
+                 stream",
+                prologue.len() + tile.content.len(),
+            )?;
+            pdf.output.write_all(prologue)?;
+            pdf.output.write_all(&tile.content)?;
+            writeln!(pdf.output, "endstream")?;
+            Ok(object_id)
+        })
+    }
+}
+
+/// A pattern used in a content stream.
+#[derive(Clone)]
+pub enum PatternResource {
+    Shading(Shading),
+    Tiling(TilingPattern),
+}
+
+impl PatternResource {
+    /// The id of the tiling pattern, if this is a tiling pattern.
+    pub fn tiling_id(&self) -> Option<usize> {
+        match *self {
+            PatternResource::Shading(_) => None,
+            PatternResource::Tiling(ref tiling) => Some(tiling.id),
+        }
+    }
+
+    /// Write the pattern as new object(s) in the pdf.
+    /// Return the object id of the pattern.
+    pub fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        match *self {
+            PatternResource::Shading(ref shading) => {
+                shading.write_pattern_object(pdf)
+            }
+            PatternResource::Tiling(ref tiling) => tiling.write_object(pdf),
+        }
+    }
+}
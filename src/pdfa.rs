@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use time::Tm;
+
+/// A PDF/A conformance level, for long term archiving, as set by
+/// [Pdf::set_pdfa](struct.Pdf.html#method.set_pdfa).
+///
+/// The document gets the XMP metadata and the sRGB output intent
+/// that PDF/A requires, and JavaScript is not allowed.
+/// Note that PDF/A requires all fonts to be embedded, and the built-in
+/// fonts are not, so strict validators reject documents with text.
+/// Launch actions and annotations without an appearance, as well as
+/// encryption, are also not allowed.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{EmbeddedFile, FileRelationship, PdfA, Pdf};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.set_pdfa(PdfA::A3B);
+/// document.set_title("Invoice 2017");
+/// document.add_embedded_file(
+///     EmbeddedFile::new("factur-x.xml", b"<Invoice/>".to_vec())
+///         .with_mime_type("text/xml")
+///         .with_description("The invoice in machine readable form")
+///         .with_relationship(FileRelationship::Alternative),
+/// );
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PdfA {
+    /// PDF/A-2b, where embedded files must be PDF/A documents too.
+    A2B,
+    /// PDF/A-3b, which allows any embedded files, such as the XML of
+    /// an electronic invoice.
+    A3B,
+}
+
+impl PdfA {
+    /// The rdf:Description identifying the PDF/A level in the XMP
+    /// metadata.
+    pub(crate) fn identification(self) -> String {
+        let part = match self {
+            PdfA::A2B => 2,
+            PdfA::A3B => 3,
+        };
+        format!(
+            "<rdf:Description rdf:about=\"\"\n    \
+             xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n  \
+             <pdfaid:part>{}</pdfaid:part>\n  \
+             <pdfaid:conformance>B</pdfaid:conformance>\n\
+             </rdf:Description>\n",
+            part,
+        )
+    }
+}
+
+/// The XMP metadata of a PDF/A (or PDF/X) document, with the same
+/// values as the document information dictionary `info`.
+///
+/// `identification` is the rdf:Description elements identifying the
+/// standards, and `extra` is any additional ones.
+pub fn xmp_metadata(
+    identification: &str,
+    info: &BTreeMap<String, String>,
+    created: Option<&Tm>,
+    extra: &str,
+) -> String {
+    let mut result = String::from(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+    );
+    result.push_str(identification);
+    result.push_str(
+        "<rdf:Description rdf:about=\"\"\n    \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n    \
+         xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n    \
+         xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">\n",
+    );
+    for (key, value) in info {
+        let value = xml_escape(value);
+        let property = match key.as_str() {
+            "Title" => format!(
+                "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}\
+                 </rdf:li></rdf:Alt></dc:title>",
+                value,
+            ),
+            "Author" => format!(
+                "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq>\
+                 </dc:creator>",
+                value,
+            ),
+            "Subject" => format!(
+                "<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">\
+                 {}</rdf:li></rdf:Alt></dc:description>",
+                value,
+            ),
+            "Keywords" => format!("<pdf:Keywords>{}</pdf:Keywords>", value),
+            "Creator" => {
+                format!("<xmp:CreatorTool>{}</xmp:CreatorTool>", value)
+            }
+            "Producer" => format!("<pdf:Producer>{}</pdf:Producer>", value),
+            _ => continue,
+        };
+        result.push_str(&format!("  {}\n", property));
+    }
+    if let Some(time) = created {
+        let offset = time.tm_utcoff / 60;
+        let date = format!(
+            "{}{}{:02}:{:02}",
+            time.strftime("%Y-%m-%dT%H:%M:%S").unwrap(),
+            if offset < 0 { '-' } else { '+' },
+            offset.abs() / 60,
+            offset.abs() % 60,
+        );
+        result.push_str(&format!(
+            "  <xmp:CreateDate>{date}</xmp:CreateDate>\n  \
+             <xmp:ModifyDate>{date}</xmp:ModifyDate>\n",
+            date = date,
+        ));
+    }
+    result.push_str("</rdf:Description>\n");
+    result.push_str(extra);
+    result.push_str(
+        "</rdf:RDF>\n\
+         </x:xmpmeta>\n\
+         <?xpacket end=\"w\"?>",
+    );
+    result
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[test]
+fn test_xmp_metadata() {
+    let mut info = BTreeMap::new();
+    info.insert("Title".to_string(), "Fish & Chips".to_string());
+    let xmp = xmp_metadata(&PdfA::A3B.identification(), &info, None, "");
+    assert!(xmp.contains("<pdfaid:part>3</pdfaid:part>"));
+    assert!(xmp.contains(">Fish &amp; Chips</rdf:li>"));
+    assert!(!xmp.contains("CreateDate"));
+}
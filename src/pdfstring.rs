@@ -0,0 +1,81 @@
+//! Writing strings in pdf syntax.
+
+use time::Tm;
+
+/// Write `text` as a pdf string, for strings that are expected to be
+/// ascii, such as a URI.
+/// Any other characters are written as their UTF-8 bytes.
+pub fn ascii_string(text: &str) -> String {
+    let mut result = String::from("(");
+    for ch in text.chars() {
+        match ch {
+            '\\' | '(' | ')' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            ' '..='~' => result.push(ch),
+            _ => {
+                for byte in ch.to_string().bytes() {
+                    result.push_str(&format!("\\{:03o}", byte));
+                }
+            }
+        }
+    }
+    result.push(')');
+    result
+}
+
+/// Write `text` as a pdf text string, as used for annotation contents
+/// and other text shown by the viewer.
+/// Text that is not plain ascii is written as UTF-16.
+pub fn text_string(text: &str) -> String {
+    if text
+        .chars()
+        .all(|ch| (' '..='~').contains(&ch) || ch == '\n')
+    {
+        ascii_string(text)
+    } else {
+        let mut result = String::from("<FEFF");
+        for unit in text.encode_utf16() {
+            result.push_str(&format!("{:04X}", unit));
+        }
+        result.push('>');
+        result
+    }
+}
+
+/// Write `text` as a pdf name (including the leading slash), with
+/// any delimiters, whitespace and non-ascii characters escaped.
+pub fn name(text: &str) -> String {
+    let mut result = String::from("/");
+    for byte in text.bytes() {
+        match byte {
+            b'!'..=b'~' if !b"#%()/<>[]{}".contains(&byte) => {
+                result.push(char::from(byte))
+            }
+            _ => result.push_str(&format!("#{:02X}", byte)),
+        }
+    }
+    result
+}
+
+/// Write `time` as a pdf date string.
+pub fn date(time: &Tm) -> String {
+    let offset = time.tm_utcoff / 60;
+    format!(
+        "(D:{}{}{:02}'{:02}')",
+        time.strftime("%Y%m%d%H%M%S").unwrap(),
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60,
+    )
+}
+
+#[test]
+fn test_strings() {
+    assert_eq!("(a\\(b\\) \\\\)", ascii_string("a(b) \\"));
+    assert_eq!("(x\\303\\251)", ascii_string("xé"));
+    assert_eq!("(two\\012lines)", text_string("two\nlines"));
+    assert_eq!("<FEFF00E9>", text_string("é"));
+    assert_eq!("/text#2Fcsv", name("text/csv"));
+}
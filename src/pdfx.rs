@@ -0,0 +1,164 @@
+use icc;
+use pdfstring::text_string;
+
+/// A PDF/X conformance level, for print production, as set by
+/// [Pdf::set_pdfx](struct.Pdf.html#method.set_pdfx).
+///
+/// The document gets the output intent and metadata that PDF/X
+/// requires, and the version of the file is set to the one of the
+/// standard.
+/// Each page must have a trim box (or art box), see
+/// [Pdf::set_page_boxes](struct.Pdf.html#method.set_page_boxes), and
+/// the document must have a title.
+/// As for [PDF/A](enum.PdfA.html), note that the built-in fonts are
+/// not embedded, which strict preflight checks reject.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Margins, OutputIntent, PageBoxes, PdfX, Pdf};
+/// # use pdf_canvas::graphicsstate::Color;
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.set_pdfx(
+///     PdfX::X1a,
+///     OutputIntent::registered(
+///         "FOGRA39",
+///         "Coated FOGRA39 (ISO 12647-2:2004)",
+///     ),
+/// );
+/// document.set_title("Flyer");
+/// document.set_page_boxes(PageBoxes {
+///     trim: Some(Margins::uniform(9.0)),
+///     ..PageBoxes::default()
+/// });
+/// document.render_page(198.0, 258.0, |canvas| {
+///     canvas.set_fill_color(Color::cmyk(0, 128, 255, 0))?;
+///     canvas.rectangle(0.0, 0.0, 198.0, 258.0)?;
+///     canvas.fill()
+/// }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PdfX {
+    /// PDF/X-1a:2003, for CMYK and spot colors only, without
+    /// transparency.
+    ///
+    /// Using RGB colors, RGB images or transparency gives an error
+    /// when the page (or other content) is written.
+    X1a,
+    /// PDF/X-4, which allows RGB colors and transparency, to be
+    /// converted for the output intent when printed.
+    ///
+    /// The output intent must have an embedded profile.
+    X4,
+}
+
+impl PdfX {
+    /// The version of the standard, as written in the metadata.
+    pub(crate) fn version(self) -> &'static str {
+        match self {
+            PdfX::X1a => "PDF/X-1a:2003",
+            PdfX::X4 => "PDF/X-4",
+        }
+    }
+
+    /// The rdf:Description identifying the PDF/X version in the XMP
+    /// metadata.
+    pub(crate) fn identification(self) -> String {
+        format!(
+            "<rdf:Description rdf:about=\"\"\n    \
+             xmlns:pdfxid=\"http://www.npes.org/pdfx/ns/id/\"\n    \
+             xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">\n  \
+             <pdfxid:GTS_PDFXVersion>{}</pdfxid:GTS_PDFXVersion>\n  \
+             <pdf:Trapped>False</pdf:Trapped>\n\
+             </rdf:Description>\n",
+            self.version(),
+        )
+    }
+
+    /// The pdf version the standard is based on.
+    pub(crate) fn pdf_version(self) -> &'static str {
+        match self {
+            PdfX::X1a => "1.4",
+            PdfX::X4 => "1.6",
+        }
+    }
+}
+
+/// The printing condition that a PDF/X document is prepared for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputIntent {
+    identifier: String,
+    info: String,
+    registered: bool,
+    profile: Option<Vec<u8>>,
+}
+
+impl OutputIntent {
+    /// A printing condition registered in the ICC characterization
+    /// data registry, e.g. `FOGRA39` for coated paper in offset
+    /// printing, with a description `info`.
+    pub fn registered(identifier: &str, info: &str) -> Self {
+        OutputIntent {
+            identifier: identifier.to_string(),
+            info: info.to_string(),
+            registered: true,
+            profile: None,
+        }
+    }
+
+    /// Embed the ICC profile of the printing condition, as given by
+    /// the print shop.
+    pub fn with_profile(mut self, icc_profile: Vec<u8>) -> Self {
+        self.profile = Some(icc_profile);
+        self
+    }
+
+    /// The sRGB output intent of a PDF/A document.
+    pub(crate) fn srgb() -> Self {
+        OutputIntent {
+            identifier: icc::SRGB_NAME.to_string(),
+            info: icc::SRGB_NAME.to_string(),
+            registered: false,
+            profile: Some(icc::srgb_profile()),
+        }
+    }
+
+    /// The embedded profile, if any.
+    pub(crate) fn profile(&self) -> Option<&[u8]> {
+        self.profile.as_deref()
+    }
+
+    /// The output intent dictionary, of the given subtype (such as
+    /// GTS_PDFX), with the object id of the embedded profile, if any.
+    pub(crate) fn to_pdf(
+        &self,
+        subtype: &str,
+        profile_id: Option<usize>,
+    ) -> String {
+        let mut result = format!(
+            "<< /Type /OutputIntent /S /{}\n   \
+             /OutputConditionIdentifier {} /Info {}",
+            subtype,
+            text_string(&self.identifier),
+            text_string(&self.info),
+        );
+        if self.registered {
+            result.push_str(" /RegistryName (http://www.color.org)");
+        }
+        if let Some(id) = profile_id {
+            result.push_str(&format!("\n   /DestOutputProfile {} 0 R", id));
+        }
+        result.push_str(" >>");
+        result
+    }
+}
+
+/// The number of color components of an ICC profile.
+pub(crate) fn profile_components(profile: &[u8]) -> usize {
+    match profile.get(16..20) {
+        Some(b"GRAY") => 1,
+        Some(b"CMYK") => 4,
+        _ => 3,
+    }
+}
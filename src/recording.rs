@@ -0,0 +1,90 @@
+use canvas::{render_content, Canvas};
+use formxobject::FormXObject;
+use rect::Rect;
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Source of unique ids for recordings, so each recording can be
+/// written only once, even if it is replayed on many pages.
+static NEXT_RECORDING_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Drawing operations recorded once, that can be replayed on any
+/// number of canvases.
+///
+/// The recorded content is stored only once in the pdf file (as a
+/// form XObject), no matter how many times it is replayed.
+/// Use it for repeated elements, such as a page header or a logo.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{BuiltinFont, Pdf, Recording};
+/// # use pdf_canvas::graphicsstate::{Color, Matrix};
+/// let logo = Recording::new(40.0, 40.0, |c| {
+///     c.set_fill_color(Color::rgb(200, 0, 0))?;
+///     c.circle(20.0, 20.0, 18.0)?;
+///     c.fill()?;
+///     c.set_fill_color(Color::gray(255))?;
+///     c.center_text(20.0, 14.0, BuiltinFont::Helvetica_Bold, 16.0, "R")
+/// }).unwrap();
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// for page in 0..3 {
+///     document.render_page(180.0, 240.0, |canvas| {
+///         canvas.with_state(|c| {
+///             c.concat(Matrix::translate(130.0, 190.0))?;
+///             c.replay(&logo)
+///         })
+///     }).unwrap();
+/// }
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Recording {
+    form: FormXObject,
+    width: f32,
+    height: f32,
+}
+
+impl Recording {
+    /// Record the content drawn by `render`.
+    ///
+    /// The recording extends from (0, 0) to (width, height), anything
+    /// drawn outside of that is clipped when replayed.
+    pub fn new<F>(width: f32, height: f32, render: F) -> io::Result<Self>
+    where
+        F: FnOnce(&mut Canvas) -> io::Result<()>,
+    {
+        let mut outline_items = Vec::new();
+        let (content, resources) = render_content(
+            Rect::new(0.0, 0.0, width, height),
+            false,
+            &mut outline_items,
+            &HashMap::new(),
+            render,
+        )?;
+        let mut form =
+            FormXObject::new([0.0, 0.0, width, height], content, resources);
+        form.set_shared_id(NEXT_RECORDING_ID.fetch_add(1, Ordering::Relaxed));
+        Ok(Recording {
+            form,
+            width,
+            height,
+        })
+    }
+
+    /// Get the width of the recording.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Get the height of the recording.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+}
+
+// Should not be called by user code.
+pub fn recording_form(recording: &Recording) -> FormXObject {
+    recording.form.clone()
+}
@@ -0,0 +1,255 @@
+/// An axis-aligned rectangle, given by its lower left corner, width and
+/// height.
+///
+/// # Example
+///
+/// ```
+/// use pdf_canvas::Rect;
+/// let r = Rect::new(10.0, 20.0, 100.0, 50.0);
+/// assert_eq!(110.0, r.right());
+/// assert_eq!(70.0, r.top());
+/// assert_eq!((60.0, 45.0), r.center());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    /// The left edge of the rectangle.
+    pub x: f32,
+    /// The bottom edge of the rectangle.
+    pub y: f32,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle.
+    pub height: f32,
+}
+
+impl Rect {
+    /// Create a rectangle with a corner at (x, y), and extending
+    /// width × height.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Create a rectangle from the coordinates of two opposite corners.
+    pub fn from_corners(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Rect::new(x1.min(x2), y1.min(y2), (x2 - x1).abs(), (y2 - y1).abs())
+    }
+
+    /// The x coordinate of the right edge.
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    /// The y coordinate of the top edge.
+    pub fn top(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// The center point of the rectangle.
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Return a rectangle that is smaller by `d` on each side (or larger,
+    /// if `d` is negative).
+    pub fn inset(&self, d: f32) -> Self {
+        Rect::new(
+            self.x + d,
+            self.y + d,
+            self.width - 2.0 * d,
+            self.height - 2.0 * d,
+        )
+    }
+
+    /// Return the part of this rectangle that is inside of `margins`.
+    pub fn inside(&self, margins: Margins) -> Self {
+        Rect::new(
+            self.x + margins.left,
+            self.y + margins.bottom,
+            self.width - margins.left - margins.right,
+            self.height - margins.bottom - margins.top,
+        )
+    }
+
+    /// Return the smallest rectangle containing both this and `other`.
+    pub fn union(&self, other: &Rect) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.top().max(other.top()) - y,
+        )
+    }
+
+    /// Return a rectangle with the aspect ratio of `width` × `height`,
+    /// sized to this rectangle as given by `fit`, and placed in it as
+    /// given by `align`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pdf_canvas::{Align, Fit, Rect};
+    /// let slot = Rect::new(0.0, 0.0, 100.0, 50.0);
+    /// assert_eq!(
+    ///     Rect::new(25.0, 0.0, 50.0, 50.0),
+    ///     slot.fit(10.0, 10.0, Fit::Contain, Align::Center),
+    /// );
+    /// assert_eq!(
+    ///     Rect::new(0.0, -50.0, 100.0, 100.0),
+    ///     slot.fit(10.0, 10.0, Fit::Cover, Align::Top),
+    /// );
+    /// ```
+    pub fn fit(
+        &self,
+        width: f32,
+        height: f32,
+        fit: Fit,
+        align: Align,
+    ) -> Self {
+        let (sx, sy) = (self.width / width, self.height / height);
+        let scale = match fit {
+            Fit::Stretch => return *self,
+            Fit::Contain => sx.min(sy),
+            Fit::Cover => sx.max(sy),
+        };
+        let (w, h) = (width * scale, height * scale);
+        let (ax, ay) = align.fractions();
+        Rect::new(
+            self.x + (self.width - w) * ax,
+            self.y + (self.height - h) * ay,
+            w,
+            h,
+        )
+    }
+}
+
+/// How to size something with a fixed aspect ratio, such as an image,
+/// to a rectangle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fit {
+    /// As large as possible while still inside the rectangle.
+    Contain,
+    /// As small as possible while still covering the rectangle, so
+    /// some of it is outside.
+    Cover,
+    /// Exactly the size of the rectangle, ignoring the aspect ratio.
+    Stretch,
+}
+
+impl Default for Fit {
+    fn default() -> Self {
+        Fit::Contain
+    }
+}
+
+/// Where to place something in a rectangle that it doesn't exactly
+/// fill, see [Rect::fit](struct.Rect.html#method.fit).
+///
+/// Top and bottom refer to the y axis pointing up; use
+/// [flip_vertical](#method.flip_vertical) for the other way around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// In the top left corner.
+    TopLeft,
+    /// At the top, centered horizontally.
+    Top,
+    /// In the top right corner.
+    TopRight,
+    /// At the left edge, centered vertically.
+    Left,
+    /// Centered both horizontally and vertically.
+    Center,
+    /// At the right edge, centered vertically.
+    Right,
+    /// In the bottom left corner.
+    BottomLeft,
+    /// At the bottom, centered horizontally.
+    Bottom,
+    /// In the bottom right corner.
+    BottomRight,
+}
+
+impl Default for Align {
+    fn default() -> Self {
+        Align::Center
+    }
+}
+
+impl Align {
+    /// The position as fractions (0 to 1) of the free space to the
+    /// left and below.
+    pub fn fractions(self) -> (f32, f32) {
+        use self::Align::*;
+        let x = match self {
+            TopLeft | Left | BottomLeft => 0.0,
+            Top | Center | Bottom => 0.5,
+            TopRight | Right | BottomRight => 1.0,
+        };
+        let y = match self {
+            BottomLeft | Bottom | BottomRight => 0.0,
+            Left | Center | Right => 0.5,
+            TopLeft | Top | TopRight => 1.0,
+        };
+        (x, y)
+    }
+
+    /// Swap top and bottom.
+    pub fn flip_vertical(self) -> Self {
+        use self::Align::*;
+        match self {
+            TopLeft => BottomLeft,
+            Top => Bottom,
+            TopRight => BottomRight,
+            BottomLeft => TopLeft,
+            Bottom => Top,
+            BottomRight => TopRight,
+            other => other,
+        }
+    }
+}
+
+/// Distances from each edge of a page to its content area.
+///
+/// # Example
+///
+/// ```
+/// use pdf_canvas::{Margins, Rect};
+/// let page = Rect::new(0.0, 0.0, 180.0, 240.0);
+/// let margins = Margins::new(20.0, 10.0, 20.0, 10.0);
+/// assert_eq!(Rect::new(10.0, 20.0, 160.0, 200.0), page.inside(margins));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Margins {
+    /// The distance from the top edge.
+    pub top: f32,
+    /// The distance from the right edge.
+    pub right: f32,
+    /// The distance from the bottom edge.
+    pub bottom: f32,
+    /// The distance from the left edge.
+    pub left: f32,
+}
+
+impl Margins {
+    /// Create margins given in the order top, right, bottom, left (as
+    /// in css).
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Margins {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Create margins that are the same on all sides.
+    pub fn uniform(margin: f32) -> Self {
+        Margins::new(margin, margin, margin, margin)
+    }
+}
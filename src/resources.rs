@@ -0,0 +1,148 @@
+use colorspace::ColorSpace;
+use extgstate::ExtGState;
+use fontref::{create_font_ref, FontRef};
+use fontsource::{BuiltinFont, FontSource};
+use formxobject::FormXObject;
+use imagexobject::Image;
+use layer::Layer;
+use pattern::PatternResource;
+use shading::Shading;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The resources used by a content stream, such as a page.
+///
+/// Resources are collected while the content stream is written, and
+/// the objects they refer to are written when the content stream is
+/// done.
+#[derive(Default)]
+pub struct Resources {
+    pub fonts: HashMap<BuiltinFont, FontRef>,
+    pub ext_gstates: HashMap<ExtGState, String>,
+    pub forms: Vec<FormXObject>,
+    /// Names of forms used as XObjects, as pairs of name and index in
+    /// `forms`.
+    pub xobjects: Vec<(String, usize)>,
+    pub images: Vec<(String, Image)>,
+    pub shadings: Vec<(String, Shading)>,
+    pub patterns: Vec<(String, PatternResource)>,
+    pub color_spaces: HashMap<ColorSpace, String>,
+    /// The layers of optional content, by their property name.
+    pub layers: Vec<(String, Layer)>,
+    /// True if the content sets any DeviceRGB colors.
+    pub uses_rgb: bool,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Resources::default()
+    }
+
+    /// Get the FontRef for `font`, adding it to the resources if needed.
+    pub fn font_ref(&mut self, font: BuiltinFont) -> FontRef {
+        let next_n = self.fonts.len();
+        self.fonts
+            .entry(font)
+            .or_insert_with(|| {
+                create_font_ref(
+                    next_n,
+                    font.get_encoding().clone(),
+                    Arc::new(font.get_metrics()),
+                )
+            })
+            .clone()
+    }
+
+    /// Get the resource name for `state`, adding it if needed.
+    pub fn ext_gstate_name(&mut self, state: ExtGState) -> String {
+        let next_n = self.ext_gstates.len();
+        self.ext_gstates
+            .entry(state)
+            .or_insert_with(|| format!("/GS{}", next_n))
+            .clone()
+    }
+
+    /// Add a form to be written with these resources.
+    /// Return the index of the form.
+    pub fn add_form(&mut self, form: FormXObject) -> usize {
+        self.forms.push(form);
+        self.forms.len() - 1
+    }
+
+    /// Add a form to be used as a named XObject.
+    /// Return the name of the XObject.
+    ///
+    /// A shared form that is already in the resources is not added
+    /// again.
+    pub fn add_form_xobject(&mut self, form: FormXObject) -> String {
+        if let Some(id) = form.shared_id() {
+            for (name, existing) in &self.xobjects {
+                if self.forms[*existing].shared_id() == Some(id) {
+                    return name.clone();
+                }
+            }
+        }
+        let form = self.add_form(form);
+        let name = format!("/Fm{}", self.xobjects.len());
+        self.xobjects.push((name.clone(), form));
+        name
+    }
+
+    /// Add an image to be used as a named XObject.
+    /// Return the name of the XObject.
+    pub fn add_image(&mut self, image: &Image) -> String {
+        for (name, existing) in &self.images {
+            if existing.id() == image.id() {
+                return name.clone();
+            }
+        }
+        let name = format!("/Im{}", self.images.len());
+        self.images.push((name.clone(), image.clone()));
+        name
+    }
+
+    /// Add a shading to the resources.  Return its name.
+    pub fn add_shading(&mut self, shading: Shading) -> String {
+        let name = format!("/Sh{}", self.shadings.len());
+        self.shadings.push((name.clone(), shading));
+        name
+    }
+
+    /// Add a pattern to the resources.  Return its name.
+    ///
+    /// A tiling pattern that is already in the resources is not added
+    /// again.
+    pub fn add_pattern(&mut self, pattern: PatternResource) -> String {
+        if let Some(id) = pattern.tiling_id() {
+            for (name, existing) in &self.patterns {
+                if existing.tiling_id() == Some(id) {
+                    return name.clone();
+                }
+            }
+        }
+        let name = format!("/P{}", self.patterns.len());
+        self.patterns.push((name.clone(), pattern));
+        name
+    }
+
+    /// Get the property name for `layer`, adding it if needed.
+    pub fn layer_name(&mut self, layer: Layer) -> String {
+        for (name, existing) in &self.layers {
+            if *existing == layer {
+                return name.clone();
+            }
+        }
+        let name = format!("/OC{}", self.layers.len());
+        self.layers.push((name.clone(), layer));
+        name
+    }
+
+    /// Get the resource name for `space`, adding it if needed.
+    pub fn color_space_name(&mut self, space: ColorSpace) -> String {
+        let next_n = self.color_spaces.len();
+        self.color_spaces
+            .entry(space)
+            .or_insert_with(|| format!("/CS{}", next_n))
+            .clone()
+    }
+}
@@ -0,0 +1,342 @@
+use graphicsstate::Color;
+use rect::Rect;
+use std::io::{self, Write};
+use Pdf;
+
+/// A gradient described relative to a rectangle, rather than by
+/// absolute coordinates, for
+/// [Canvas::fill_rect_gradient](struct.Canvas.html#method.fill_rect_gradient).
+///
+/// The colors are given as color stops, as for a
+/// [Shading](struct.Shading.html).
+#[derive(Clone, Debug)]
+pub struct GradientSpec {
+    kind: GradientKind,
+    stops: Vec<(f32, Color)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum GradientKind {
+    Linear(f32),
+    Radial,
+}
+
+impl GradientSpec {
+    /// A gradient from the bottom edge to the top edge.
+    pub fn vertical(stops: &[(f32, Color)]) -> Self {
+        GradientSpec::linear(90.0, stops)
+    }
+
+    /// A gradient from the left edge to the right edge.
+    pub fn horizontal(stops: &[(f32, Color)]) -> Self {
+        GradientSpec::linear(0.0, stops)
+    }
+
+    /// A gradient in the direction `angle` degrees counterclockwise
+    /// from the positive x axis, extending from one corner of the
+    /// rectangle to the opposite corner.
+    pub fn linear(angle: f32, stops: &[(f32, Color)]) -> Self {
+        GradientSpec {
+            kind: GradientKind::Linear(angle),
+            stops: stops.to_vec(),
+        }
+    }
+
+    /// A circular gradient from the center of the rectangle to its
+    /// corners.
+    pub fn radial(stops: &[(f32, Color)]) -> Self {
+        GradientSpec {
+            kind: GradientKind::Radial,
+            stops: stops.to_vec(),
+        }
+    }
+
+    /// Get the shading for this gradient over a rectangle.
+    pub fn to_shading(&self, rect: Rect) -> Shading {
+        let (cx, cy) = rect.center();
+        let (w, h) = (rect.width.abs(), rect.height.abs());
+        match self.kind {
+            GradientKind::Linear(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let half = (w * cos.abs() + h * sin.abs()) / 2.0;
+                Shading::axial(
+                    (cx - half * cos, cy - half * sin),
+                    (cx + half * cos, cy + half * sin),
+                    &self.stops,
+                )
+            }
+            GradientKind::Radial => {
+                let r = (w * w + h * h).sqrt() / 2.0;
+                Shading::radial((cx, cy), 0.0, (cx, cy), r, &self.stops)
+            }
+        }
+    }
+}
+
+/// A smooth color gradient, as described in section 8.7.4.5 of the PDF
+/// specification.
+///
+/// The colors of a shading are given as color stops, pairs of an offset
+/// from 0.0 (the start of the gradient) to 1.0 (the end of the gradient)
+/// and the color at that offset.
+/// Between the stops, the color is interpolated linearly.
+/// The stops should be ordered by offset.
+/// If all stops are CMYK colors, the shading is painted in the
+/// DeviceCMYK color space, otherwise in DeviceRGB.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Pdf, Shading};
+/// # use pdf_canvas::graphicsstate::Color;
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// let rainbow = Shading::axial(
+///     (20.0, 0.0),
+///     (160.0, 0.0),
+///     &[
+///         (0.0, Color::rgb(255, 0, 0)),
+///         (0.5, Color::rgb(0, 255, 0)),
+///         (1.0, Color::rgb(0, 0, 255)),
+///     ],
+/// );
+/// canvas.with_state(|c| {
+///     c.rectangle(20.0, 20.0, 140.0, 40.0)?;
+///     c.clip()?;
+///     c.shade(rainbow)
+/// })
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Shading {
+    shading_type: u8,
+    coords: Vec<f32>,
+    stops: Vec<(f32, Color)>,
+    extend: (bool, bool),
+}
+
+impl Shading {
+    /// Create an axial shading, where the color varies along the line
+    /// from `from` to `to`, and is constant perpendicular to that line.
+    pub fn axial(
+        from: (f32, f32),
+        to: (f32, f32),
+        stops: &[(f32, Color)],
+    ) -> Self {
+        Shading {
+            shading_type: 2,
+            coords: vec![from.0, from.1, to.0, to.1],
+            stops: stops.to_vec(),
+            extend: (true, true),
+        }
+    }
+
+    /// Create a radial shading, where the color varies between a start
+    /// circle with center `from` and radius `from_r`, and an end circle
+    /// with center `to` and radius `to_r`.
+    ///
+    /// For a plain circular gradient, use the same center for both
+    /// circles and zero as the start radius.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdf_canvas::{Pdf, Shading};
+    /// # use pdf_canvas::graphicsstate::Color;
+    /// # let mut document = Pdf::create("foo.pdf").unwrap();
+    /// # document.render_page(180.0, 240.0, |canvas| {
+    /// // A sphere, lit from the upper left.
+    /// let sphere = Shading::radial(
+    ///     (75.0, 135.0),
+    ///     5.0,
+    ///     (90.0, 120.0),
+    ///     50.0,
+    ///     &[(0.0, Color::gray(255)), (1.0, Color::rgb(0, 0, 128))],
+    /// )
+    /// .extend(true, false);
+    /// canvas.shade(sphere)
+    /// # }).unwrap();
+    /// # document.finish().unwrap();
+    /// ```
+    pub fn radial(
+        from: (f32, f32),
+        from_r: f32,
+        to: (f32, f32),
+        to_r: f32,
+        stops: &[(f32, Color)],
+    ) -> Self {
+        Shading {
+            shading_type: 3,
+            coords: vec![from.0, from.1, from_r, to.0, to.1, to_r],
+            stops: stops.to_vec(),
+            extend: (true, true),
+        }
+    }
+
+    /// Set whether the shading is extended with the color of the first
+    /// stop before its start and with the color of the last stop after
+    /// its end.  Both default to true.
+    pub fn extend(mut self, before: bool, after: bool) -> Self {
+        self.extend = (before, after);
+        self
+    }
+
+    /// Write the shading dictionary as a new object in the pdf.
+    pub fn write_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        pdf.write_new_object(|object_id, pdf| {
+            write!(
+                pdf.output,
+                "<< /ShadingType {} /ColorSpace /{}\n   \
+                 /Coords [",
+                self.shading_type,
+                if self.is_cmyk() {
+                    "DeviceCMYK"
+                } else {
+                    "DeviceRGB"
+                },
+            )?;
+            for c in &self.coords {
+                write!(pdf.output, " {}", c)?;
+            }
+            write!(pdf.output, " ]\n   /Function ")?;
+            self.write_function(&mut pdf.output)?;
+            writeln!(
+                pdf.output,
+                "\n   /Extend [ {} {} ]\n\
+                 >>",
+                self.extend.0, self.extend.1,
+            )?;
+            Ok(object_id)
+        })
+    }
+
+    /// Write a shading pattern using this shading as a new object in
+    /// the pdf.
+    ///
+    /// The shading itself is written as a separate object.
+    pub fn write_pattern_object(&self, pdf: &mut Pdf) -> io::Result<usize> {
+        let shading_id = self.write_object(pdf)?;
+        pdf.write_new_object(|object_id, pdf| {
+            writeln!(
+                pdf.output,
+                "<< /Type /Pattern /PatternType 2 /Shading {} 0 R >>",
+                shading_id,
+            )?;
+            Ok(object_id)
+        })
+    }
+
+    pub(crate) fn is_cmyk(&self) -> bool {
+        !self.stops.is_empty()
+            && self
+                .stops
+                .iter()
+                .all(|&(_, c)| matches!(c, Color::CMYK { .. }))
+    }
+
+    /// Write the color function, a Type 2 (exponential interpolation)
+    /// function for each pair of adjacent color stops, stitched
+    /// together by a Type 3 function if there is more than one pair.
+    fn write_function<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let mut stops = self.stops.clone();
+        if stops.is_empty() {
+            stops.push((0.0, Color::gray(0)));
+        }
+        if stops[0].0 > 0.0 {
+            let first = stops[0].1;
+            stops.insert(0, (0.0, first));
+        }
+        let last = stops[stops.len() - 1];
+        if last.0 < 1.0 || stops.len() == 1 {
+            stops.push((1.0, last.1));
+        }
+        let components = if self.is_cmyk() {
+            cmyk_components
+        } else {
+            rgb_components
+        };
+        if stops.len() == 2 {
+            return write_interpolation(
+                output,
+                &components(stops[0].1),
+                &components(stops[1].1),
+            );
+        }
+        write!(
+            output,
+            "<< /FunctionType 3 /Domain [ 0 1 ]\n   /Functions ["
+        )?;
+        for pair in stops.windows(2) {
+            write!(output, "\n     ")?;
+            write_interpolation(
+                output,
+                &components(pair[0].1),
+                &components(pair[1].1),
+            )?;
+        }
+        write!(output, " ]\n   /Bounds [")?;
+        for stop in &stops[1..stops.len() - 1] {
+            write!(output, " {}", stop.0.clamp(0.0, 1.0))?;
+        }
+        write!(output, " ]\n   /Encode [")?;
+        for _ in 1..stops.len() {
+            write!(output, " 0 1")?;
+        }
+        write!(output, " ] >>")
+    }
+}
+
+fn write_interpolation<W: Write>(
+    output: &mut W,
+    c0: &[f32],
+    c1: &[f32],
+) -> io::Result<()> {
+    write!(output, "<< /FunctionType 2 /Domain [ 0 1 ] /C0 [")?;
+    for c in c0 {
+        write!(output, " {}", c)?;
+    }
+    write!(output, " ] /C1 [")?;
+    for c in c1 {
+        write!(output, " {}", c)?;
+    }
+    write!(output, " ] /N 1 >>")
+}
+
+/// Get the components of a color in the DeviceRGB color space.
+fn rgb_components(color: Color) -> Vec<f32> {
+    let norm = |c| f32::from(c) / 255.0;
+    match color {
+        Color::RGB { red, green, blue } => {
+            vec![norm(red), norm(green), norm(blue)]
+        }
+        Color::Gray { gray } => vec![norm(gray), norm(gray), norm(gray)],
+        Color::CMYK {
+            cyan,
+            magenta,
+            yellow,
+            black,
+        } => {
+            let k = 1.0 - norm(black);
+            vec![
+                (1.0 - norm(cyan)) * k,
+                (1.0 - norm(magenta)) * k,
+                (1.0 - norm(yellow)) * k,
+            ]
+        }
+    }
+}
+
+/// Get the components of a CMYK color in the DeviceCMYK color space.
+fn cmyk_components(color: Color) -> Vec<f32> {
+    let norm = |c| f32::from(c) / 255.0;
+    match color {
+        Color::CMYK {
+            cyan,
+            magenta,
+            yellow,
+            black,
+        } => vec![norm(cyan), norm(magenta), norm(yellow), norm(black)],
+        other => rgb_components(other),
+    }
+}
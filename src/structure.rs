@@ -0,0 +1,271 @@
+use pdfstring::{ascii_string, text_string};
+
+/// The type of a structure element, as described in section 14.8.4 of
+/// the PDF specification.
+///
+/// Content tagged with structure elements (see
+/// [Canvas::tagged](struct.Canvas.html#method.tagged)) gives the
+/// document a logical structure, which screen readers and other
+/// tools use to read it in the right order, and to tell headings
+/// from paragraphs and figures.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{BuiltinFont, Pdf, StructureType};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// # document.render_page(180.0, 240.0, |canvas| {
+/// canvas.tagged(StructureType::H1, |c| {
+///     c.left_text(10.0, 200.0, BuiltinFont::Helvetica_Bold, 18.0, "Fish")
+/// })?;
+/// canvas.tagged(StructureType::P, |c| {
+///     c.left_text(10.0, 180.0, BuiltinFont::Times_Roman, 12.0, "Blub.")
+/// })
+/// # }).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructureType {
+    /// A top-level heading.
+    H1,
+    /// A second-level heading.
+    H2,
+    /// A third-level heading.
+    H3,
+    /// A fourth-level heading.
+    H4,
+    /// A fifth-level heading.
+    H5,
+    /// A sixth-level heading.
+    H6,
+    /// A paragraph.
+    P,
+    /// A figure, such as a drawing or an image.
+    Figure,
+    /// A table, of rows, optionally grouped in a head, a body and a
+    /// foot.
+    ///
+    /// A table groups other structure elements, the content drawn
+    /// directly in it is not tagged, and so do the rows and groups of
+    /// rows.
+    /// See [Canvas::header_cell](struct.Canvas.html#method.header_cell)
+    /// for an example.
+    Table,
+    /// The header rows of a table.
+    THead,
+    /// The body rows of a table.
+    TBody,
+    /// The footer rows of a table.
+    TFoot,
+    /// A row of a table, of header and data cells.
+    TR,
+    /// A header cell of a table.  Use
+    /// [Canvas::header_cell](struct.Canvas.html#method.header_cell)
+    /// to tell which cells it is a header of.
+    TH,
+    /// A data cell of a table.
+    TD,
+}
+
+impl StructureType {
+    /// True if elements of this type contain other elements rather
+    /// than content.
+    pub(crate) fn is_grouping(self) -> bool {
+        matches!(
+            self,
+            StructureType::Table
+                | StructureType::THead
+                | StructureType::TBody
+                | StructureType::TFoot
+                | StructureType::TR
+        )
+    }
+}
+
+/// Which cells a table header cell is a header of, as set by
+/// [Canvas::header_cell](struct.Canvas.html#method.header_cell).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableScope {
+    /// The cells of the same row.
+    Row,
+    /// The cells of the same column.
+    Column,
+    /// The cells of both the same row and the same column.
+    Both,
+}
+
+/// A structure element tagged on a page, before any object ids are
+/// known.
+#[derive(Clone, Debug)]
+pub struct StructElement {
+    pub kind: StructureType,
+    /// The parent, as an index among the elements of the same page,
+    /// or None for the elements directly in the document.
+    pub parent: Option<usize>,
+    /// The marked-content id of the content of the element, unless it
+    /// is a grouping element.
+    pub mcid: Option<usize>,
+    /// The alternative text, describing the element for those who
+    /// can't see it.
+    pub alt: Option<String>,
+    /// The language of the element, if it differs from the document.
+    pub language: Option<String>,
+    /// For a table header cell, which cells it is a header of.
+    pub scope: Option<TableScope>,
+}
+
+impl StructElement {
+    pub fn new(kind: StructureType) -> Self {
+        StructElement {
+            kind,
+            parent: None,
+            mcid: None,
+            alt: None,
+            language: None,
+            scope: None,
+        }
+    }
+
+    pub fn with_alt(mut self, alt: &str) -> Self {
+        self.alt = Some(alt.to_string());
+        self
+    }
+
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    pub fn with_scope(mut self, scope: TableScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// The structure element dictionary, with the object ids of its
+    /// parent, its page and its kids (for a grouping element).
+    pub fn to_pdf(
+        &self,
+        parent_id: usize,
+        page_id: usize,
+        kids: &[usize],
+    ) -> String {
+        let content = match self.mcid {
+            Some(mcid) => mcid.to_string(),
+            None => {
+                let kids = kids
+                    .iter()
+                    .map(|id| format!(" {} 0 R", id))
+                    .collect::<String>();
+                format!("[{} ]", kids)
+            }
+        };
+        let mut result = format!(
+            "<< /Type /StructElem /S /{:?} /P {} 0 R /Pg {} 0 R\n   ",
+            self.kind, parent_id, page_id,
+        );
+        if let Some(ref alt) = self.alt {
+            result.push_str(&format!("/Alt {}\n   ", text_string(alt)));
+        }
+        if let Some(ref language) = self.language {
+            result
+                .push_str(&format!("/Lang {}\n   ", ascii_string(language)));
+        }
+        if let Some(scope) = self.scope {
+            result.push_str(&format!(
+                "/A << /O /Table /Scope /{:?} >>\n   ",
+                scope,
+            ));
+        }
+        result.push_str(&format!("/K {} >>", content));
+        result
+    }
+}
+
+/// The logical structure of a document, with the elements of all
+/// pages, to be written by `finish`.
+#[derive(Default)]
+pub struct StructTree {
+    /// The elements, with the object id of their page.  The parents
+    /// are element indexes in `elements`.
+    pub elements: Vec<(usize, StructElement)>,
+    /// The object id of each page with tagged content, by its key in
+    /// the parent tree.
+    pub pages: Vec<usize>,
+}
+
+impl StructTree {
+    /// Add the elements of the page with object id `page_id`.
+    /// Return the key of the page in the parent tree.
+    pub fn add_page(
+        &mut self,
+        page_id: usize,
+        elements: Vec<StructElement>,
+    ) -> usize {
+        let offset = self.elements.len();
+        for mut element in elements {
+            element.parent = element.parent.map(|p| p + offset);
+            self.elements.push((page_id, element));
+        }
+        self.pages.push(page_id);
+        self.pages.len() - 1
+    }
+
+    /// Remove the elements of the page with object id `page_id`.
+    pub fn remove_page(&mut self, page_id: usize) {
+        let mut new_index = Vec::with_capacity(self.elements.len());
+        let mut kept = Vec::new();
+        for (page, element) in self.elements.drain(..) {
+            if page == page_id {
+                new_index.push(None);
+            } else {
+                new_index.push(Some(kept.len()));
+                kept.push((page, element));
+            }
+        }
+        for (_, element) in &mut kept {
+            element.parent = element.parent.and_then(|p| new_index[p]);
+        }
+        self.elements = kept;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+#[test]
+fn test_remove_page() {
+    let element = |kind, parent, mcid| StructElement {
+        parent,
+        mcid,
+        ..StructElement::new(kind)
+    };
+    let mut tree = StructTree::default();
+    tree.add_page(3, vec![element(StructureType::P, None, Some(0))]);
+    let key = tree.add_page(
+        5,
+        vec![
+            element(StructureType::Table, None, None),
+            element(StructureType::P, Some(0), Some(0)),
+        ],
+    );
+    assert_eq!(1, key);
+    assert_eq!(Some(1), tree.elements[2].1.parent);
+    tree.remove_page(3);
+    assert_eq!(2, tree.elements.len());
+    assert_eq!(Some(0), tree.elements[1].1.parent);
+    assert_eq!(vec![3, 5], tree.pages);
+}
+
+#[test]
+fn test_header_cell() {
+    let mut cell =
+        StructElement::new(StructureType::TH).with_scope(TableScope::Column);
+    cell.mcid = Some(2);
+    assert_eq!(
+        "<< /Type /StructElem /S /TH /P 7 0 R /Pg 4 0 R\n   \
+         /A << /O /Table /Scope /Column >>\n   \
+         /K 2 >>",
+        cell.to_pdf(7, 4, &[]),
+    );
+}
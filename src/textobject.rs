@@ -36,17 +36,34 @@ use std::io::{self, Write};
 pub struct TextObject<'a> {
     output: &'a mut Write,
     encoding: Encoding,
+    y_down: bool,
+    uses_rgb: bool,
 }
 
 // Should not be called by user code.
-pub fn create_text_object(output: &mut Write) -> TextObject {
-    TextObject {
+// If `y_down` is true, the user space of the canvas is flipped, so the
+// text matrix and positions are flipped again to keep text upright.
+pub fn create_text_object(
+    output: &mut Write,
+    y_down: bool,
+) -> io::Result<TextObject> {
+    if y_down {
+        writeln!(output, "1 0 0 -1 0 0 Tm")?;
+    }
+    Ok(TextObject {
         output,
         encoding: WIN_ANSI_ENCODING.clone(),
-    }
+        y_down,
+        uses_rgb: false,
+    })
 }
 
 impl<'a> TextObject<'a> {
+    /// True if any DeviceRGB colors were set in this text object.
+    pub(crate) fn uses_rgb(&self) -> bool {
+        self.uses_rgb
+    }
+
     /// Set the font and font-size to be used by the following text
     /// operations.
     pub fn set_font(&mut self, font: &FontRef, size: f32) -> io::Result<()> {
@@ -78,29 +95,61 @@ impl<'a> TextObject<'a> {
     /// Set color for stroking operations.
     pub fn set_stroke_color(&mut self, color: Color) -> io::Result<()> {
         let norm = |c| f32::from(c) / 255.0;
+        if let Color::RGB { .. } = color {
+            self.uses_rgb = true;
+        }
         match color {
             Color::RGB { red, green, blue } => writeln!(
                 self.output,
-                "{} {} {} SC",
+                "{} {} {} RG",
                 norm(red),
                 norm(green),
                 norm(blue),
             ),
             Color::Gray { gray } => writeln!(self.output, "{} G", norm(gray)),
+            Color::CMYK {
+                cyan,
+                magenta,
+                yellow,
+                black,
+            } => writeln!(
+                self.output,
+                "{} {} {} {} K",
+                norm(cyan),
+                norm(magenta),
+                norm(yellow),
+                norm(black),
+            ),
         }
     }
     /// Set color for non-stroking operations.
     pub fn set_fill_color(&mut self, color: Color) -> io::Result<()> {
         let norm = |c| f32::from(c) / 255.0;
+        if let Color::RGB { .. } = color {
+            self.uses_rgb = true;
+        }
         match color {
             Color::RGB { red, green, blue } => writeln!(
                 self.output,
-                "{} {} {} sc",
+                "{} {} {} rg",
                 norm(red),
                 norm(green),
                 norm(blue),
             ),
             Color::Gray { gray } => writeln!(self.output, "{} g", norm(gray)),
+            Color::CMYK {
+                cyan,
+                magenta,
+                yellow,
+                black,
+            } => writeln!(
+                self.output,
+                "{} {} {} {} k",
+                norm(cyan),
+                norm(magenta),
+                norm(yellow),
+                norm(black),
+            ),
         }
     }
 
@@ -111,6 +160,7 @@ impl<'a> TextObject<'a> {
     /// [Canvas::move_to](struct.Canvas.html#method.move_to), after that,
     /// the point is relative to the earlier pos.
     pub fn pos(&mut self, x: f32, y: f32) -> io::Result<()> {
+        let y = if self.y_down { -y } else { y };
         writeln!(self.output, "{} {} Td", x, y)
     }
     /// Show a text.
@@ -0,0 +1,164 @@
+use action::Destination;
+use canvas::Canvas;
+use fontsource::{BuiltinFont, FontSource};
+use rect::Rect;
+use std::io;
+
+/// The style of a table of contents, generated from the document
+/// outline by
+/// [Pdf::insert_table_of_contents](struct.Pdf.html#method.insert_table_of_contents).
+///
+/// Each outline item gets a line with its title, indented by its
+/// level, followed by dot leaders and the number of its page.
+/// The lines are links to the pages.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{BuiltinFont, Pdf, TableOfContents};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// let font = BuiltinFont::Times_Roman;
+/// for title in &["Beginning", "Middle", "End"] {
+///     document.render_page(180.0, 240.0, |canvas| {
+///         canvas.add_outline(title);
+///         canvas.left_text(10.0, 220.0, font, 14.0, title)
+///     }).unwrap();
+/// }
+/// let contents = TableOfContents::new("Contents")
+///     .with_font(BuiltinFont::Times_Roman, 12.0);
+/// document.insert_table_of_contents(0, 180.0, 240.0, &contents).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableOfContents {
+    title: String,
+    title_font: BuiltinFont,
+    title_size: f32,
+    font: BuiltinFont,
+    size: f32,
+    indent: f32,
+    levels: Option<usize>,
+}
+
+/// An entry of a table of contents: the title, the level and the
+/// (final) index of the page.
+pub(crate) type TocEntry = (String, usize, usize);
+
+impl TableOfContents {
+    /// A table of contents with the given heading, with 11 pt
+    /// Helvetica entries.
+    pub fn new(title: &str) -> Self {
+        TableOfContents {
+            title: title.to_string(),
+            title_font: BuiltinFont::Helvetica_Bold,
+            title_size: 18.0,
+            font: BuiltinFont::Helvetica,
+            size: 11.0,
+            indent: 15.0,
+            levels: None,
+        }
+    }
+
+    /// Set the font and font size of the heading.
+    pub fn with_title_font(mut self, font: BuiltinFont, size: f32) -> Self {
+        self.title_font = font;
+        self.title_size = size;
+        self
+    }
+
+    /// Set the font and font size of the entries.
+    pub fn with_font(mut self, font: BuiltinFont, size: f32) -> Self {
+        self.font = font;
+        self.size = size;
+        self
+    }
+
+    /// Set how far each level of entries is indented, in points.
+    pub fn with_indent(mut self, indent: f32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Only list outline items of the first `levels` levels, e.g. 2
+    /// for chapters and sections.
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = Some(levels);
+        self
+    }
+
+    /// True if the outline item at `level` is listed.
+    pub(crate) fn lists(&self, level: usize) -> bool {
+        self.levels.map_or(true, |levels| level < levels)
+    }
+
+    /// The distance between the baselines of the entries.
+    fn leading(&self) -> f32 {
+        1.5 * self.size
+    }
+
+    /// The height of the heading, including the space below it.
+    fn title_height(&self) -> f32 {
+        2.0 * self.title_size
+    }
+
+    /// The number of entries that fit in `area`, on the first page
+    /// (with the heading) or on the following pages.
+    pub(crate) fn entries_per_page(&self, area: Rect, first: bool) -> usize {
+        let mut height = area.height;
+        if first {
+            height -= self.title_height();
+        }
+        (height / self.leading()).max(0.0) as usize
+    }
+
+    /// Draw a page of the table of contents with `entries`, with the
+    /// heading if it is the `first` page.
+    pub(crate) fn draw_page(
+        &self,
+        canvas: &mut Canvas,
+        entries: &[TocEntry],
+        first: bool,
+    ) -> io::Result<()> {
+        let area = canvas.content_box();
+        let mut y = area.top();
+        if first {
+            canvas.left_text(
+                area.x,
+                y - self.title_size,
+                self.title_font,
+                self.title_size,
+                &self.title,
+            )?;
+            y -= self.title_height();
+        }
+        let font = self.font;
+        let size = self.size;
+        // The dots are placed at multiples of `pitch` from the right
+        // edge, so they line up on all lines.
+        let pitch = font.get_width(size, ". ");
+        let dot = font.get_width(size, ".");
+        for &(ref title, level, page) in entries {
+            y -= self.leading();
+            let x = area.x + level as f32 * self.indent;
+            canvas.left_text(x, y, font, size, title)?;
+            let number = (page + 1).to_string();
+            canvas.right_text(area.right(), y, font, size, &number)?;
+            let start = x + font.get_width(size, title) + dot;
+            let end = area.right() - font.get_width(size, &number) - dot;
+            let dots = ((end - start) / pitch).max(0.0) as usize;
+            if dots > 0 {
+                let font_ref = canvas.get_font(font);
+                canvas.text(|t| {
+                    t.set_font(&font_ref, size)?;
+                    t.set_char_spacing(pitch - dot)?;
+                    t.pos(end - dots as f32 * pitch, y)?;
+                    t.show(&".".repeat(dots))
+                })?;
+            }
+            let rect =
+                Rect::new(x, y - 0.3 * size, area.right() - x, 1.2 * size);
+            canvas.link(rect, Destination::page(page));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+/// A transition effect, shown when moving to a page in presentation
+/// (full screen) mode, as described in section 12.4.4 of the PDF
+/// specification.
+///
+/// Set with
+/// [Pdf::set_transition](struct.Pdf.html#method.set_transition).
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Pdf, Transition, TransitionDirection, TransitionStyle};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.set_transition(Some(Transition {
+///     style: TransitionStyle::Wipe(TransitionDirection::TopToBottom),
+///     duration: 0.5,
+/// }));
+/// // This page, and any pages after it, are wiped in.
+/// document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// The effect.
+    pub style: TransitionStyle,
+    /// The duration of the effect, in seconds.
+    pub duration: f32,
+}
+
+/// The effect of a [Transition](struct.Transition.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionStyle {
+    /// Two lines sweep across the screen, horizontally or vertically,
+    /// inward from the edges or outward from the center.
+    Split {
+        /// Whether the lines are vertical.
+        vertical: bool,
+        /// Whether the lines move inward.
+        inward: bool,
+    },
+    /// Multiple lines sweep across the screen, like blinds.
+    Blinds {
+        /// Whether the lines are vertical.
+        vertical: bool,
+    },
+    /// A rectangle sweeps inward from the edges or outward from the
+    /// center.
+    Box {
+        /// Whether the rectangle moves inward.
+        inward: bool,
+    },
+    /// A single line sweeps across the screen in the given direction.
+    Wipe(TransitionDirection),
+    /// The old page dissolves gradually into the new one.
+    Dissolve,
+    /// Like dissolve, but sweeping across the screen in the given
+    /// direction.
+    Glitter(TransitionDirection),
+    /// The new page flies in, in the given direction (or the old page
+    /// flies out, if not `inward`).
+    Fly {
+        /// The direction of the movement.
+        direction: TransitionDirection,
+        /// Whether the new page flies in.
+        inward: bool,
+    },
+    /// The new page pushes the old page off the screen in the given
+    /// direction.
+    Push(TransitionDirection),
+    /// The new page slides on in the given direction, covering the
+    /// old page.
+    Cover(TransitionDirection),
+    /// The old page slides off in the given direction, uncovering the
+    /// new page.
+    Uncover(TransitionDirection),
+    /// The new page fades in, as the old page fades out.
+    Fade,
+}
+
+/// The direction of a [TransitionStyle](enum.TransitionStyle.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionDirection {
+    /// From the left edge to the right.
+    LeftToRight,
+    /// From the bottom edge to the top.
+    BottomToTop,
+    /// From the right edge to the left.
+    RightToLeft,
+    /// From the top edge to the bottom.
+    TopToBottom,
+    /// From the top left corner to the bottom right (only for
+    /// `Glitter`).
+    TopLeftToBottomRight,
+}
+
+impl TransitionDirection {
+    /// The direction as an angle, counterclockwise from left to right.
+    fn angle(self) -> u16 {
+        match self {
+            TransitionDirection::LeftToRight => 0,
+            TransitionDirection::BottomToTop => 90,
+            TransitionDirection::RightToLeft => 180,
+            TransitionDirection::TopToBottom => 270,
+            TransitionDirection::TopLeftToBottomRight => 315,
+        }
+    }
+}
+
+impl Transition {
+    /// The transition as a pdf dictionary.
+    pub(crate) fn to_pdf(self) -> String {
+        let dimension = |vertical: bool| if vertical { "/V" } else { "/H" };
+        let motion = |inward: bool| if inward { "/I" } else { "/O" };
+        let (style, entries) = match self.style {
+            TransitionStyle::Split { vertical, inward } => (
+                "Split",
+                format!(" /Dm {} /M {}", dimension(vertical), motion(inward)),
+            ),
+            TransitionStyle::Blinds { vertical } => {
+                ("Blinds", format!(" /Dm {}", dimension(vertical)))
+            }
+            TransitionStyle::Box { inward } => {
+                ("Box", format!(" /M {}", motion(inward)))
+            }
+            TransitionStyle::Wipe(d) => {
+                ("Wipe", format!(" /Di {}", d.angle()))
+            }
+            TransitionStyle::Dissolve => ("Dissolve", String::new()),
+            TransitionStyle::Glitter(d) => {
+                ("Glitter", format!(" /Di {}", d.angle()))
+            }
+            TransitionStyle::Fly { direction, inward } => (
+                "Fly",
+                format!(" /Di {} /M {}", direction.angle(), motion(inward)),
+            ),
+            TransitionStyle::Push(d) => {
+                ("Push", format!(" /Di {}", d.angle()))
+            }
+            TransitionStyle::Cover(d) => {
+                ("Cover", format!(" /Di {}", d.angle()))
+            }
+            TransitionStyle::Uncover(d) => {
+                ("Uncover", format!(" /Di {}", d.angle()))
+            }
+            TransitionStyle::Fade => ("Fade", String::new()),
+        };
+        format!(
+            "<< /Type /Trans /S /{} /D {}{} >>",
+            style, self.duration, entries,
+        )
+    }
+}
+
+#[test]
+fn test_transition() {
+    let split = Transition {
+        style: TransitionStyle::Split {
+            vertical: true,
+            inward: false,
+        },
+        duration: 1.5,
+    };
+    assert_eq!(
+        "<< /Type /Trans /S /Split /D 1.5 /Dm /V /M /O >>",
+        split.to_pdf(),
+    );
+    let push = Transition {
+        style: TransitionStyle::Push(TransitionDirection::TopToBottom),
+        duration: 1.0,
+    };
+    assert_eq!("<< /Type /Trans /S /Push /D 1 /Di 270 >>", push.to_pdf());
+}
@@ -0,0 +1,112 @@
+/// A length in points (1/72 inch), the unit used by all sizes and
+/// coordinates in pdf.
+///
+/// These types only convert lengths: the page and canvas methods take
+/// plain `f32` sizes and coordinates, which are always in points.
+/// Lengths in other units convert to points, or directly to `f32`,
+/// with `into()` where they are passed.
+/// A number in another unit passed without conversion is not caught,
+/// and is taken as points.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Cm, Inch, Mm, Pdf, Pt};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.render_page(Mm(210.0).into(), Mm(297.0).into(), |canvas| {
+///     canvas.rectangle(
+///         Cm(2.0).into(),
+///         Cm(2.0).into(),
+///         Cm(17.0).into(),
+///         Inch(1.0).into(),
+///     )?;
+///     canvas.stroke()
+/// }).unwrap();
+/// # document.finish().unwrap();
+/// assert_eq!(Pt(72.0), Inch(1.0).into());
+/// assert_eq!(36.0, f32::from(Inch(0.5)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Pt(pub f32);
+
+/// A length in millimeters, see [Pt](struct.Pt.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Mm(pub f32);
+
+/// A length in centimeters, see [Pt](struct.Pt.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Cm(pub f32);
+
+/// A length in inches, see [Pt](struct.Pt.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Inch(pub f32);
+
+const POINTS_PER_INCH: f32 = 72.0;
+const POINTS_PER_MM: f32 = POINTS_PER_INCH / 25.4;
+
+impl From<f32> for Pt {
+    fn from(points: f32) -> Self {
+        Pt(points)
+    }
+}
+
+impl From<Pt> for f32 {
+    fn from(length: Pt) -> Self {
+        length.0
+    }
+}
+
+impl From<Mm> for Pt {
+    fn from(length: Mm) -> Self {
+        Pt(length.0 * POINTS_PER_MM)
+    }
+}
+
+impl From<Mm> for f32 {
+    fn from(length: Mm) -> Self {
+        Pt::from(length).0
+    }
+}
+
+impl From<Cm> for Pt {
+    fn from(length: Cm) -> Self {
+        Pt(length.0 * 10.0 * POINTS_PER_MM)
+    }
+}
+
+impl From<Cm> for f32 {
+    fn from(length: Cm) -> Self {
+        Pt::from(length).0
+    }
+}
+
+impl From<Inch> for Pt {
+    fn from(length: Inch) -> Self {
+        Pt(length.0 * POINTS_PER_INCH)
+    }
+}
+
+impl From<Inch> for f32 {
+    fn from(length: Inch) -> Self {
+        Pt::from(length).0
+    }
+}
+
+impl From<Pt> for Mm {
+    fn from(length: Pt) -> Self {
+        Mm(length.0 / POINTS_PER_MM)
+    }
+}
+
+impl From<Pt> for Inch {
+    fn from(length: Pt) -> Self {
+        Inch(length.0 / POINTS_PER_INCH)
+    }
+}
+
+#[test]
+fn test_units() {
+    assert_eq!(Pt(72.0), Pt::from(Mm(25.4)));
+    assert_eq!(f32::from(Mm(100.0)), f32::from(Cm(10.0)));
+    assert_eq!(Inch(2.0), Inch::from(Pt(144.0)));
+}
@@ -0,0 +1,134 @@
+/// How a viewer should present the document, as described in section
+/// 12.2 of the PDF specification.
+///
+/// Set with
+/// [Pdf::set_viewer_preferences](struct.Pdf.html#method.set_viewer_preferences).
+/// The default values leave everything to the viewer.
+///
+/// # Example
+///
+/// ```
+/// # use pdf_canvas::{Duplex, Pdf, ViewerPreferences};
+/// # let mut document = Pdf::create("foo.pdf").unwrap();
+/// document.set_viewer_preferences(ViewerPreferences {
+///     hide_toolbar: true,
+///     display_doc_title: true,
+///     duplex: Some(Duplex::FlipLongEdge),
+///     ..ViewerPreferences::default()
+/// });
+/// # document.render_page(180.0, 240.0, |_| Ok(())).unwrap();
+/// # document.finish().unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ViewerPreferences {
+    /// Hide the toolbars of the viewer.
+    pub hide_toolbar: bool,
+    /// Hide the menu bar of the viewer.
+    pub hide_menubar: bool,
+    /// Hide the user interface elements of the document window, such
+    /// as scroll bars, leaving only the pages.
+    pub hide_window_ui: bool,
+    /// Resize the document window to fit the first page.
+    pub fit_window: bool,
+    /// Center the document window on the screen.
+    pub center_window: bool,
+    /// Show the title of the document (as set by
+    /// [Pdf::set_title](struct.Pdf.html#method.set_title)) in the
+    /// title bar, instead of the file name.
+    pub display_doc_title: bool,
+    /// Read the pages from right to left, e.g. when showing two pages
+    /// side by side.
+    pub right_to_left: bool,
+    /// Print the pages at their actual size, without scaling them to
+    /// the paper.
+    pub disable_print_scaling: bool,
+    /// Print on both sides of the paper, if the printer supports it.
+    pub duplex: Option<Duplex>,
+}
+
+/// How to print on both sides of the paper, see
+/// [ViewerPreferences](struct.ViewerPreferences.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Duplex {
+    /// Print on one side only.
+    Simplex,
+    /// Flip the paper on its short edge.
+    FlipShortEdge,
+    /// Flip the paper on its long edge.
+    FlipLongEdge,
+}
+
+/// Which panel, if any, a viewer shows with the document when it is
+/// opened, as set by
+/// [Pdf::set_page_mode](struct.Pdf.html#method.set_page_mode).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageMode {
+    /// Show only the pages.
+    UseNone,
+    /// Show the document outline (bookmarks).
+    UseOutlines,
+    /// Show thumbnail images of the pages.
+    UseThumbs,
+    /// Show the pages in full screen mode, without any menus or
+    /// windows.
+    FullScreen,
+    /// Show the optional content (layers) panel.
+    UseOC,
+    /// Show the attached files.
+    UseAttachments,
+}
+
+impl Default for PageMode {
+    fn default() -> Self {
+        PageMode::UseNone
+    }
+}
+
+impl ViewerPreferences {
+    /// The preferences as a pdf dictionary, unless they are all
+    /// default.
+    pub(crate) fn to_pdf(self) -> Option<String> {
+        let mut entries = String::new();
+        let flags = [
+            (self.hide_toolbar, "/HideToolbar"),
+            (self.hide_menubar, "/HideMenubar"),
+            (self.hide_window_ui, "/HideWindowUI"),
+            (self.fit_window, "/FitWindow"),
+            (self.center_window, "/CenterWindow"),
+            (self.display_doc_title, "/DisplayDocTitle"),
+        ];
+        for &(set, key) in &flags {
+            if set {
+                entries.push_str(&format!(" {} true", key));
+            }
+        }
+        if self.right_to_left {
+            entries.push_str(" /Direction /R2L");
+        }
+        if self.disable_print_scaling {
+            entries.push_str(" /PrintScaling /None");
+        }
+        if let Some(duplex) = self.duplex {
+            entries.push_str(&format!(" /Duplex /{:?}", duplex));
+        }
+        if entries.is_empty() {
+            None
+        } else {
+            Some(format!("<<{} >>", entries))
+        }
+    }
+}
+
+#[test]
+fn test_viewer_preferences() {
+    assert_eq!(None, ViewerPreferences::default().to_pdf());
+    let preferences = ViewerPreferences {
+        fit_window: true,
+        duplex: Some(Duplex::Simplex),
+        ..ViewerPreferences::default()
+    };
+    assert_eq!(
+        Some("<< /FitWindow true /Duplex /Simplex >>".to_string()),
+        preferences.to_pdf(),
+    );
+}